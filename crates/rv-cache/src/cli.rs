@@ -21,6 +21,7 @@ pub struct CacheArgs {
     /// Path to the cache directory.
     ///
     /// Defaults to platform-specific cache directory or `~/.cache/rv` on Unix systems.
+    /// Ignored when `--no-cache` (or `RV_NO_CACHE`) is set.
     #[arg(global = true, long, env = "RV_CACHE_DIR")]
     pub cache_dir: Option<Utf8PathBuf>,
 }
@@ -257,7 +257,7 @@ pub trait CleanReporter: Send + Sync {
 pub enum CacheBucket {
     /// Ruby interpreters and metadata.
     ///
-    /// Cache structure: `ruby-v0/<digest(path)>.json`
+    /// Cache structure: `ruby-v0/<digest(path)>.json`, plus HTTP responses in `ruby-v0/http/`
     Ruby,
 }
 
@@ -268,6 +268,13 @@ impl CacheBucket {
         }
     }
 
+    /// A short, unversioned name for this bucket, for showing it to users.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ruby => "ruby",
+        }
+    }
+
     /// Return an iterator over all cache buckets.
     pub fn iter() -> impl Iterator<Item = Self> {
         [Self::Ruby].iter().copied()
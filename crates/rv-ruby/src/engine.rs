@@ -29,6 +29,9 @@ pub enum RubyEngine {
 }
 
 impl RubyEngine {
+    /// Names of the engines rv knows about, i.e. everything but [`RubyEngine::Unknown`].
+    pub const KNOWN: [&'static str; 5] = ["ruby", "jruby", "truffleruby", "mruby", "artichoke"];
+
     /// Get the display name for this implementation
     pub fn name(&self) -> &str {
         match self {
@@ -112,6 +115,15 @@ impl CacheKey for RubyEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[test]
+    fn test_known_engines_round_trip() {
+        for name in RubyEngine::KNOWN {
+            let engine = RubyEngine::from(name);
+            assert!(!matches!(engine, RubyEngine::Unknown(_)));
+            assert_eq!(engine.name(), name);
+        }
+    }
+
     #[test]
     fn test_ruby_engine_from_str() {
         assert_eq!(RubyEngine::from_str("ruby").unwrap(), RubyEngine::Ruby);
@@ -2,7 +2,7 @@ pub mod engine;
 pub mod request;
 pub mod version;
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use rv_cache::{CacheKey, CacheKeyHasher};
 use serde::{Deserialize, Serialize};
 use std::env::{self, home_dir};
@@ -13,9 +13,35 @@ use tracing::instrument;
 use crate::request::RubyRequest;
 use crate::version::RubyVersion;
 
+/// File name of the Ruby interpreter on this platform
+const RUBY_EXECUTABLE: &str = if cfg!(windows) { "ruby.exe" } else { "ruby" };
+
+/// Directory holding the executables of a Ruby or gem installation prefix
+pub fn bin_dir(prefix: &Utf8Path) -> Utf8PathBuf {
+    prefix.join("bin")
+}
+
+/// The user's gem directory for a Ruby `engine` at `version`, like `~/.gem/ruby/3.4.5`
+pub fn gem_home_dir(engine: &str, version: &str) -> Option<Utf8PathBuf> {
+    let home = home_dir()?;
+    home.join(".gem")
+        .join(engine)
+        .join(version)
+        .to_str()
+        .map(Utf8PathBuf::from)
+}
+
+/// Path to the Ruby interpreter inside a Ruby installation prefix
+pub fn ruby_executable(prefix: &Utf8Path) -> Utf8PathBuf {
+    bin_dir(prefix).join(RUBY_EXECUTABLE)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Release {
     pub name: String,
+    /// Git tag of the release. Lists cached by older versions of rv don't have it.
+    #[serde(default)]
+    pub tag_name: Option<String>,
     pub assets: Vec<Asset>,
 }
 
@@ -62,12 +88,14 @@ impl Ruby {
         }
 
         // Check for Ruby executable
-        let ruby_bin = dir.join("bin").join("ruby");
-        if !ruby_bin.exists() {
-            return Err(RubyError::NoRubyExecutable);
+        let validity = installation_validity(&dir);
+        if validity != Validity::Valid {
+            return Err(RubyError::Invalid(validity));
         }
+        let ruby_bin = ruby_executable(&dir);
 
-        let symlink = find_symlink_target(&ruby_bin);
+        // Either the executable or the whole installation may be linked from elsewhere.
+        let symlink = find_symlink_target(&ruby_bin).or_else(|| find_symlink_target(&dir));
 
         // Extract all information from the Ruby executable itself
         let mut ruby = extract_ruby_info(&ruby_bin)?;
@@ -80,7 +108,12 @@ impl Ruby {
 
     /// Check if this Ruby installation is valid
     pub fn is_valid(&self) -> bool {
-        self.executable_path().exists()
+        self.validity() == Validity::Valid
+    }
+
+    /// Like [`Ruby::is_valid`], but says what is wrong with an invalid installation
+    pub fn validity(&self) -> Validity {
+        installation_validity(&self.path)
     }
 
     /// Get display name for this Ruby
@@ -90,11 +123,19 @@ impl Ruby {
 
     /// Get the path to the Ruby executable for display purposes
     pub fn executable_path(&self) -> Utf8PathBuf {
-        self.bin_path().join("ruby")
+        ruby_executable(&self.path)
     }
 
     pub fn bin_path(&self) -> Utf8PathBuf {
-        self.path.join("bin")
+        bin_dir(&self.path)
+    }
+
+    /// Whether `other` is the same installation as `self`.
+    ///
+    /// Only the version and the install path are compared. The other fields, like the key,
+    /// the symlink or the gem root, depend on how the installation was discovered.
+    pub fn same_install(&self, other: &Ruby) -> bool {
+        self.version == other.version && self.path == other.path
     }
 
     pub fn is_active(&self, active_version: &str) -> bool {
@@ -106,17 +147,7 @@ impl Ruby {
     }
 
     pub fn gem_home(&self) -> Option<Utf8PathBuf> {
-        if let Some(home) = home_dir() {
-            Some(
-                home.join(".gem")
-                    .join(self.version.engine.name())
-                    .join(self.version.number())
-                    .to_str()
-                    .map(Utf8PathBuf::from)?,
-            )
-        } else {
-            None
-        }
+        gem_home_dir(self.version.engine.name(), &self.version.number())
     }
 }
 
@@ -152,6 +183,8 @@ pub enum RubyError {
     InvalidPath { path: String },
     #[error("No ruby executable found in bin/ directory")]
     NoRubyExecutable,
+    #[error("Not a usable Ruby installation: {0}")]
+    Invalid(Validity),
     #[error("Running ruby failed with status {0}:\n{1}")]
     RubyFailed(ExitStatus, String),
     #[error("Failed to parse Ruby directory name: {0}")]
@@ -162,6 +195,66 @@ pub enum RubyError {
     RequestError(#[from] crate::request::RequestError),
 }
 
+/// Whether a Ruby installation can be used, and if not, why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Validity {
+    Valid,
+    /// The installation has no `bin/` directory at this path
+    MissingBinDir(Utf8PathBuf),
+    /// The `bin/` directory has no Ruby executable at this path
+    MissingExecutable(Utf8PathBuf),
+    /// The Ruby executable at this path is a symlink to a target that doesn't exist
+    DanglingSymlink {
+        path: Utf8PathBuf,
+        target: Utf8PathBuf,
+    },
+    /// The Ruby executable at this path can't be inspected
+    Unreadable {
+        path: Utf8PathBuf,
+        error: String,
+    },
+}
+
+impl std::fmt::Display for Validity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Valid => write!(f, "valid"),
+            Self::MissingBinDir(path) => write!(f, "missing bin directory {path}"),
+            Self::MissingExecutable(path) => write!(f, "missing ruby executable {path}"),
+            Self::DanglingSymlink { path, target } => {
+                write!(f, "ruby executable {path} links to missing {target}")
+            }
+            Self::Unreadable { path, error } => write!(f, "can't read {path}: {error}"),
+        }
+    }
+}
+
+/// Checks the installation in `dir` the same way [`Ruby::is_valid`] does
+fn installation_validity(dir: &Utf8Path) -> Validity {
+    let executable = ruby_executable(dir);
+    // Resolving the whole chain of links confirms the final target exists.
+    match executable.canonicalize_utf8() {
+        Ok(_) => Validity::Valid,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            let bin_dir = bin_dir(dir);
+            if let Some(target) = find_symlink_target(&executable) {
+                Validity::DanglingSymlink {
+                    path: executable,
+                    target,
+                }
+            } else if bin_dir.is_dir() {
+                Validity::MissingExecutable(executable)
+            } else {
+                Validity::MissingBinDir(bin_dir)
+            }
+        }
+        Err(err) => Validity::Unreadable {
+            path: executable,
+            error: err.to_string(),
+        },
+    }
+}
+
 /// Extract all Ruby information from the executable in a single call
 #[instrument(skip_all)]
 fn extract_ruby_info(ruby_bin: &Utf8PathBuf) -> Result<Ruby, RubyError> {
@@ -172,7 +265,7 @@ fn extract_ruby_info(ruby_bin: &Utf8PathBuf) -> Result<Ruby, RubyError> {
         puts(Object.const_defined?(:RUBY_PLATFORM) ? RUBY_PLATFORM : 'unknown')
         puts(Object.const_defined?(:RbConfig) && RbConfig::CONFIG['host_cpu'] ? RbConfig::CONFIG['host_cpu'] : 'unknown')
         puts(Object.const_defined?(:RbConfig) && RbConfig::CONFIG['host_os'] ? RbConfig::CONFIG['host_os'] : 'unknown')
-        puts(begin; require 'rubygems'; puts Gem.default_dir; rescue ScriptError, NoMethodError; end)
+        puts(begin; require 'rubygems'; Gem.default_dir; rescue ScriptError, NoMethodError; end)
     "#;
 
     let output = Command::new(ruby_bin)
@@ -354,4 +447,80 @@ mod tests {
         assert!(ruby1 < jruby);
         assert!(ruby2 < jruby);
     }
+
+    #[test]
+    fn test_same_install_ignores_discovery_details() {
+        let ruby = Ruby {
+            key: "ruby-3.3.0-macos-aarch64".to_string(),
+            version: RubyVersion::from_str("3.3.0").unwrap(),
+            path: "/opt/rubies/ruby-3.3.0".into(),
+            symlink: None,
+            arch: "aarch64".to_string(),
+            os: "macos".to_string(),
+            gem_root: None,
+        };
+        let aliased = Ruby {
+            key: "ruby-3.3-macos-aarch64".to_string(),
+            symlink: Some("/opt/rubies/3.3".into()),
+            gem_root: Some("/home/user/.gem/ruby/3.3.0".into()),
+            ..ruby.clone()
+        };
+        assert_ne!(ruby, aliased);
+        assert!(ruby.same_install(&aliased));
+
+        let elsewhere = Ruby {
+            path: "/usr/local/rubies/ruby-3.3.0".into(),
+            ..ruby.clone()
+        };
+        assert!(!ruby.same_install(&elsewhere));
+    }
+
+    #[test]
+    fn test_validity_explains_invalid_installations() {
+        let dir = Utf8PathBuf::from_path_buf(env::temp_dir())
+            .unwrap()
+            .join(format!("rv-ruby-validity-{}", std::process::id()));
+        let ruby = Ruby {
+            key: "ruby-3.3.0-macos-aarch64".to_string(),
+            version: RubyVersion::from_str("3.3.0").unwrap(),
+            path: dir.clone(),
+            symlink: None,
+            arch: "aarch64".to_string(),
+            os: "macos".to_string(),
+            gem_root: None,
+        };
+
+        assert_eq!(ruby.validity(), Validity::MissingBinDir(dir.join("bin")));
+        assert!(!ruby.is_valid());
+
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        assert_eq!(
+            ruby.validity(),
+            Validity::MissingExecutable(ruby.executable_path())
+        );
+        assert!(matches!(
+            Ruby::from_dir(dir.clone()),
+            Err(RubyError::Invalid(Validity::MissingExecutable(_)))
+        ));
+
+        #[cfg(unix)]
+        {
+            let target = dir.join("deleted-ruby");
+            std::os::unix::fs::symlink(&target, ruby.executable_path()).unwrap();
+            assert_eq!(
+                ruby.validity(),
+                Validity::DanglingSymlink {
+                    path: ruby.executable_path(),
+                    target,
+                }
+            );
+            std::fs::remove_file(ruby.executable_path()).unwrap();
+        }
+
+        std::fs::write(ruby.executable_path(), "").unwrap();
+        assert_eq!(ruby.validity(), Validity::Valid);
+        assert!(ruby.is_valid());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
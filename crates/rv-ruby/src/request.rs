@@ -34,6 +34,40 @@ pub enum MatchError {
     NotFound(String),
 }
 
+/// The reason a Ruby version does not satisfy a [`RubyRequest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    Engine {
+        requested: RubyEngine,
+        found: RubyEngine,
+    },
+    Part {
+        part: &'static str,
+        requested: String,
+        found: Option<String>,
+    },
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mismatch::Engine { requested, found } => {
+                write!(f, "engine mismatch, {found} != {requested}")
+            }
+            Mismatch::Part {
+                part,
+                requested,
+                found: Some(found),
+            } => write!(f, "{part} {found} != {requested}"),
+            Mismatch::Part {
+                part,
+                requested,
+                found: None,
+            } => write!(f, "{part} missing, expected {requested}"),
+        }
+    }
+}
+
 impl Default for RubyRequest {
     fn default() -> Self {
         RubyRequest {
@@ -56,28 +90,47 @@ impl RubyRequest {
     }
 
     pub fn satisfied_by(&self, ruby: &Ruby) -> bool {
-        let version = &ruby.version;
+        self.mismatch(&ruby.version).is_none()
+    }
 
+    /// Returns the first part of `version` that doesn't satisfy this request, if any.
+    pub fn mismatch(&self, version: &RubyRequest) -> Option<Mismatch> {
         if self.engine != version.engine {
-            return false;
-        }
-        if self.major.is_some() && self.major != version.major {
-            return false;
-        }
-        if self.minor.is_some() && self.minor != version.minor {
-            return false;
-        }
-        if self.patch.is_some() && self.patch != version.patch {
-            return false;
+            return Some(Mismatch::Engine {
+                requested: self.engine.clone(),
+                found: version.engine.clone(),
+            });
         }
-        if self.tiny.is_some() && self.tiny != version.tiny {
-            return false;
+
+        let parts = [
+            ("major", self.major, version.major),
+            ("minor", self.minor, version.minor),
+            ("patch", self.patch, version.patch),
+            ("tiny", self.tiny, version.tiny),
+        ];
+        for (part, requested, found) in parts {
+            if let Some(requested) = requested
+                && Some(requested) != found
+            {
+                return Some(Mismatch::Part {
+                    part,
+                    requested: requested.to_string(),
+                    found: found.map(|found| found.to_string()),
+                });
+            }
         }
-        if self.prerelease.is_some() && self.prerelease != version.prerelease {
-            return false;
+
+        if let Some(requested) = &self.prerelease
+            && Some(requested) != version.prerelease.as_ref()
+        {
+            return Some(Mismatch::Part {
+                part: "prerelease",
+                requested: requested.clone(),
+                found: version.prerelease.clone(),
+            });
         }
 
-        true
+        None
     }
 
     pub fn number(&self) -> String {
@@ -452,4 +505,22 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_mismatch_names_the_failing_part() {
+        let request = RubyRequest::from_str("3.3").unwrap();
+        let mismatch = |version: &str| {
+            request
+                .mismatch(&RubyRequest::from_str(version).unwrap())
+                .map(|mismatch| mismatch.to_string())
+        };
+
+        assert_eq!(mismatch("3.3.5"), None);
+        assert_eq!(mismatch("3.2.1"), Some("minor 2 != 3".to_string()));
+        assert_eq!(mismatch("3"), Some("minor missing, expected 3".to_string()));
+        assert_eq!(
+            mismatch("jruby-3.3.0"),
+            Some("engine mismatch, jruby != ruby".to_string())
+        );
+    }
 }
@@ -1,3 +1,4 @@
 pub mod cache;
+pub mod doctor;
 pub mod ruby;
 pub mod shell;
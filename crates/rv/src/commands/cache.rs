@@ -1,11 +1,15 @@
+use std::collections::BTreeMap;
 use std::io;
 
 use anstream::println;
 use bytesize::ByteSize;
+use camino::{Utf8Path, Utf8PathBuf};
 use clap::{Args, Subcommand};
 use owo_colors::OwoColorize;
-use rv_cache::CleanReporter;
+use rv_cache::{CacheBucket, CleanReporter};
+use serde::Serialize;
 
+use crate::commands::ruby::list::OutputFormat;
 use crate::config::Config;
 
 #[derive(Args)]
@@ -21,13 +25,55 @@ pub enum CacheCommand {
     #[command(about = "Prune all unused entries from the cache")]
     Prune,
     #[command(about = "Show the cache directory")]
-    Dir,
+    Dir {
+        /// Output format, `json` also lists the directory of each cache bucket
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+    #[command(about = "Check cached Ruby interpreters for entries that are no longer valid")]
+    Verify {
+        /// Remove the invalid entries
+        #[arg(long)]
+        prune: bool,
+    },
 }
 
-pub fn cache_dir(config: &Config) -> io::Result<()> {
-    println!("{}", config.cache.root().as_str().cyan());
+/// The cache directory and where each bucket in it lives, for the machine-readable formats.
+#[derive(Serialize)]
+struct CacheDirs<'a> {
+    cache_dir: &'a Utf8Path,
+    buckets: BTreeMap<&'static str, Utf8PathBuf>,
+}
+
+pub fn cache_dir(config: &Config, format: OutputFormat) -> io::Result<()> {
+    let root = config.cache.root();
+    if format.is_human_readable() {
+        println!("{}", root.as_str().cyan());
+        return Ok(());
+    }
+
+    let dirs = CacheDirs {
+        cache_dir: root,
+        buckets: CacheBucket::iter()
+            .map(|bucket| (bucket.name(), config.cache.bucket(bucket)))
+            .collect(),
+    };
+    match format {
+        OutputFormat::Yaml => {
+            serde_yaml::to_writer(io::stdout(), &dirs).map_err(io::Error::other)?
+        }
+        OutputFormat::Ndjson => {
+            serde_json::to_writer(io::stdout(), &dirs)?;
+            println!();
+        }
+        _ => {
+            serde_json::to_writer_pretty(io::stdout(), &dirs)?;
+            println!();
+        }
+    }
     Ok(())
 }
+
 pub fn cache_clean(config: &Config) -> io::Result<()> {
     struct Reporter {}
     impl CleanReporter for Reporter {
@@ -36,21 +82,47 @@ pub fn cache_clean(config: &Config) -> io::Result<()> {
     }
     let removal = config.cache.clear(Box::new(Reporter {}))?;
     let num_bytes_cleaned = ByteSize::b(removal.bytes).display().iec_short();
-    println!(
-        "Removed {} directories, totalling {}",
-        removal.dirs.cyan(),
-        num_bytes_cleaned.cyan()
-    );
+    if !config.quiet {
+        println!(
+            "Removed {} directories, totalling {}",
+            removal.dirs.cyan(),
+            num_bytes_cleaned.cyan()
+        );
+    }
     Ok(())
 }
 
 pub fn cache_prune(config: &Config) -> io::Result<()> {
     let removal = config.cache.prune()?;
     let num_bytes_cleaned = ByteSize::b(removal.bytes).display().iec_short();
-    println!(
-        "Removed {} directories, totalling {}",
-        removal.dirs.cyan(),
-        num_bytes_cleaned.cyan()
-    );
+    if !config.quiet {
+        println!(
+            "Removed {} directories, totalling {}",
+            removal.dirs.cyan(),
+            num_bytes_cleaned.cyan()
+        );
+    }
+    Ok(())
+}
+
+pub fn cache_verify(config: &Config, prune: bool) -> io::Result<()> {
+    let report = config.verify_cached_rubies()?;
+    for invalid in &report.invalid {
+        if prune {
+            fs_err::remove_file(&invalid.entry)?;
+        }
+        if !config.quiet {
+            println!("{} {}", invalid.entry.cyan(), invalid.reason);
+        }
+    }
+
+    if !config.quiet {
+        let action = if prune { "removed" } else { "found" };
+        println!(
+            "Checked {} cached interpreters, {action} {} invalid",
+            report.checked.cyan(),
+            report.invalid.len().cyan()
+        );
+    }
     Ok(())
 }
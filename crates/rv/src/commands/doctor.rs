@@ -0,0 +1,154 @@
+use std::fmt::Display;
+
+use anstream::println;
+use camino::Utf8Path;
+use owo_colors::OwoColorize;
+
+use crate::config::Config;
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum Error {
+    #[error("{0} check(s) failed")]
+    ChecksFailed(usize),
+}
+
+type Result<T> = miette::Result<T, Error>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+struct Check {
+    status: Status,
+    message: String,
+}
+
+impl Check {
+    fn new(status: Status, message: impl Display) -> Self {
+        Self {
+            status,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Checks the configuration for common problems and prints a checklist of the results.
+pub fn doctor(config: &Config) -> Result<()> {
+    let mut checks = check_ruby_dirs(config);
+    checks.push(check_cache_dir(config.cache.root()));
+    checks.push(check_shell_hook());
+    checks.push(check_exe_on_path(&config.current_exe));
+
+    for check in &checks {
+        let marker = match check.status {
+            Status::Pass => "[pass]".green().to_string(),
+            Status::Warn => "[warn]".yellow().to_string(),
+            Status::Fail => "[fail]".red().to_string(),
+        };
+        println!("{marker} {}", check.message);
+    }
+
+    let failures = checks
+        .iter()
+        .filter(|check| check.status == Status::Fail)
+        .count();
+    if failures > 0 {
+        return Err(Error::ChecksFailed(failures));
+    }
+    Ok(())
+}
+
+fn check_ruby_dirs(config: &Config) -> Vec<Check> {
+    if config.ruby_dirs.is_empty() {
+        return vec![Check::new(
+            Status::Fail,
+            "No Ruby directories are configured",
+        )];
+    }
+
+    let mut checks: Vec<Check> = config
+        .ruby_dirs
+        .iter()
+        .map(|dir| {
+            if !dir.exists() {
+                Check::new(
+                    Status::Warn,
+                    format_args!("Ruby directory {} does not exist", dir.cyan()),
+                )
+            } else if let Err(err) = dir.read_dir_utf8() {
+                Check::new(
+                    Status::Fail,
+                    format_args!("Ruby directory {} is not readable: {err}", dir.cyan()),
+                )
+            } else {
+                Check::new(
+                    Status::Pass,
+                    format_args!("Ruby directory {} is readable", dir.cyan()),
+                )
+            }
+        })
+        .collect();
+
+    if config.ruby_dirs.iter().all(|dir| !dir.exists()) {
+        checks.push(Check::new(
+            Status::Warn,
+            "None of the Ruby directories exist yet, try 'rv ruby install <version>'",
+        ));
+    }
+    checks
+}
+
+fn check_cache_dir(cache_dir: &Utf8Path) -> Check {
+    let probe = cache_dir.join(".rv-doctor");
+    let writable = fs_err::create_dir_all(cache_dir)
+        .and_then(|_| fs_err::write(&probe, b""))
+        .and_then(|_| fs_err::remove_file(&probe));
+
+    match writable {
+        Ok(()) => Check::new(
+            Status::Pass,
+            format_args!("Cache directory {} is writable", cache_dir.cyan()),
+        ),
+        Err(err) => Check::new(
+            Status::Fail,
+            format_args!(
+                "Cache directory {} is not writable: {err}",
+                cache_dir.cyan()
+            ),
+        ),
+    }
+}
+
+fn check_shell_hook() -> Check {
+    // Evaluating `rv shell env` sets RUBY_ROOT whenever a Ruby is active.
+    if std::env::var_os("RUBY_ROOT").is_some() {
+        Check::new(Status::Pass, "Shell integration appears to be installed")
+    } else {
+        Check::new(
+            Status::Warn,
+            "Shell integration does not appear to be installed, see 'rv shell init --help'",
+        )
+    }
+}
+
+fn check_exe_on_path(current_exe: &Utf8Path) -> Check {
+    let on_path = current_exe.parent().is_some_and(|exe_dir| {
+        std::env::var_os("PATH")
+            .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir == exe_dir))
+    });
+
+    if on_path {
+        Check::new(
+            Status::Pass,
+            format_args!("{} is on your PATH", current_exe.cyan()),
+        )
+    } else {
+        Check::new(
+            Status::Warn,
+            format_args!("{} is not on your PATH", current_exe.cyan()),
+        )
+    }
+}
@@ -1,16 +1,21 @@
 use clap::{Args, Subcommand};
 
-use crate::commands::ruby::list::OutputFormat;
+use crate::commands::ruby::install::InstallArgs;
+use crate::commands::ruby::list::{ListArgs, OutputFormat};
 use rv_ruby::request::RubyRequest;
 
+pub mod current;
 pub mod dir;
 pub mod find;
+pub mod info;
 pub mod install;
 pub mod list;
 pub mod pin;
+pub mod reshim;
 #[cfg(unix)]
 pub mod run;
 pub mod uninstall;
+pub mod upgrade;
 
 #[derive(Args)]
 pub struct RubyArgs {
@@ -21,43 +26,73 @@ pub struct RubyArgs {
 #[derive(Subcommand)]
 pub enum RubyCommand {
     #[command(about = "List the available Ruby installations")]
-    List {
-        /// Output format for the Ruby list
-        #[arg(long, value_enum, default_value = "text")]
-        format: OutputFormat,
-
-        /// Show only installed Ruby versions
-        #[arg(long)]
-        installed_only: bool,
-    },
+    List(ListArgs),
 
     #[command(about = "Show or set the Ruby version for the current project")]
     Pin {
         /// The Ruby version to pin
         version_request: Option<String>,
+
+        /// Remove the pinned Ruby version
+        #[arg(long, conflicts_with = "version_request")]
+        unset: bool,
+
+        /// Show the installed rubies the version would resolve to, without pinning it
+        #[arg(long, requires = "version_request")]
+        list_candidates: bool,
     },
 
     #[command(about = "Show the Ruby installation directory")]
     Dir,
 
-    #[command(about = "Search for a Ruby installation")]
+    #[command(about = "Show the active Ruby for the current project")]
+    Current {
+        /// Output format for the active Ruby
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    #[command(
+        about = "Search for a Ruby installation",
+        after_help = "Exits with status 0 when a matching Ruby is found, 5 when no installed Ruby \
+                      matches the request, 2 for invalid arguments, 3 for an invalid \
+                      configuration, and 1 for any other error."
+    )]
     Find {
         /// Ruby version to find
         request: Option<RubyRequest>,
+
+        /// Explain on stderr why each installed Ruby does or doesn't match the request
+        #[arg(long)]
+        explain: bool,
+
+        /// Print every installed Ruby that matches the request, newest first
+        #[arg(long)]
+        all: bool,
+
+        /// Output format, `json` prints the whole Ruby rather than its executable
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    #[command(about = "Show details about an installed Ruby")]
+    Info {
+        /// Ruby version to describe, defaults to the project's Ruby
+        request: Option<RubyRequest>,
+
+        /// Output format, `json` prints the whole Ruby
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
     },
 
     #[command(about = "Install a Ruby version")]
-    Install {
-        /// Directory to install into
-        #[arg(short, long, value_name = "DIR")]
-        install_dir: Option<String>,
-
-        /// Ruby version to install
-        version: RubyRequest,
+    Install(InstallArgs),
 
-        /// Path to a local ruby tarball
-        #[arg(long, value_name = "TARBALL_PATH")]
-        tarball_path: Option<String>,
+    #[command(about = "Upgrade the project's Ruby to the latest patch of its minor version")]
+    Upgrade {
+        /// Install the latest patch of every installed minor version, without changing the pin
+        #[arg(long)]
+        all: bool,
     },
 
     #[command(about = "Uninstall a Ruby version")]
@@ -66,11 +101,19 @@ pub enum RubyCommand {
         version: RubyRequest,
     },
 
+    #[command(about = "Probe all Ruby installations again, refreshing their cached information")]
+    Reshim,
+
     #[cfg(unix)]
     #[command(about = "Run a specific Ruby", dont_delimit_trailing_values = true)]
     Run {
-        /// Ruby version to run
-        version: RubyRequest,
+        /// Ruby version to run, defaults to the project's pinned version
+        #[arg(conflicts_with = "version_flag")]
+        version: Option<RubyRequest>,
+
+        /// Ruby version to run, for use when the arguments come after a `--`
+        #[arg(long = "version", id = "version_flag", value_name = "VERSION")]
+        version_flag: Option<RubyRequest>,
 
         /// Arguments passed to the `ruby` invocation
         #[arg(last = true, allow_hyphen_values = true)]
@@ -0,0 +1,41 @@
+use std::io;
+
+use anstream::println;
+
+use crate::commands::ruby::list::OutputFormat;
+use crate::config::Config;
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum Error {
+    #[error("no active ruby")]
+    NoActiveRuby,
+    #[error("Failed to serialize JSON output")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Failed to serialize YAML output")]
+    YamlError(#[from] serde_yaml::Error),
+}
+
+type Result<T> = miette::Result<T, Error>;
+
+/// Print the Ruby that is active for the current project, e.g. `ruby-3.3.0`.
+///
+/// Kept to a single line so it can be used in shell prompts.
+pub fn current(config: &Config, format: OutputFormat) -> Result<()> {
+    let Some(ruby) = config.project_ruby() else {
+        return Err(Error::NoActiveRuby);
+    };
+
+    match format {
+        OutputFormat::Text | OutputFormat::Table => println!("{}", ruby.display_name()),
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(io::stdout(), &ruby)?;
+            println!();
+        }
+        OutputFormat::Yaml => serde_yaml::to_writer(io::stdout(), &ruby)?,
+        OutputFormat::Ndjson => {
+            serde_json::to_writer(io::stdout(), &ruby)?;
+            println!();
+        }
+    }
+    Ok(())
+}
@@ -1,9 +1,11 @@
 use std::borrow::Cow;
+use std::io;
 
-use anstream::println;
+use anstream::{eprintln, println};
 use owo_colors::OwoColorize;
 use rv_ruby::request::RubyRequest;
 
+use crate::commands::ruby::list::OutputFormat;
 use crate::config::Config;
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
@@ -12,20 +14,81 @@ pub enum Error {
     NoMatchingRuby,
     #[error(transparent)]
     ConfigError(#[from] crate::config::Error),
+    #[error("Failed to serialize JSON output")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Failed to serialize YAML output")]
+    YamlError(#[from] serde_yaml::Error),
 }
 
 type Result<T> = miette::Result<T, Error>;
 
-pub fn find(config: &Config, request: &Option<RubyRequest>) -> Result<()> {
+/// Exit status of `rv ruby find` when the request is valid but no installed Ruby matches it.
+///
+/// Not 2, which clap exits with for invalid arguments.
+pub const NOT_FOUND_EXIT_CODE: i32 = 5;
+
+/// Prints the executable of the Ruby that best matches `request`, or of every matching Ruby
+/// newest first with `all`.
+pub fn find(
+    config: &Config,
+    request: &Option<RubyRequest>,
+    explain: bool,
+    all: bool,
+    format: OutputFormat,
+) -> Result<()> {
     let request = if let Some(request) = request {
         Cow::Borrowed(request)
     } else {
         Cow::Owned(config.ruby_request()?)
     };
-    if let Some(ruby) = config.matching_ruby(&request) {
-        println!("{}", ruby.executable_path().cyan());
-        Ok(())
+    if explain {
+        explain_request(config, &request);
+    }
+
+    let rubies = if all {
+        config.matching_rubies(&request)
     } else {
-        Err(Error::NoMatchingRuby)
+        config.matching_ruby(&request).into_iter().collect()
+    };
+    if rubies.is_empty() {
+        return Err(Error::NoMatchingRuby);
+    }
+
+    match format {
+        OutputFormat::Text | OutputFormat::Table => {
+            for ruby in &rubies {
+                println!("{}", ruby.executable_path().cyan());
+            }
+        }
+        OutputFormat::Json if all => {
+            serde_json::to_writer_pretty(io::stdout(), &rubies)?;
+            println!();
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(io::stdout(), &rubies[0])?;
+            println!();
+        }
+        OutputFormat::Yaml if all => serde_yaml::to_writer(io::stdout(), &rubies)?,
+        OutputFormat::Yaml => serde_yaml::to_writer(io::stdout(), &rubies[0])?,
+        OutputFormat::Ndjson => {
+            for ruby in &rubies {
+                serde_json::to_writer(io::stdout(), ruby)?;
+                println!();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prints, for every installed Ruby, whether it satisfies `request` and why not.
+///
+/// This goes to stderr so the path printed on stdout stays usable from scripts.
+fn explain_request(config: &Config, request: &RubyRequest) {
+    eprintln!("Matching {} against installed rubies:", request.cyan());
+    for ruby in config.rubies() {
+        match request.mismatch(&ruby.version) {
+            None => eprintln!("  {} {}", ruby.display_name(), "yes".green()),
+            Some(mismatch) => eprintln!("  {} {} ({mismatch})", ruby.display_name(), "no".red()),
+        }
     }
 }
@@ -0,0 +1,96 @@
+use std::borrow::Cow;
+use std::io;
+
+use anstream::println;
+use camino::Utf8Path;
+use owo_colors::OwoColorize;
+use rv_ruby::Ruby;
+use rv_ruby::request::RubyRequest;
+
+use crate::commands::ruby::list::OutputFormat;
+use crate::config::Config;
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum Error {
+    #[error("no matching ruby version found")]
+    NoMatchingRuby,
+    #[error(transparent)]
+    ConfigError(#[from] crate::config::Error),
+    #[error("Failed to serialize JSON output")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Failed to serialize YAML output")]
+    YamlError(#[from] serde_yaml::Error),
+}
+
+type Result<T> = miette::Result<T, Error>;
+
+/// Prints everything rv knows about the Ruby that best matches `request`, or about the
+/// project's Ruby without one.
+pub fn info(config: &Config, request: &Option<RubyRequest>, format: OutputFormat) -> Result<()> {
+    let request = if let Some(request) = request {
+        Cow::Borrowed(request)
+    } else {
+        Cow::Owned(config.ruby_request()?)
+    };
+    let Some(ruby) = config.matching_ruby(&request) else {
+        return Err(Error::NoMatchingRuby);
+    };
+
+    match format {
+        OutputFormat::Text | OutputFormat::Table => {
+            let active = config
+                .project_ruby()
+                .is_some_and(|project_ruby| project_ruby.same_install(&ruby));
+            print_report(&ruby, active);
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(io::stdout(), &ruby)?;
+            println!();
+        }
+        OutputFormat::Yaml => serde_yaml::to_writer(io::stdout(), &ruby)?,
+        OutputFormat::Ndjson => {
+            serde_json::to_writer(io::stdout(), &ruby)?;
+            println!();
+        }
+    }
+    Ok(())
+}
+
+fn print_report(ruby: &Ruby, active: bool) {
+    fn path_or_none(path: Option<&Utf8Path>) -> String {
+        path.map_or_else(
+            || "none".dimmed().to_string(),
+            |path| path.cyan().to_string(),
+        )
+    }
+
+    println!("{:<11} {}", "Engine:", ruby.version.engine.name());
+    println!("{:<11} {}", "Version:", ruby.version.number());
+    println!("{:<11} {}-{}", "Platform:", ruby.arch, ruby.os);
+    println!("{:<11} {}", "Path:", ruby.path.cyan());
+    println!("{:<11} {}", "Executable:", ruby.executable_path().cyan());
+    println!(
+        "{:<11} {}",
+        "Symlink:",
+        path_or_none(ruby.symlink.as_deref())
+    );
+    println!(
+        "{:<11} {}",
+        "Gem root:",
+        path_or_none(ruby.gem_root().as_deref())
+    );
+    println!(
+        "{:<11} {}",
+        "Gem home:",
+        path_or_none(ruby.gem_home().as_deref())
+    );
+    println!(
+        "{:<11} {}",
+        "Active:",
+        if active {
+            "yes".green().to_string()
+        } else {
+            "no".to_string()
+        }
+    );
+}
@@ -1,26 +1,45 @@
-use anstream::println;
-use camino::{Utf8Path, Utf8PathBuf};
+use anstream::{println, stream::IsTerminal};
+use bytesize::ByteSize;
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use clap::builder::PossibleValuesParser;
 use core::panic;
 use current_platform::CURRENT_PLATFORM;
 use futures_util::StreamExt;
+use indicatif::ProgressStyle;
 use owo_colors::OwoColorize;
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tar::EntryType;
 use tokio::io::AsyncWriteExt;
+use tracing::{Instrument, Span, debug, info_span, warn};
+use tracing_indicatif::span_ext::IndicatifSpanExt;
 
-use rv_ruby::request::RubyRequest;
+use rv_ruby::{
+    Ruby,
+    engine::RubyEngine,
+    request::{RubyRequest, VersionPart},
+};
 
+use super::list::{
+    self, available_rubies_for_platform, fetch_available_rubies, latest_available_rubies,
+};
 use crate::config::Config;
+use crate::signature::{self, PublicKey};
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
 pub enum Error {
     #[error(transparent)]
     ReqwestError(#[from] reqwest::Error),
     #[error(transparent)]
+    HttpError(#[from] crate::http::Error),
+    #[error(transparent)]
     IoError(#[from] std::io::Error),
     #[error(transparent)]
     StripPrefixError(#[from] std::path::StripPrefixError),
-    #[error("Major, minor, and patch version is required, but got {0}")]
-    IncompleteVersion(RubyRequest),
     #[error("Download from URL {url} failed with status code {status}. Response body was {body}")]
     DownloadFailed {
         url: String,
@@ -28,21 +47,131 @@ pub enum Error {
         body: String,
     },
     #[error("Could not get latest Ruby release")]
-    GetLatestReleaseFailed { error: super::list::Error },
+    GetLatestReleaseFailed {
+        #[source]
+        error: super::list::Error,
+    },
+    #[error("No available Ruby matches {requested}, the nearest available versions are: {nearest}")]
+    NoMatchingRelease {
+        requested: RubyRequest,
+        nearest: String,
+    },
     #[error("Failed to unpack tarball path {0}")]
     InvalidTarballPath(PathBuf),
+    #[error("Refusing to unpack {0} through a symlink from the tarball")]
+    SymlinkInTarballPath(Utf8PathBuf),
+    #[error("{0} is not in the cache, and rv is offline")]
+    Offline(String),
+    #[error("{0} already exists, use --force to overwrite it")]
+    AlreadyInstalled(Utf8PathBuf),
+    #[error("{0} can't be used as an alias, it must be the name of a directory")]
+    InvalidAlias(String),
+    #[error("{0} already exists and isn't an alias, remove it to use that alias")]
+    AliasExists(Utf8PathBuf),
     #[error("rv does not (yet) support your platform ({0}). Sorry :(")]
     UnsupportedPlatform(&'static str),
+    #[error("rv can only download ruby, not {0}")]
+    UnsupportedEngine(RubyEngine),
+    #[error("Could not tell the Ruby version from the file name of {0}, please pass the version")]
+    UnknownTarballVersion(Utf8PathBuf),
+    #[error("Checksum of {path} is {actual}, but {expected} was expected")]
+    ChecksumMismatch {
+        path: Utf8PathBuf,
+        expected: String,
+        actual: String,
+    },
+    #[error(transparent)]
+    SignatureError(#[from] signature::Error),
+    #[error("Could not start threads to extract with")]
+    ThreadPoolError(#[from] rayon::ThreadPoolBuildError),
 }
 
 type Result<T> = miette::Result<T, Error>;
 
-pub async fn install(
-    config: &Config,
-    install_dir: Option<String>,
-    requested: RubyRequest,
-    tarball_path: Option<String>,
-) -> Result<()> {
+const GITHUB_RELEASES_URL: &str = "https://github.com/spinel-coop/rv-ruby/releases";
+
+#[derive(clap::Args, Default)]
+pub struct InstallArgs {
+    /// Directory to install into
+    #[arg(short, long, value_name = "DIR")]
+    pub install_dir: Option<String>,
+
+    /// Ruby version to install, e.g. `3.4.5`, or `3.4` for the latest 3.4 patch
+    #[arg(required_unless_present = "from_file")]
+    pub version: Option<RubyRequest>,
+
+    /// Path to a local ruby tarball
+    #[arg(long, value_name = "TARBALL_PATH")]
+    pub tarball_path: Option<String>,
+
+    /// Install from a release tarball on disk, like `ruby-3.4.5.arm64_linux.tar.gz`, without
+    /// using the network. The version comes from the file name unless given, and a
+    /// `<file>.sha256` next to it is checked if present.
+    #[arg(long, value_name = "PATH", conflicts_with = "tarball_path")]
+    pub from_file: Option<Utf8PathBuf>,
+
+    /// Overwrite an existing installation
+    #[arg(long)]
+    pub force: bool,
+
+    /// Download from this mirror instead of GitHub releases
+    #[arg(long, env = "RV_DOWNLOAD_MIRROR", value_name = "URL")]
+    pub download_mirror: Option<String>,
+
+    /// Number of threads writing files while extracting [default: number of CPUs]
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Verify the tarball's minisign signature, `<tarball>.minisig`, before extracting it.
+    /// Only legacy signatures made with `minisign -S -l` are supported.
+    #[arg(long, requires = "signature_key")]
+    pub verify_signature: bool,
+
+    /// Minisign public key to verify signatures with, or the contents of its `.pub` file
+    #[arg(long, env = "RV_SIGNATURE_KEY", value_name = "KEY")]
+    pub signature_key: Option<PublicKey>,
+
+    /// Also make a symlink with this name next to the install, like `current` or `3.3`,
+    /// replacing an earlier alias of the same name
+    #[arg(long, value_name = "NAME")]
+    pub alias: Option<String>,
+
+    /// Download the Ruby built for this platform instead of the host's. Rubies for other
+    /// platforms go into `<install-dir>-<platform>`, as they can't run here
+    #[arg(
+        long,
+        value_name = "PLATFORM",
+        value_parser = PossibleValuesParser::new(list::PLATFORMS),
+        conflicts_with_all = ["tarball_path", "from_file"]
+    )]
+    pub platform: Option<String>,
+}
+
+pub async fn install(config: &Config, args: InstallArgs) -> Result<()> {
+    let InstallArgs {
+        install_dir,
+        version: requested,
+        tarball_path,
+        from_file,
+        force,
+        download_mirror,
+        jobs,
+        verify_signature,
+        signature_key,
+        alias,
+        platform,
+    } = args;
+    // The key is only used when asked to verify, it may just be set in the environment.
+    let signature_key = signature_key.filter(|_| verify_signature);
+
+    if let Some(alias) = &alias {
+        validate_alias(alias)?;
+    }
+    // Asking for the host's own platform is just a normal install.
+    let platform = platform.filter(|platform| host_platform().ok() != Some(platform.as_str()));
+    let jobs = jobs
+        .or_else(|| std::thread::available_parallelism().ok())
+        .unwrap_or(NonZeroUsize::MIN);
     let install_dir = match install_dir {
         Some(dir) => Utf8PathBuf::from(dir),
         None => match config.ruby_dirs.first() {
@@ -50,34 +179,293 @@ pub async fn install(
             None => panic!("No Ruby directories to install into"),
         },
     };
+    // Keep rubies that can't run here out of the Ruby directories, so they're never picked.
+    let install_dir = match &platform {
+        Some(platform) => Utf8PathBuf::from(format!("{install_dir}-{platform}")),
+        None => install_dir,
+    };
+
+    if let Some(from_file) = from_file {
+        let requested = match requested {
+            Some(requested) => requested,
+            None => list::version_from_asset_name(from_file.file_name().unwrap_or_default())
+                .ok()
+                .filter(|version| version.major.is_some())
+                .ok_or_else(|| Error::UnknownTarballVersion(from_file.clone()))?,
+        };
+        verify_sidecar_checksum(&from_file)?;
+        if let Some(key) = &signature_key {
+            verify_tarball_signature(&from_file, key)?;
+        }
+        extract_ruby_tarball(&from_file, &install_dir, &requested.number(), force, jobs)?;
+        print_installed(config, &requested, &install_dir);
+        if let Some(alias) = alias {
+            link_alias(config, &install_dir, &requested, &alias)?;
+        }
+        return Ok(());
+    }
+
+    // Clap only lets the version be left out together with `--from-file`.
+    let requested = requested.expect("a version to install");
+    if tarball_path.is_none() && requested.engine != RubyEngine::Ruby {
+        return Err(Error::UnsupportedEngine(requested.engine));
+    }
+    let requested = if tarball_path.is_none() && requested.patch.is_none() {
+        resolve_partial_request(config, &requested, platform.as_deref()).await?
+    } else {
+        requested
+    };
+    if let Some(platform) = &platform {
+        warn!(
+            "Ruby {requested} for {platform} won't run on this host, installing it to {install_dir}"
+        );
+    }
 
     match tarball_path {
         Some(tarball_path) => {
-            extract_local_ruby_tarball(tarball_path, &install_dir, &requested.number()).await?
+            if let Some(key) = &signature_key {
+                verify_tarball_signature(Utf8Path::new(&tarball_path), key)?;
+            }
+            extract_local_ruby_tarball(tarball_path, &install_dir, &requested.number(), force, jobs)
+                .await?
+        }
+        None => {
+            let download = Download {
+                mirror: download_mirror.as_deref(),
+                signature_key: signature_key.as_ref(),
+                platform: platform.as_deref(),
+            };
+            download_and_extract_remote_tarball(
+                config,
+                &install_dir,
+                &requested,
+                force,
+                jobs,
+                download,
+            )
+            .await?
         }
-        None => download_and_extract_remote_tarball(config, &install_dir, &requested).await?,
     }
 
-    println!(
-        "Installed Ruby version {} to {}",
-        requested.to_string().cyan(),
-        install_dir.cyan()
+    print_installed(config, &requested, &install_dir);
+    if let Some(alias) = alias {
+        link_alias(config, &install_dir, &requested, &alias)?;
+    }
+    Ok(())
+}
+
+fn print_installed(config: &Config, requested: &RubyRequest, install_dir: &Utf8Path) {
+    if !config.quiet {
+        println!(
+            "Installed Ruby version {} to {}",
+            requested.to_string().cyan(),
+            install_dir.cyan()
+        );
+    }
+}
+
+/// Aliases are created next to the installs, so they must be a single path component.
+fn validate_alias(alias: &str) -> Result<()> {
+    let mut components = Utf8Path::new(alias).components();
+    match (components.next(), components.next()) {
+        (Some(Utf8Component::Normal(_)), None) => Ok(()),
+        _ => Err(Error::InvalidAlias(alias.to_owned())),
+    }
+}
+
+/// Points the symlink `alias` in `install_dir` at the install of `installed`, replacing an
+/// earlier alias of the same name. The link is relative, so it survives moving `install_dir`.
+fn link_alias(
+    config: &Config,
+    install_dir: &Utf8Path,
+    installed: &RubyRequest,
+    alias: &str,
+) -> Result<()> {
+    let link = install_dir.join(alias);
+    let target = format!("ruby-{}", installed.number());
+    match link.symlink_metadata() {
+        Ok(metadata) if metadata.is_symlink() => remove_symlink(&link)?,
+        Ok(_) => return Err(Error::AliasExists(link)),
+        Err(_) => {}
+    }
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&target, &link)?;
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_dir(&target, &link)?;
+
+    if !config.quiet {
+        println!("Aliased {} to {}", alias.cyan(), target.cyan());
+    }
+    Ok(())
+}
+
+/// Directory symlinks are directories on Windows, and have to be removed as one.
+fn remove_symlink(link: &Utf8Path) -> std::io::Result<()> {
+    #[cfg(windows)]
+    return std::fs::remove_dir(link);
+    #[cfg(not(windows))]
+    std::fs::remove_file(link)
+}
+
+/// Checks `tarball_path` against the SHA-256 digest in `<tarball_path>.sha256`, if there is one.
+///
+/// The sidecar may be in the `sha256sum` format, only the first word in it is used.
+fn verify_sidecar_checksum(tarball_path: &Utf8Path) -> Result<()> {
+    let sidecar = Utf8PathBuf::from(format!("{tarball_path}.sha256"));
+    let Ok(content) = std::fs::read_to_string(&sidecar) else {
+        debug!("No checksum file at {sidecar}, skipping verification");
+        return Ok(());
+    };
+    let expected = content
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(tarball_path)?, &mut hasher)?;
+    let actual = format!("{:x}", hasher.finalize());
+
+    if actual != expected {
+        return Err(Error::ChecksumMismatch {
+            path: tarball_path.to_owned(),
+            expected,
+            actual,
+        });
+    }
+    debug!("Verified {tarball_path} against {sidecar}");
+    Ok(())
+}
+
+/// Where the minisign signature of `tarball_path` is expected.
+fn signature_path(tarball_path: &Utf8Path) -> Utf8PathBuf {
+    Utf8PathBuf::from(format!("{tarball_path}.minisig"))
+}
+
+/// Checks `tarball_path` against its minisign signature, `<tarball_path>.minisig`.
+fn verify_tarball_signature(tarball_path: &Utf8Path, key: &PublicKey) -> Result<()> {
+    signature::verify_file(tarball_path, &signature_path(tarball_path), key)?;
+    debug!(
+        "Verified the signature of {tarball_path} made with key {}",
+        key.id()
     );
+    Ok(())
+}
 
+/// Downloads the minisign signature at `url` to `path`.
+async fn download_signature(config: &Config, url: &str, path: &Utf8Path) -> Result<()> {
+    let response = config
+        .http_client()?
+        .get(url)
+        .send()
+        .await
+        .map_err(|err| crate::http::request_error(err, url))?;
+    let status = response.status();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|err| crate::http::request_error(err, url))?;
+    if !status.is_success() {
+        return Err(Error::DownloadFailed {
+            url: url.to_owned(),
+            status,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        });
+    }
+    std::fs::write(path, body)?;
     Ok(())
 }
 
+/// Resolves a request without a patch version, like `3.3`, to the newest matching
+/// Ruby available for `platform`, or this platform if that's not given.
+pub(crate) async fn resolve_partial_request(
+    config: &Config,
+    requested: &RubyRequest,
+    platform: Option<&str>,
+) -> Result<RubyRequest> {
+    let mut release = fetch_available_rubies(config, None)
+        .await
+        .map_err(|error| Error::GetLatestReleaseFailed { error })?;
+    // Like `ruby list`, only settle on a prerelease when one was asked for.
+    if requested.prerelease.is_none() {
+        list::drop_prereleases(&mut release);
+    }
+    let available = match platform {
+        Some(platform) => available_rubies_for_platform(&release, platform),
+        None => latest_available_rubies(&release),
+    };
+
+    let Some(ruby) = available
+        .iter()
+        .filter(|ruby| requested.satisfied_by(ruby))
+        .max_by(|a, b| a.version.cmp(&b.version))
+    else {
+        return Err(Error::NoMatchingRelease {
+            requested: requested.clone(),
+            nearest: nearest_versions(requested, &available),
+        });
+    };
+    debug!("Resolved {requested} to {}", ruby.version);
+    Ok(ruby.version.clone())
+}
+
+/// The (up to) three available versions of the requested engine closest to `requested`,
+/// newest first.
+fn nearest_versions(requested: &RubyRequest, available: &[Ruby]) -> String {
+    let distance = |part: Option<VersionPart>, other: Option<VersionPart>| match (part, other) {
+        (Some(part), Some(other)) => part.abs_diff(other),
+        _ => 0,
+    };
+    let mut candidates: Vec<&RubyRequest> = available
+        .iter()
+        .map(|ruby| &ruby.version)
+        .filter(|version| version.engine == requested.engine)
+        .collect();
+    candidates.sort_by_key(|version| {
+        (
+            distance(requested.major, version.major),
+            distance(requested.minor, version.minor),
+        )
+    });
+    candidates.truncate(3);
+    candidates.sort_by(|a, b| b.cmp(a));
+
+    if candidates.is_empty() {
+        return "none".to_owned();
+    }
+    candidates
+        .iter()
+        .map(|version| version.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Where a release tarball is downloaded from, and how it's checked.
+struct Download<'a> {
+    /// Replaces GitHub releases in the download URL, see [`mirrored_url`].
+    mirror: Option<&'a str>,
+    /// Verifies the tarball's minisign signature, when given.
+    signature_key: Option<&'a PublicKey>,
+    /// The platform the Ruby is built for, the host's if `None`.
+    platform: Option<&'a str>,
+}
+
 // downloads and extracts a remote ruby tarball
 async fn download_and_extract_remote_tarball(
     config: &Config,
     install_dir: &Utf8PathBuf,
     requested: &RubyRequest,
+    force: bool,
+    jobs: NonZeroUsize,
+    download: Download<'_>,
 ) -> Result<()> {
-    if requested.patch.is_none() {
-        Err(Error::IncompleteVersion(requested.clone()))?;
-    }
-
-    let url = ruby_url(&requested.to_string())?;
+    let Download {
+        mirror: download_mirror,
+        signature_key,
+        platform,
+    } = download;
+    let url = ruby_url(requested, platform)?;
     let tarball_path = tarball_path(config, &url);
 
     let new_dir = tarball_path.parent().unwrap();
@@ -86,15 +474,37 @@ async fn download_and_extract_remote_tarball(
     }
 
     if valid_tarball_exists(&tarball_path) {
-        println!(
-            "Tarball {} already exists, skipping download.",
-            tarball_path.cyan()
-        );
+        if !config.quiet {
+            println!(
+                "Tarball {} already exists, skipping download.",
+                tarball_path.cyan()
+            );
+        }
+    } else if config.offline {
+        return Err(Error::Offline(url));
     } else {
-        download_ruby_tarball(config, &url, &tarball_path).await?;
+        let download_url = mirrored_url(&url, download_mirror);
+        download_ruby_tarball(config, &url, &download_url, &tarball_path).await?;
     }
 
-    extract_ruby_tarball(&tarball_path, install_dir, &requested.number())?;
+    if let Some(key) = signature_key {
+        let signature_path = signature_path(&tarball_path);
+        if !signature_path.exists() {
+            let signature_url = format!("{}.minisig", mirrored_url(&url, download_mirror));
+            if config.offline {
+                return Err(Error::Offline(signature_url));
+            }
+            download_signature(config, &signature_url, &signature_path).await?;
+        }
+        if let Err(err) = verify_tarball_signature(&tarball_path, key) {
+            // Don't keep a tarball around that can't be trusted.
+            let _ = std::fs::remove_file(&tarball_path);
+            let _ = std::fs::remove_file(&signature_path);
+            return Err(err);
+        }
+    }
+
+    extract_ruby_tarball(&tarball_path, install_dir, &requested.number(), force, jobs)?;
 
     Ok(())
 }
@@ -104,8 +514,16 @@ async fn extract_local_ruby_tarball(
     tarball_path: String,
     install_dir: &Utf8PathBuf,
     version: &str,
+    force: bool,
+    jobs: NonZeroUsize,
 ) -> Result<()> {
-    extract_ruby_tarball(Utf8Path::new(&tarball_path), install_dir, version)?;
+    extract_ruby_tarball(
+        Utf8Path::new(&tarball_path),
+        install_dir,
+        version,
+        force,
+        jobs,
+    )?;
 
     Ok(())
 }
@@ -124,18 +542,29 @@ fn valid_tarball_exists(path: &Utf8Path) -> bool {
     true
 }
 
-fn ruby_url(version: &str) -> Result<String> {
-    let version = version.strip_prefix("ruby-").unwrap();
-    let arch = match CURRENT_PLATFORM {
-        "aarch64-apple-darwin" => "arm64_sonoma",
-        "x86_64-apple-darwin" => "ventura",
-        "x86_64-unknown-linux-gnu" => "x86_64_linux",
-        "aarch64-unknown-linux-gnu" => "arm64_linux",
-        other => return Err(Error::UnsupportedPlatform(other)),
+/// The arch part of the asset names of rubies built for this host.
+fn host_platform() -> Result<&'static str> {
+    match CURRENT_PLATFORM {
+        "aarch64-apple-darwin" => Ok("arm64_sonoma"),
+        "x86_64-apple-darwin" => Ok("ventura"),
+        "x86_64-unknown-linux-gnu" => Ok("x86_64_linux"),
+        "aarch64-unknown-linux-gnu" => Ok("arm64_linux"),
+        other => Err(Error::UnsupportedPlatform(other)),
+    }
+}
+
+/// The URL of the release tarball of `requested`, only released for the `ruby` engine.
+fn ruby_url(requested: &RubyRequest, platform: Option<&str>) -> Result<String> {
+    if requested.engine != RubyEngine::Ruby {
+        return Err(Error::UnsupportedEngine(requested.engine.clone()));
+    }
+    let version = requested.number();
+    let arch = match platform {
+        Some(platform) => platform,
+        None => host_platform()?,
     };
 
-    let download_base = std::env::var("RV_RELEASES_URL")
-        .unwrap_or("https://github.com/spinel-coop/rv-ruby/releases".to_owned());
+    let download_base = std::env::var("RV_RELEASES_URL").unwrap_or(GITHUB_RELEASES_URL.to_owned());
 
     Ok(format!(
         "{}/latest/download/ruby-{version}.{arch}.tar.gz",
@@ -143,6 +572,15 @@ fn ruby_url(version: &str) -> Result<String> {
     ))
 }
 
+/// Rewrites a GitHub release download URL to point at `mirror` instead.
+/// URLs from anywhere else are left alone.
+fn mirrored_url(url: &str, mirror: Option<&str>) -> String {
+    match (mirror, url.strip_prefix(GITHUB_RELEASES_URL)) {
+        (Some(mirror), Some(rest)) => format!("{}{rest}", mirror.trim_end_matches('/')),
+        _ => url.to_owned(),
+    }
+}
+
 fn tarball_path(config: &Config, url: impl AsRef<str>) -> Utf8PathBuf {
     let cache_key = rv_cache::cache_digest(url.as_ref());
     config
@@ -161,32 +599,81 @@ fn temp_tarball_path(config: &Config, url: impl AsRef<str>) -> Utf8PathBuf {
         .join(format!("{cache_key}.tar.gz.tmp"))
 }
 
+/// How often to log download progress when no progress bar is shown, if the total size is unknown.
+const DOWNLOAD_LOG_INTERVAL: u64 = 10 * 1024 * 1024;
+
+/// Whether progress bars will actually be drawn, otherwise we fall back to debug logs.
+fn progress_bars_visible() -> bool {
+    std::env::var_os("RV_DISABLE_INDICATIF").is_none() && std::io::stderr().is_terminal()
+}
+
+fn progress_style(template: &str) -> ProgressStyle {
+    ProgressStyle::with_template(template).expect("progress bar template should be valid")
+}
+
 /// Write the file from this HTTP `response` to the given `path`.
 /// While the stream is being handled, it'll be written to the given `temp_path`.
 /// Then once the download finishes, the file will be renamed to `path`.
+///
+/// Progress is reported on the current span's progress bar.
 async fn write_to_filesystem(
     response: reqwest::Response,
     temp_path: &Utf8Path,
     path: &Utf8Path,
 ) -> Result<()> {
+    let span = Span::current();
+    let total = response.content_length();
+    if let Some(total) = total {
+        span.pb_set_length(total);
+    }
+    let log_interval = total.map_or(DOWNLOAD_LOG_INTERVAL, |total| (total / 10).max(1));
+    let log_progress = !progress_bars_visible();
+
+    let url = response.url().to_string();
     let mut file = tokio::fs::File::create(&temp_path).await?;
     let mut stream = response.bytes_stream();
+    let mut downloaded = 0;
+    let mut next_log = log_interval;
     while let Some(chunk) = stream.next().await {
-        let chunk = chunk?;
+        let chunk = chunk.map_err(|err| crate::http::request_error(err, &url))?;
         file.write_all(&chunk).await?;
+
+        downloaded += chunk.len() as u64;
+        span.pb_inc(chunk.len() as u64);
+        if log_progress && downloaded >= next_log {
+            match total {
+                Some(total) => debug!(
+                    "Downloaded {} of {}",
+                    ByteSize::b(downloaded).display().iec_short(),
+                    ByteSize::b(total).display().iec_short()
+                ),
+                None => debug!(
+                    "Downloaded {}",
+                    ByteSize::b(downloaded).display().iec_short()
+                ),
+            }
+            next_log = (downloaded / log_interval + 1) * log_interval;
+        }
     }
     file.sync_all().await?;
     tokio::fs::rename(temp_path, path).await?;
     Ok(())
 }
 
+/// Downloads the tarball for `url` from `download_url`, which may be a mirror of it.
 async fn download_ruby_tarball(
     config: &Config,
     url: &str,
+    download_url: &str,
     tarball_path: &Utf8PathBuf,
 ) -> Result<()> {
     // Start downloading the tarball.
-    let response = reqwest::get(url).await?;
+    let response = config
+        .http_client()?
+        .get(download_url)
+        .send()
+        .await
+        .map_err(|err| crate::http::request_error(err, download_url))?;
     if !response.status().is_success() {
         let status = response.status();
         let body = response
@@ -194,48 +681,590 @@ async fn download_ruby_tarball(
             .await
             .unwrap_or_else(|e| format!("<error reading body: {e}>"));
         return Err(Error::DownloadFailed {
-            url: url.to_string(),
+            url: download_url.to_string(),
             status,
             body,
         });
     }
 
+    let span = info_span!("download", url = download_url);
+    span.pb_set_style(&progress_style(
+        "{spinner:.green} Downloading {msg} {bytes}/{total_bytes} ({eta}) {wide_bar:.cyan/blue}",
+    ));
+    span.pb_set_message(download_url.rsplit('/').next().unwrap_or(download_url));
+
     // Write the tarball bytes to the filesystem.
     let temp_path = temp_tarball_path(config, url);
-    if let Err(e) = write_to_filesystem(response, &temp_path, tarball_path).await {
+    if let Err(e) = write_to_filesystem(response, &temp_path, tarball_path)
+        .instrument(span)
+        .await
+    {
         // Clean up the temporary file if there was any error.
         tokio::fs::remove_file(temp_path).await?;
         return Err(e);
     }
 
-    println!("Downloaded {} to {}", url.cyan(), tarball_path.cyan());
+    if !config.quiet {
+        println!(
+            "Downloaded {} to {}",
+            download_url.cyan(),
+            tarball_path.cyan()
+        );
+    }
     Ok(())
 }
 
+/// Extracts the tarball into a temporary sibling directory first, and only moves the result
+/// into `rubies_dir` once everything was unpacked, so a failed install leaves nothing behind.
 fn extract_ruby_tarball(
     tarball_path: &Utf8Path,
     rubies_dir: &Utf8Path,
     version: &str,
+    force: bool,
+    jobs: NonZeroUsize,
 ) -> Result<()> {
     if !rubies_dir.exists() {
         std::fs::create_dir_all(rubies_dir)?;
     }
+
+    let temp_dir = rubies_dir.join(format!(".rv-extract-{version}-{}", std::process::id()));
+    if temp_dir.exists() {
+        std::fs::remove_dir_all(&temp_dir)?;
+    }
+    std::fs::create_dir(&temp_dir)?;
+
+    let result = unpack_ruby_tarball(tarball_path, &temp_dir, version, jobs)
+        .and_then(|()| move_extracted_rubies(&temp_dir, rubies_dir, force))
+        .and_then(|()| sync_dir(rubies_dir).map_err(Error::from));
+    if result.is_ok() {
+        debug!("Extracted {} to {}", tarball_path, rubies_dir);
+    }
+
+    if let Err(err) = std::fs::remove_dir_all(&temp_dir) {
+        debug!("Failed to remove temporary directory {temp_dir}: {err}");
+    }
+
+    result
+}
+
+/// Moves everything extracted into `temp_dir` over to `rubies_dir`.
+fn move_extracted_rubies(temp_dir: &Utf8Path, rubies_dir: &Utf8Path, force: bool) -> Result<()> {
+    let mut moves = Vec::new();
+    for entry in temp_dir.read_dir_utf8()? {
+        let entry = entry?;
+        let dst = rubies_dir.join(entry.file_name());
+        if dst.symlink_metadata().is_ok() && !force {
+            return Err(Error::AlreadyInstalled(dst));
+        }
+        moves.push((entry.into_path(), dst));
+    }
+
+    for (src, dst) in moves {
+        if let Ok(metadata) = dst.symlink_metadata() {
+            if metadata.is_dir() {
+                std::fs::remove_dir_all(&dst)?;
+            } else {
+                std::fs::remove_file(&dst)?;
+            }
+        }
+        std::fs::rename(src, dst)?;
+    }
+
+    Ok(())
+}
+
+fn unpack_ruby_tarball(
+    tarball_path: &Utf8Path,
+    dest_dir: &Utf8Path,
+    version: &str,
+    jobs: NonZeroUsize,
+) -> Result<()> {
+    let span = info_span!("extract", tarball = %tarball_path);
+    span.pb_set_style(&progress_style(
+        "{spinner:.green} Extracting {msg} ({pos} files)",
+    ));
+    span.pb_set_message(&format!("Ruby {version}"));
+    let _guard = span.enter();
+
     let tarball = std::fs::File::open(tarball_path)?;
     let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(tarball));
-    for e in archive.entries()? {
-        let mut entry = e?;
-        let entry_path = entry.path()?;
-        let path = entry_path
-            .to_str()
-            .ok_or_else(|| Error::InvalidTarballPath(entry_path.to_path_buf()))?
-            .replace(
-                &format!("rv-ruby@{version}/{version}"),
-                &format!("ruby-{version}"),
-            )
-            .replace('@', "-");
-        let dst = rubies_dir.join(path);
-        entry.unpack(dst)?;
+    unpack_entries(&mut archive, dest_dir, version, jobs, &span)
+}
+
+/// Unpacks `archive` into `dest_dir`, writing regular files on `jobs` threads.
+///
+/// The tarball is a single gzip stream, so entries are still read one after the other. Each
+/// file's parent directories are created before it is handed off, and hard links are only
+/// made once all files have been written. At most `jobs` files are read ahead of the threads
+/// writing them, so a large tarball isn't read into memory on a slow disk.
+fn unpack_entries<R: Read>(
+    archive: &mut tar::Archive<R>,
+    dest_dir: &Utf8Path,
+    version: &str,
+    jobs: NonZeroUsize,
+    span: &Span,
+) -> Result<()> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.get())
+        .build()?;
+    let failure: Mutex<Option<std::io::Error>> = Mutex::new(None);
+    let mut hard_links = Vec::new();
+
+    // A permit for each file that may be waiting to be written, handed back once it was.
+    let (release, acquire) = std::sync::mpsc::sync_channel(jobs.get());
+    for _ in 0..jobs.get() {
+        release
+            .send(())
+            .expect("the channel has room for every permit");
     }
 
+    pool.in_place_scope(|scope| -> Result<()> {
+        for e in archive.entries()? {
+            let mut entry = e?;
+            let dst = dest_dir.join(extracted_path(&entry.path()?, version)?);
+            check_no_symlinks(dest_dir, &dst)?;
+            match entry.header().entry_type() {
+                EntryType::Regular | EntryType::Continuous => {
+                    if let Some(parent) = dst.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    let mode = entry.header().mode()?;
+                    // The writers hold on to every permit, wait for one of them to finish.
+                    acquire.recv().expect("a writer hands back its permit");
+                    let permit = Permit(release.clone());
+                    let mut contents = Vec::with_capacity(entry.size() as usize);
+                    entry.read_to_end(&mut contents)?;
+
+                    let failure = &failure;
+                    scope.spawn(move |_| {
+                        let _permit = permit;
+                        if let Err(err) = write_extracted_file(&dst, &contents, mode) {
+                            failure.lock().unwrap().get_or_insert(err);
+                        }
+                        span.pb_inc(1);
+                    });
+                }
+                EntryType::Link => {
+                    let target = entry
+                        .link_name()?
+                        .ok_or_else(|| Error::InvalidTarballPath(dst.clone().into()))?;
+                    hard_links.push((dest_dir.join(extracted_path(&target, version)?), dst));
+                }
+                _ => {
+                    entry.unpack(dst)?;
+                    span.pb_inc(1);
+                }
+            }
+        }
+        Ok(())
+    })?;
+
+    if let Some(err) = failure.into_inner().unwrap() {
+        return Err(err.into());
+    }
+    for (target, dst) in hard_links {
+        std::fs::hard_link(target, dst)?;
+        span.pb_inc(1);
+    }
+
+    Ok(())
+}
+
+/// Lets [`unpack_entries`] read another file once dropped, even if writing this one panicked.
+struct Permit(std::sync::mpsc::SyncSender<()>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        // Only fails once the reader stopped waiting for permits.
+        let _ = self.0.send(());
+    }
+}
+
+/// Fails if anything between `dest_dir` and `path` is a symlink, like one unpacked from the
+/// tarball earlier, as writing to `path` could then change files outside of `dest_dir`.
+fn check_no_symlinks(dest_dir: &Utf8Path, path: &Utf8Path) -> Result<()> {
+    let inside = path
+        .ancestors()
+        .skip(1)
+        .take_while(|ancestor| *ancestor != dest_dir);
+    for ancestor in inside {
+        if ancestor.is_symlink() {
+            return Err(Error::SymlinkInTarballPath(path.to_owned()));
+        }
+    }
+    Ok(())
+}
+
+/// How often writing an extracted file is retried after a transient error.
+const WRITE_RETRIES: u32 = 3;
+/// How long to wait before the first retry, doubled for every following one.
+const WRITE_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+fn write_extracted_file(path: &Utf8Path, contents: &[u8], mode: u32) -> std::io::Result<()> {
+    let mut file = create_extracted_file(path)?;
+    write_with_retries(&mut file, contents)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
     Ok(())
 }
+
+/// Creates the file at `path`, replacing one that is already there. Unlike `File::create`,
+/// it never follows a symlink at `path`, which could point anywhere.
+fn create_extracted_file(path: &Utf8Path) -> std::io::Result<std::fs::File> {
+    let create = || {
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+    };
+    match create() {
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+            if path.is_symlink() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Refusing to write {path} through a symlink from the tarball"),
+                ));
+            }
+            std::fs::remove_file(path)?;
+            create()
+        }
+        result => result,
+    }
+}
+
+/// Like `write_all`, but slow or networked filesystems get a few more chances when a write
+/// fails with an error that may go away by itself, backing off a little more each time.
+fn write_with_retries(writer: &mut impl Write, mut contents: &[u8]) -> std::io::Result<()> {
+    let mut retries = 0;
+    while !contents.is_empty() {
+        match writer.write(contents) {
+            Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+            Ok(written) => contents = &contents[written..],
+            Err(err) if is_transient(&err) && retries < WRITE_RETRIES => {
+                let delay = WRITE_RETRY_DELAY * 2u32.pow(retries);
+                retries += 1;
+                debug!("Retrying write in {delay:?} after: {err}");
+                std::thread::sleep(delay);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+/// Whether a failed write is worth retrying, like `EINTR` and `EAGAIN`.
+fn is_transient(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        std::io::ErrorKind::Interrupted
+            | std::io::ErrorKind::WouldBlock
+            | std::io::ErrorKind::TimedOut
+    )
+}
+
+/// Flushes the entries of `dir` to disk, so the renames into it survive a crash and whoever
+/// looks for rubies next sees the whole install. Directories can't be synced on Windows.
+fn sync_dir(dir: &Utf8Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    std::fs::File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Where an entry of the tarball for `version` is unpacked to, relative to the destination.
+/// Paths that could leave the destination, absolute ones or with `..` in them, are rejected.
+fn extracted_path(entry_path: &Path, version: &str) -> Result<String> {
+    let invalid = || Error::InvalidTarballPath(entry_path.to_path_buf());
+    let path = entry_path
+        .to_str()
+        .ok_or_else(invalid)?
+        .replace(
+            &format!("rv-ruby@{version}/{version}"),
+            &format!("ruby-{version}"),
+        )
+        .replace('@', "-");
+    let stays_inside = Utf8Path::new(&path)
+        .components()
+        .all(|component| matches!(component, Utf8Component::Normal(_) | Utf8Component::CurDir));
+    if !stays_inside {
+        return Err(invalid());
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails its first write with `error`, then takes at most `chunk` bytes per write.
+    struct FlakyWriter {
+        error: Option<std::io::ErrorKind>,
+        chunk: usize,
+        written: Vec<u8>,
+    }
+
+    impl Write for FlakyWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if let Some(kind) = self.error.take() {
+                return Err(kind.into());
+            }
+            let len = buf.len().min(self.chunk);
+            self.written.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_with_retries_retries_transient_errors() {
+        let mut writer = FlakyWriter {
+            error: Some(std::io::ErrorKind::WouldBlock),
+            chunk: 3,
+            written: Vec::new(),
+        };
+        write_with_retries(&mut writer, b"#!/bin/ruby").unwrap();
+        assert_eq!(writer.written, b"#!/bin/ruby");
+
+        let mut writer = FlakyWriter {
+            error: Some(std::io::ErrorKind::PermissionDenied),
+            chunk: 3,
+            written: Vec::new(),
+        };
+        let err = write_with_retries(&mut writer, b"#!/bin/ruby").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        assert!(writer.written.is_empty());
+    }
+
+    #[test]
+    fn test_ruby_url() {
+        let requested: RubyRequest = "3.4.5".parse().unwrap();
+        let url = ruby_url(&requested, Some("arm64_linux")).unwrap();
+        assert!(url.ends_with("/latest/download/ruby-3.4.5.arm64_linux.tar.gz"));
+
+        let requested: RubyRequest = "jruby-9.4.12.0".parse().unwrap();
+        assert!(matches!(
+            ruby_url(&requested, Some("arm64_linux")),
+            Err(Error::UnsupportedEngine(RubyEngine::JRuby))
+        ));
+    }
+
+    #[test]
+    fn test_mirrored_url() {
+        let url = format!("{GITHUB_RELEASES_URL}/latest/download/ruby-3.4.5.arm64_linux.tar.gz");
+
+        assert_eq!(mirrored_url(&url, None), url);
+        assert_eq!(
+            mirrored_url(&url, Some("https://mirror.example.com/rv-ruby/")),
+            "https://mirror.example.com/rv-ruby/latest/download/ruby-3.4.5.arm64_linux.tar.gz"
+        );
+        assert_eq!(
+            mirrored_url(
+                "https://example.com/ruby-3.4.5.tar.gz",
+                Some("https://mirror.example.com")
+            ),
+            "https://example.com/ruby-3.4.5.tar.gz"
+        );
+    }
+
+    #[test]
+    fn test_nearest_versions() {
+        let available: Vec<Ruby> = ["3.2.9", "3.3.9", "3.4.5", "jruby-9.4.0"]
+            .into_iter()
+            .map(|version| Ruby {
+                key: format!("{version}-macos-aarch64"),
+                version: version.parse().unwrap(),
+                path: Utf8PathBuf::from(format!("/rubies/{version}")),
+                symlink: None,
+                arch: "aarch64".to_owned(),
+                os: "macos".to_owned(),
+                gem_root: None,
+            })
+            .collect();
+
+        let requested: RubyRequest = "3.9".parse().unwrap();
+        assert_eq!(
+            nearest_versions(&requested, &available),
+            "ruby-3.4.5, ruby-3.3.9, ruby-3.2.9"
+        );
+        let requested: RubyRequest = "jruby-10.0".parse().unwrap();
+        assert_eq!(nearest_versions(&requested, &available), "jruby-9.4.0");
+        let requested: RubyRequest = "mruby-3.3".parse().unwrap();
+        assert_eq!(nearest_versions(&requested, &available), "none");
+    }
+
+    fn write_test_tarball(path: &Utf8Path) {
+        let tarball = std::fs::File::create(path).unwrap();
+        let encoder = flate2::write::GzEncoder::new(tarball, flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+
+        let mut append = |path: &str, entry_type: EntryType, mode: u32, contents: &[u8]| {
+            let mut header = tar::Header::new_gnu();
+            header.set_path(path).unwrap();
+            header.set_entry_type(entry_type);
+            header.set_mode(mode);
+            header.set_size(contents.len() as u64);
+            header.set_cksum();
+            builder.append(&header, contents).unwrap();
+        };
+        append("rv-ruby@3.4.5/", EntryType::Directory, 0o755, b"");
+        append("rv-ruby@3.4.5/3.4.5/", EntryType::Directory, 0o755, b"");
+        append("rv-ruby@3.4.5/3.4.5/bin/", EntryType::Directory, 0o755, b"");
+        append(
+            "rv-ruby@3.4.5/3.4.5/bin/ruby",
+            EntryType::Regular,
+            0o755,
+            b"ruby",
+        );
+        append("rv-ruby@3.4.5/3.4.5/lib/", EntryType::Directory, 0o755, b"");
+        append(
+            "rv-ruby@3.4.5/3.4.5/lib/ruby/",
+            EntryType::Directory,
+            0o755,
+            b"",
+        );
+        append(
+            "rv-ruby@3.4.5/3.4.5/lib/ruby/3.4.0/",
+            EntryType::Directory,
+            0o755,
+            b"",
+        );
+        for i in 0..20 {
+            let path = format!("rv-ruby@3.4.5/3.4.5/lib/ruby/3.4.0/file{i}.rb");
+            append(&path, EntryType::Regular, 0o644, path.as_bytes());
+        }
+
+        let mut link = tar::Header::new_gnu();
+        link.set_entry_type(EntryType::Link);
+        link.set_size(0);
+        builder
+            .append_link(
+                &mut link,
+                "rv-ruby@3.4.5/3.4.5/bin/ruby3.4",
+                "rv-ruby@3.4.5/3.4.5/bin/ruby",
+            )
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_unpack_ruby_tarball_with_more_jobs_matches_one_job() {
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let root = Utf8Path::from_path(temp_dir.path()).unwrap();
+        let tarball_path = root.join("ruby.tar.gz");
+        write_test_tarball(&tarball_path);
+
+        let one_job = root.join("one-job");
+        let four_jobs = root.join("four-jobs");
+        std::fs::create_dir(&one_job).unwrap();
+        std::fs::create_dir(&four_jobs).unwrap();
+        let one = NonZeroUsize::MIN;
+        let four = NonZeroUsize::new(4).unwrap();
+        unpack_ruby_tarball(&tarball_path, &one_job, "3.4.5", one).unwrap();
+        unpack_ruby_tarball(&tarball_path, &four_jobs, "3.4.5", four).unwrap();
+
+        for i in 0..20 {
+            let file = format!("ruby-3.4.5/lib/ruby/3.4.0/file{i}.rb");
+            assert_eq!(
+                std::fs::read(four_jobs.join(&file)).unwrap(),
+                std::fs::read(one_job.join(&file)).unwrap()
+            );
+        }
+        assert_eq!(
+            std::fs::read(four_jobs.join("ruby-3.4.5/bin/ruby3.4")).unwrap(),
+            b"ruby"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: Utf8PathBuf| path.metadata().unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(four_jobs.join("ruby-3.4.5/bin/ruby")), 0o755);
+            assert_eq!(
+                mode(four_jobs.join("ruby-3.4.5/lib/ruby/3.4.0/file0.rb")),
+                0o644
+            );
+        }
+    }
+
+    #[test]
+    fn test_extracted_path_stays_inside_destination() {
+        let path = |path: &str| extracted_path(Path::new(path), "3.4.5");
+        assert_eq!(
+            path("rv-ruby@3.4.5/3.4.5/bin/ruby").unwrap(),
+            "ruby-3.4.5/bin/ruby"
+        );
+        assert!(path("/etc/passwd").is_err());
+        assert!(path("../passwd").is_err());
+        assert!(path("rv-ruby@3.4.5/3.4.5/../../../passwd").is_err());
+    }
+
+    /// Writes a tarball with a symlink named `link` to `target`, then a file at `file`.
+    fn write_symlink_tarball(path: &Utf8Path, link: &str, target: &Utf8Path, file: &str) {
+        let tarball = std::fs::File::create(path).unwrap();
+        let encoder = flate2::write::GzEncoder::new(tarball, flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+
+        let mut symlink = tar::Header::new_gnu();
+        symlink.set_entry_type(EntryType::Symlink);
+        symlink.set_size(0);
+        builder.append_link(&mut symlink, link, target).unwrap();
+
+        let mut header = tar::Header::new_gnu();
+        header.set_path(file).unwrap();
+        header.set_entry_type(EntryType::Regular);
+        header.set_mode(0o644);
+        header.set_size(4);
+        header.set_cksum();
+        builder.append(&header, &b"evil"[..]).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unpack_ruby_tarball_never_writes_through_symlinks() {
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let root = Utf8Path::from_path(temp_dir.path()).unwrap();
+        let outside = root.join("outside");
+        std::fs::create_dir(&outside).unwrap();
+        std::fs::write(outside.join("file"), "safe").unwrap();
+
+        // A file under a symlinked directory.
+        let tarball_path = root.join("dir.tar.gz");
+        write_symlink_tarball(
+            &tarball_path,
+            "rv-ruby@3.4.5/3.4.5/lib",
+            &outside,
+            "rv-ruby@3.4.5/3.4.5/lib/file",
+        );
+        let dest = root.join("dir");
+        std::fs::create_dir_all(dest.join("ruby-3.4.5")).unwrap();
+        let result = unpack_ruby_tarball(&tarball_path, &dest, "3.4.5", NonZeroUsize::MIN);
+        assert!(matches!(result, Err(Error::SymlinkInTarballPath(_))));
+
+        // A file replacing a symlink to a file.
+        let tarball_path = root.join("file.tar.gz");
+        write_symlink_tarball(
+            &tarball_path,
+            "rv-ruby@3.4.5/3.4.5/file",
+            &outside.join("file"),
+            "rv-ruby@3.4.5/3.4.5/file",
+        );
+        let dest = root.join("file");
+        std::fs::create_dir_all(dest.join("ruby-3.4.5")).unwrap();
+        let result = unpack_ruby_tarball(&tarball_path, &dest, "3.4.5", NonZeroUsize::MIN);
+        assert!(result.is_err());
+
+        assert_eq!(
+            std::fs::read_to_string(outside.join("file")).unwrap(),
+            "safe"
+        );
+    }
+}
@@ -1,34 +1,151 @@
-use std::collections::BTreeMap;
-use std::io;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, Write as _};
 use std::time::{Duration, SystemTime};
 
 use anstream::println;
 use camino::Utf8PathBuf;
+use clap::builder::{PossibleValuesParser, TypedValueParser as _};
 use current_platform::CURRENT_PLATFORM;
-use fs_err as fs;
 use once_cell::sync::Lazy;
 use owo_colors::OwoColorize;
+use rayon::prelude::*;
 use regex::Regex;
+use reqwest::StatusCode;
 use rv_ruby::Ruby;
+use rv_ruby::engine::RubyEngine;
 use rv_ruby::request::RubyRequest;
+use rv_ruby::version::RubyVersion;
 use rv_ruby::{Asset, Release};
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use tracing::{debug, info, warn};
 
 use crate::config::Config;
+use crate::http;
+use crate::update_check::UpdateCheck;
 
-// Use GitHub's TTL, but don't re-check more than every 60 seconds.
-const MINIMUM_CACHE_TTL: Duration = Duration::from_secs(60);
+/// Where the latest release is cached by [`crate::http::cached_get`].
+const RELEASES_CACHE_KEY: &str = "available_rubies.json";
 
-static ARCH_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"ruby-[\d\.]+\.(?P<arch>[a-zA-Z0-9_]+)\.tar\.gz").unwrap());
-
-static PARSE_MAX_AGE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"max-age=(\d+)").unwrap());
+/// Matches asset names like `ruby-3.4.5.arm64_linux.tar.gz` or `truffleruby-24.1.2.x86_64_linux.tar.gz`.
+static ARCH_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[a-z]+-[\d\.]+(-[a-z0-9]+)?\.(?P<arch>[a-zA-Z0-9_]+)\.tar\.gz$").unwrap()
+});
 
 #[derive(clap::ValueEnum, Clone, Debug, PartialEq, Eq)]
 pub enum OutputFormat {
     Text,
     Json,
+    Yaml,
+    /// One compact JSON object per line, see <https://github.com/ndjson/ndjson-spec>
+    Ndjson,
+    /// Aligned columns under a header row
+    Table,
+}
+
+impl OutputFormat {
+    /// Whether this format is meant for people rather than other programs.
+    pub fn is_human_readable(&self) -> bool {
+        matches!(self, Self::Text | Self::Table)
+    }
+}
+
+#[derive(clap::Args)]
+pub struct ListArgs {
+    /// Output format for the Ruby list
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: OutputFormat,
+
+    /// Show only installed Ruby versions, which doesn't need the network
+    #[arg(long)]
+    pub installed_only: bool,
+
+    /// Don't fetch the available versions, only show them if the cached list is still fresh.
+    /// Unlike `--installed-only`, available rubies are still listed
+    #[arg(long, conflicts_with_all = ["installed_only", "path_only", "refresh"])]
+    pub no_remote: bool,
+
+    /// Show at most this many of the newest available versions (installed versions are always shown)
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Re-check the available versions even if the cached list is still fresh
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "soft"
+    )]
+    pub refresh: Option<RefreshMode>,
+
+    /// Show only rubies of this engine
+    #[arg(long, value_parser = PossibleValuesParser::new(RubyEngine::KNOWN)
+        .map(|engine| RubyEngine::from(engine.as_str())))]
+    pub engine: Option<RubyEngine>,
+
+    /// Show only installed rubies that have a gem root
+    #[arg(long)]
+    pub with_gems: bool,
+
+    /// Show only rubies that are available but not installed
+    #[arg(long, conflicts_with = "installed_only")]
+    pub available_only: bool,
+
+    /// Print only the executable paths of installed rubies, one per line
+    #[arg(long, conflicts_with_all = ["available_only", "format"])]
+    pub path_only: bool,
+
+    /// Show the download URL of available rubies in text or table output
+    #[arg(long, conflicts_with = "path_only")]
+    pub show_url: bool,
+
+    /// Mark installed rubies that have a newer patch release available
+    #[arg(long, conflicts_with = "path_only")]
+    pub updatable: bool,
+
+    /// Group text or table output under a header for each version, e.g. `--group-by minor`
+    #[arg(long, value_enum, value_name = "LEVEL", conflicts_with = "path_only")]
+    pub group_by: Option<GroupBy>,
+
+    /// Print only how many rubies are installed, available and active
+    #[arg(long, conflicts_with_all = ["path_only", "group_by", "show_url"])]
+    pub count: bool,
+
+    /// Order of the listed rubies
+    #[arg(long, value_enum, value_name = "ORDER", default_value = "version")]
+    pub sort: SortBy,
+
+    /// Also show available prereleases, like `3.5.0-preview1`. They are shown anyway when a
+    /// prerelease is installed or pinned
+    #[arg(long)]
+    pub prereleases: bool,
+}
+
+/// How `--sort` orders the listed rubies.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortBy {
+    /// Oldest version first, so 3.9 comes before 3.10.
+    Version,
+    /// Alphabetically by name, so `ruby-3.10` comes before `ruby-3.9`.
+    Name,
+    /// Installed rubies first, then available ones, each by version.
+    Installed,
+}
+
+/// What `--group-by` puts under one header.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupBy {
+    /// All patches of a minor version, like `3.3`.
+    Minor,
+}
+
+/// How `--refresh` re-checks the list of available rubies.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefreshMode {
+    /// Ignore the cache expiry, but still send the ETag so an unchanged list is cheap.
+    Soft,
+    /// Ignore the cache entirely and download the full list.
+    Hard,
 }
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
@@ -36,9 +153,25 @@ pub enum Error {
     #[error(transparent)]
     SerdeJsonError(#[from] serde_json::Error),
     #[error(transparent)]
+    SerdeYamlError(#[from] serde_yaml::Error),
+    #[error(transparent)]
     ConfigError(#[from] crate::config::Error),
-    #[error("Failed to fetch available ruby versions from GitHub")]
-    RequestError(#[from] reqwest::Error),
+    #[error(transparent)]
+    HttpError(#[from] http::Error),
+    #[error("Releases endpoint {url} responded with {status}")]
+    ReleasesStatus { url: String, status: StatusCode },
+    #[error("Could not connect to releases host {host}")]
+    ConnectError {
+        host: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("Releases endpoint {url} returned an unexpected response body")]
+    MalformedReleases {
+        url: String,
+        #[source]
+        source: serde_json::Error,
+    },
     #[error(transparent)]
     IoError(#[from] std::io::Error),
     #[error(transparent)]
@@ -49,12 +182,36 @@ pub enum Error {
 
 type Result<T> = miette::Result<T, Error>;
 
-// Updated struct to hold ETag and calculated expiry time
-#[derive(Serialize, Deserialize, Debug)]
-struct CachedRelease {
-    expires_at: SystemTime,
-    etag: Option<String>,
-    release: Release,
+/// The release that the available rubies in the list came from.
+#[derive(Serialize, Debug)]
+struct ReleaseInfo {
+    name: String,
+    tag: Option<String>,
+}
+
+impl From<&Release> for ReleaseInfo {
+    fn from(release: &Release) -> Self {
+        Self {
+            name: release.name.clone(),
+            tag: release.tag_name.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for ReleaseInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.tag {
+            Some(tag) if tag != &self.name => write!(f, "{} ({tag})", self.name),
+            _ => write!(f, "{}", self.name),
+        }
+    }
+}
+
+// Top-level document of the JSON and YAML output
+#[derive(Serialize)]
+struct JsonRubyList<'a> {
+    release: Option<&'a ReleaseInfo>,
+    rubies: &'a [JsonRubyEntry],
 }
 
 // Struct for JSON output and maintaing the list of installed/active rubies
@@ -65,21 +222,19 @@ struct JsonRubyEntry {
     details: Ruby,
     installed: bool,
     active: bool,
+    /// The newest available patch of an installed minor version, if it's newer than the install.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updatable_to: Option<RubyVersion>,
 }
 
-/// Parses the `max-age` value from a `Cache-Control` header.
-fn parse_max_age(header: &str) -> Option<Duration> {
-    PARSE_MAX_AGE_REGEX
-        .captures(header)
-        .and_then(|caps| caps.get(1))
-        .and_then(|age| age.as_str().parse::<u64>().ok())
-        .map(Duration::from_secs)
-}
+/// The arch parts of the asset names of every platform rv-ruby builds for.
+pub(crate) const PLATFORMS: [&str; 4] = ["arm64_sonoma", "ventura", "x86_64_linux", "arm64_linux"];
 
 /// Parses the OS and architecture from the arch part of the asset name.
 fn parse_arch_str(arch_str: &str) -> (&'static str, &'static str) {
     match arch_str {
         "arm64_sonoma" => ("macos", "aarch64"),
+        "ventura" => ("macos", "x86_64"),
         "x86_64_linux" => ("linux", "x86_64"),
         "arm64_linux" => ("linux", "aarch64"),
         _ => ("unknown", "unknown"),
@@ -102,21 +257,26 @@ fn all_suffixes() -> impl IntoIterator<Item = &'static str> {
     [
         ".arm64_linux.tar.gz",
         ".arm64_sonoma.tar.gz",
-        "x86_64_linux.tar.gz",
+        ".x86_64_linux.tar.gz",
         // We follow the Homebrew convention that if there's no arch, it defaults to x86.
         ".ventura.tar.gz",
     ]
 }
 
+/// Parses the version from the file name of a release asset, like `ruby-3.4.5.arm64_linux.tar.gz`.
+pub(crate) fn version_from_asset_name(
+    name: &str,
+) -> std::result::Result<RubyVersion, rv_ruby::request::RequestError> {
+    let mut curr = name;
+    for suffix in all_suffixes() {
+        curr = curr.strip_suffix(suffix).unwrap_or(curr);
+    }
+    curr.parse()
+}
+
 /// Creates a Rubies info struct from a release asset
 fn ruby_from_asset(asset: &Asset) -> Result<Ruby> {
-    let version: rv_ruby::version::RubyVersion = {
-        let mut curr = asset.name.as_str();
-        for suffix in all_suffixes() {
-            curr = curr.strip_suffix(suffix).unwrap_or(curr);
-        }
-        curr.parse()
-    }?;
+    let version = version_from_asset_name(&asset.name)?;
     let display_name = version.to_string();
 
     let arch_str = ARCH_REGEX
@@ -138,14 +298,12 @@ fn ruby_from_asset(asset: &Asset) -> Result<Ruby> {
 }
 
 /// Fetches available rubies
-pub(crate) async fn fetch_available_rubies(cache: &rv_cache::Cache) -> Result<Release> {
-    let cache_entry = cache.entry(
-        rv_cache::CacheBucket::Ruby,
-        "releases",
-        "available_rubies.json",
-    );
-    let client = reqwest::Client::new();
-
+///
+/// When offline, only the cached list is used, however old it is.
+pub(crate) async fn fetch_available_rubies(
+    config: &Config,
+    refresh: Option<RefreshMode>,
+) -> Result<Release> {
     let api_base =
         std::env::var("RV_RELEASES_URL").unwrap_or_else(|_| "https://api.github.com".to_string());
     if api_base == "-" {
@@ -153,201 +311,326 @@ pub(crate) async fn fetch_available_rubies(cache: &rv_cache::Cache) -> Result<Re
         tracing::debug!("RV_RELEASES_URL is '-', returning empty list without network request.");
         return Ok(Release {
             name: "Empty release".to_owned(),
+            tag_name: None,
             assets: Vec::new(),
         });
     }
     let url = format!("{}/repos/spinel-coop/rv-ruby/releases/latest", api_base);
 
-    // 1. Try to read from the disk cache.
-    let cached_data: Option<CachedRelease> =
-        if let Ok(content) = fs::read_to_string(cache_entry.path()) {
-            serde_json::from_str(&content).ok()
-        } else {
-            None
-        };
-
-    // 2. If we have fresh cached data, use it immediately.
-    if let Some(cache) = &cached_data {
-        if SystemTime::now() < cache.expires_at {
-            debug!("Using cached list of available rubies.");
-            return Ok(cache.release.clone());
-        }
-        debug!("Cached ruby list is stale, re-validating with server.");
-    }
-
-    // 3. Cache is stale or missing
-    let etag = cached_data.as_ref().and_then(|c| c.etag.clone());
-    let mut request_builder = client
-        .get(url)
-        .header("User-Agent", "rv-cli")
-        .header("Accept", "application/vnd.github+json");
-
-    // 4. Use ETag for conditional requests if we have one
-    if let Some(etag) = &etag {
-        debug!("Using ETag to make a conditional request: {}", etag);
-        request_builder = request_builder.header("If-None-Match", etag.clone());
-    }
-
-    let response = request_builder.send().await?;
-
-    // 4. Handle the server's response.
-    match response.status() {
-        reqwest::StatusCode::NOT_MODIFIED => {
-            debug!("GitHub API confirmed releases list is unchanged (304 Not Modified).");
-            let mut stale_cache =
-                cached_data.ok_or_else(|| io::Error::other("304 response without prior cache"))?;
-
-            // Update the expiry time based on the latest Cache-Control header
-            let max_age = response
-                .headers()
-                .get("Cache-Control")
-                .and_then(|v| v.to_str().ok())
-                .and_then(parse_max_age)
-                .unwrap_or(Duration::from_secs(60));
-
-            stale_cache.expires_at = SystemTime::now() + max_age.max(MINIMUM_CACHE_TTL);
-            fs::write(cache_entry.path(), serde_json::to_string(&stale_cache)?)?;
-            Ok(stale_cache.release)
-        }
-        reqwest::StatusCode::OK => {
-            debug!("Received new releases list from GitHub (200 OK).");
-            let headers = response.headers().clone();
-            let new_etag = headers
-                .get("ETag")
-                .and_then(|v| v.to_str().ok())
-                .map(String::from);
-
-            let max_age = headers
-                .get("Cache-Control")
-                .and_then(|v| v.to_str().ok())
-                .and_then(parse_max_age)
-                .unwrap_or(Duration::from_secs(60)); // Default to 60s if header is missing
-
-            let release: Release = response.json().await?;
-            debug!("Fetched latest release {}", release.name);
-
-            let new_cache_entry = CachedRelease {
-                expires_at: SystemTime::now() + max_age.max(MINIMUM_CACHE_TTL),
-                etag: new_etag,
-                release: release.clone(),
-            };
-
-            if let Some(parent) = cache_entry.path().parent() {
-                fs::create_dir_all(parent)?;
-            }
-            fs::write(cache_entry.path(), serde_json::to_string(&new_cache_entry)?)?;
-
-            Ok(release)
-        }
-        status => {
+    // A hard refresh skips the ETag to force a full response.
+    let policy = http::CachePolicy {
+        offline: config.offline,
+        revalidate: refresh.is_some(),
+        ignore_etag: refresh == Some(RefreshMode::Hard),
+        ttl: config.releases_ttl,
+    };
+    let (cache, timeout) = (&config.cache, config.timeout);
+    let client = config.http_client().map_err(http::Error::from)?;
+    let bucket = rv_cache::CacheBucket::Ruby;
+    let body = match http::cached_get(
+        client,
+        cache,
+        bucket,
+        RELEASES_CACHE_KEY,
+        &url,
+        policy,
+        timeout,
+    )
+    .await
+    {
+        Ok((status, body)) if status.is_success() || status == StatusCode::NOT_MODIFIED => body,
+        Ok((status, _)) => {
             warn!("Failed to fetch releases, status: {}", status);
-            Err(response.error_for_status().unwrap_err().into())
+            return Err(Error::ReleasesStatus { url, status });
         }
-    }
+        Err(http::Error::NotCached { .. }) => {
+            return Ok(Release {
+                name: "Empty release".to_owned(),
+                tag_name: None,
+                assets: Vec::new(),
+            });
+        }
+        Err(http::Error::ConnectError { host, source }) => {
+            return Err(Error::ConnectError { host, source });
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let release: Release = serde_json::from_slice(&body).map_err(|source| {
+        // Don't keep serving the broken response from the cache.
+        http::remove_cached_response(cache, bucket, RELEASES_CACHE_KEY);
+        Error::MalformedReleases {
+            url: url.clone(),
+            source,
+        }
+    })?;
+    debug!("Fetched latest release {}", release.name);
+    Ok(release)
 }
 
 /// Lists the available and installed rubies.
-pub async fn list(config: &Config, format: OutputFormat, installed_only: bool) -> Result<()> {
+///
+/// With `RV_CHECK_UPDATE=1`, rv also checks whether a newer rv has been released meanwhile,
+/// unless `--no-remote` keeps it off the network.
+pub async fn list(config: &Config, args: ListArgs) -> Result<()> {
+    let update_check = if args.no_remote {
+        None
+    } else {
+        UpdateCheck::start(config)
+    };
+    let result = list_rubies(config, args).await;
+    if let Some(update_check) = update_check {
+        update_check.finish().await;
+    }
+    result
+}
+
+async fn list_rubies(config: &Config, args: ListArgs) -> Result<()> {
+    let ListArgs {
+        format,
+        installed_only,
+        no_remote,
+        limit,
+        refresh,
+        engine,
+        with_gems,
+        available_only,
+        path_only,
+        show_url,
+        updatable,
+        group_by,
+        count,
+        sort,
+        prereleases,
+    } = args;
+    let installed_only = installed_only || path_only;
     let installed_rubies = config.rubies();
     let active_ruby = config.project_ruby();
+    let mut release_info = None;
 
-    if installed_only {
-        if installed_rubies.is_empty() && format == OutputFormat::Text {
-            warn!("No Ruby installations found.");
-            info!("Try installing Ruby with 'rv ruby install <version>'");
-            return Ok(());
-        }
-
-        let entries: Vec<JsonRubyEntry> = installed_rubies
+    // Finding updates needs the available rubies, even when only installs are listed.
+    let mut entries = if installed_only && !updatable {
+        installed_rubies
             .into_iter()
+            .filter(|ruby| {
+                engine
+                    .as_ref()
+                    .is_none_or(|engine| &ruby.version.engine == engine)
+            })
             .map(|ruby| {
-                let active = active_ruby.as_ref().is_some_and(|a| a == &ruby);
+                let active = active_ruby.as_ref().is_some_and(|a| a.same_install(&ruby));
                 JsonRubyEntry {
                     installed: true,
                     active,
                     details: ruby,
+                    updatable_to: None,
                 }
             })
-            .collect();
+            .collect()
+    } else {
+        let release = if no_remote {
+            fresh_cached_release(config)
+        } else {
+            fetch_or_cached_release(config, refresh).await
+        };
+
+        release_info = Some(ReleaseInfo::from(&release));
+        let prereleases = prereleases
+            || installed_rubies
+                .iter()
+                .any(|ruby| ruby.version.prerelease.is_some())
+            || config
+                .ruby_request()
+                .is_ok_and(|request| request.prerelease.is_some());
+        let mut entries = rubies_to_show(
+            release,
+            installed_rubies,
+            active_ruby,
+            current_platform_arch_str(),
+            prereleases,
+        );
+        if updatable {
+            mark_updatable(&mut entries);
+        }
+        if installed_only {
+            entries.retain(|entry| entry.installed);
+        }
+        // Filter before limiting, so `--limit` counts only rubies of the requested engine.
+        if let Some(engine) = &engine {
+            entries.retain(|entry| &entry.details.version.engine == engine);
+        }
+        match limit {
+            Some(limit) => limit_available(entries, limit),
+            None => entries,
+        }
+    };
 
-        return print_entries(&entries, format);
+    // Only installs that have been given a gem environment, which available rubies never have.
+    if with_gems {
+        entries.retain(|entry| entry.installed && entry.details.gem_root().is_some());
     }
 
-    let release = match fetch_available_rubies(&config.cache).await {
+    if available_only {
+        entries.retain(|entry| !entry.installed);
+    }
+
+    sort_entries(&mut entries, sort);
+
+    if path_only {
+        for entry in &entries {
+            println!("{}", entry.details.executable_path());
+        }
+        return Ok(());
+    }
+
+    // Only cite the release if something from it is actually listed. Quiet mode still lists
+    // the rubies, just without the citation and hints around them.
+    if entries.iter().all(|entry| entry.installed) || config.quiet {
+        release_info = None;
+    }
+
+    if count {
+        return print_counts(&RubyCounts::of(&entries), format);
+    }
+
+    // Machine-readable formats always print their (possibly empty) document,
+    // the human hints are only for text output.
+    if entries.is_empty() && format.is_human_readable() {
+        if installed_only {
+            warn!("No Ruby installations found.");
+            info!("Try installing Ruby with 'rv ruby install <version>'");
+        } else {
+            warn!("No rubies found for your platform.");
+        }
+        return Ok(());
+    }
+
+    print_entries(&entries, release_info.as_ref(), format, show_url, group_by)
+}
+
+/// Fetches the available rubies, falling back to the cached list however old it is.
+async fn fetch_or_cached_release(config: &Config, refresh: Option<RefreshMode>) -> Release {
+    match fetch_available_rubies(config, refresh).await {
         Ok(release) => release,
         Err(e) => {
             warn!(
                 "Could not fetch or re-validate available Ruby versions: {}",
                 e
             );
-            let cache_entry = config.cache.entry(
+            let cached = http::cached_response(
+                &config.cache,
                 rv_cache::CacheBucket::Ruby,
-                "releases",
-                "available_rubies.json",
+                RELEASES_CACHE_KEY,
             );
-            if let Ok(content) = fs::read_to_string(cache_entry.path())
-                && let Ok(cached_data) = serde_json::from_str::<CachedRelease>(&content)
+            if let Some(cached) = cached
+                && let Ok(release) = serde_json::from_str::<Release>(&cached.body)
             {
-                warn!("Displaying stale list of available rubies from cache.");
-                cached_data.release
+                let stale_by = stale_by(cached.expires_at, SystemTime::now());
+                warn!(
+                    "Displaying stale list of available rubies from cache (stale by {}).",
+                    format_age(stale_by)
+                );
+                release
             } else {
                 Release {
                     name: "Empty".to_owned(),
+                    tag_name: None,
                     assets: Vec::new(),
                 }
             }
         }
-    };
+    }
+}
 
-    let entries = rubies_to_show(
-        release,
-        installed_rubies,
-        active_ruby,
-        current_platform_arch_str(),
+/// The cached list of available rubies for `--no-remote`, if it is still fresh.
+fn fresh_cached_release(config: &Config) -> Release {
+    let cached = http::fresh_cached_response(
+        &config.cache,
+        rv_cache::CacheBucket::Ruby,
+        RELEASES_CACHE_KEY,
+        config.releases_ttl,
     );
-    if entries.is_empty() && format == OutputFormat::Text {
-        warn!("No rubies found for your platform.");
-        return Ok(());
+    match cached.map(|cached| serde_json::from_str::<Release>(&cached.body)) {
+        Some(Ok(release)) => release,
+        _ => {
+            debug!("No fresh list of available rubies in the cache.");
+            Release {
+                name: "Empty".to_owned(),
+                tag_name: None,
+                assets: Vec::new(),
+            }
+        }
     }
+}
 
-    print_entries(&entries, format)
+/// How long ago the cache expired. A cache that expires in the future (e.g. because of
+/// clock skew) isn't stale at all.
+fn stale_by(expires_at: SystemTime, now: SystemTime) -> Duration {
+    now.duration_since(expires_at).unwrap_or(Duration::ZERO)
+}
+
+/// Formats a duration in its largest whole unit, e.g. `2h` or `3d`.
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+/// Keep only the `limit` newest available (not installed) entries.
+/// Installed and active entries are always kept, and the order is unchanged.
+fn limit_available(entries: Vec<JsonRubyEntry>, limit: usize) -> Vec<JsonRubyEntry> {
+    let mut available: Vec<(usize, &RubyRequest)> = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| !entry.installed && !entry.active)
+        .map(|(index, entry)| (index, &entry.details.version))
+        .collect();
+    available.sort_by(|(_, a), (_, b)| b.cmp(a));
+    let newest: HashSet<usize> = available
+        .into_iter()
+        .take(limit)
+        .map(|(index, _)| index)
+        .collect();
+
+    entries
+        .into_iter()
+        .enumerate()
+        .filter(|(index, entry)| entry.installed || entry.active || newest.contains(index))
+        .map(|(_, entry)| entry)
+        .collect()
 }
 
 /// Merge ruby lists from various sources, choose which ones to show to the user.
 /// E.g. don't show rv-ruby installable 3.3.2 if a later patch 3.3.9 is available.
-/// Don't show duplicates, etc.
+/// Don't show duplicates, etc. Available prereleases are only shown with `prereleases`,
+/// installed ones always are.
 fn rubies_to_show(
-    release: Release,
+    mut release: Release,
     installed_rubies: Vec<Ruby>,
     active_ruby: Option<Ruby>,
     current_platform: &'static str,
+    prereleases: bool,
 ) -> Vec<JsonRubyEntry> {
+    // Dropped before picking the latest patches, so a prerelease can't hide its final release.
+    if !prereleases {
+        drop_prereleases(&mut release);
+    }
+
     // Might have multiple installed rubies with the same version (e.g., "ruby-3.2.0" and "mruby-3.2.0").
-    let mut rubies_map: BTreeMap<String, Vec<Ruby>> = BTreeMap::new();
+    // Each ruby is stored along with whether it is installed.
+    let mut rubies_map: BTreeMap<String, Vec<(Ruby, bool)>> = BTreeMap::new();
     for ruby in installed_rubies {
         rubies_map
             .entry(ruby.display_name())
             .or_default()
-            .push(ruby);
+            .push((ruby, true));
     }
 
-    // Filter releases+assets for current platform
-    let (desired_os, desired_arch) = parse_arch_str(current_platform);
-    let rubies_for_this_platform: Vec<Ruby> = release
-        .assets
-        .iter()
-        .filter_map(|asset| ruby_from_asset(asset).ok())
-        .filter(|ruby| ruby.os == desired_os && ruby.arch == desired_arch)
-        .collect();
-
-    let available_rubies = latest_patch_version(rubies_for_this_platform);
-
-    debug!(
-        "Found {} available rubies for platform {}/{}",
-        available_rubies.len(),
-        desired_os,
-        desired_arch
-    );
+    let available_rubies = available_rubies_for_platform(&release, current_platform);
 
     // Merge in installed rubies, replacing any available ones with the installed versions
     for ruby in available_rubies {
@@ -355,27 +638,102 @@ fn rubies_to_show(
             rubies_map
                 .entry(ruby.display_name())
                 .or_default()
-                .push(ruby);
+                .push((ruby, false));
         }
     }
 
     // Create entries for output
-    let entries: Vec<JsonRubyEntry> = rubies_map
+    let mut entries: Vec<JsonRubyEntry> = rubies_map
         .into_values()
         .flatten()
-        .map(|ruby| {
-            let installed = !ruby.path.as_str().starts_with("http");
-            let active = active_ruby.as_ref().is_some_and(|a| a == &ruby);
+        .map(|(ruby, installed)| {
+            let active = active_ruby.as_ref().is_some_and(|a| a.same_install(&ruby));
             JsonRubyEntry {
                 installed,
                 active,
                 details: ruby,
+                updatable_to: None,
             }
         })
         .collect();
+    // The map is keyed by name, which would put 3.10 before 3.2. The sort is stable, so
+    // installs of the same version stay in the order they were discovered in.
+    entries.sort_by(|a, b| a.details.version.cmp(&b.details.version));
     entries
 }
 
+/// Drops the assets of prereleases, like `3.5.0-preview1`, from `release`.
+pub(crate) fn drop_prereleases(release: &mut Release) {
+    release.assets.retain(|asset| {
+        version_from_asset_name(&asset.name).is_ok_and(|version| version.prerelease.is_none())
+    });
+}
+
+/// Puts `entries` in the order asked for with `--sort`. The sorts are stable, so installs of
+/// the same version stay in the order they were discovered in.
+fn sort_entries(entries: &mut [JsonRubyEntry], sort: SortBy) {
+    match sort {
+        SortBy::Version => entries.sort_by(|a, b| a.details.version.cmp(&b.details.version)),
+        SortBy::Name => entries.sort_by_cached_key(|entry| entry.details.display_name()),
+        SortBy::Installed => {
+            entries.sort_by(|a, b| a.details.version.cmp(&b.details.version));
+            entries.sort_by_key(|entry| !entry.installed);
+        }
+    }
+}
+
+/// Sets `updatable_to` of installed entries to the newest available entry of the same minor
+/// version, if that is newer.
+fn mark_updatable(entries: &mut [JsonRubyEntry]) {
+    let same_minor = |a: &RubyVersion, b: &RubyVersion| {
+        a.engine == b.engine && a.major == b.major && a.minor == b.minor
+    };
+    let available: Vec<RubyVersion> = entries
+        .iter()
+        .filter(|entry| !entry.installed)
+        .map(|entry| entry.details.version.clone())
+        .collect();
+
+    for entry in entries.iter_mut().filter(|entry| entry.installed) {
+        let installed = &entry.details.version;
+        entry.updatable_to = available
+            .iter()
+            .filter(|version| same_minor(version, installed) && *version > installed)
+            .max()
+            .cloned();
+    }
+}
+
+/// The latest patch of each minor version in `release` that can be installed on this platform.
+pub(crate) fn latest_available_rubies(release: &Release) -> Vec<Ruby> {
+    available_rubies_for_platform(release, current_platform_arch_str())
+}
+
+pub(crate) fn available_rubies_for_platform(
+    release: &Release,
+    current_platform: &str,
+) -> Vec<Ruby> {
+    // Filter releases+assets for current platform. Collecting an indexed parallel
+    // iterator keeps the asset order, so the dedup below stays deterministic.
+    let (desired_os, desired_arch) = parse_arch_str(current_platform);
+    let rubies_for_this_platform: Vec<Ruby> = release
+        .assets
+        .par_iter()
+        .filter_map(|asset| ruby_from_asset(asset).ok())
+        .filter(|ruby| ruby.os == desired_os && ruby.arch == desired_arch)
+        .collect();
+
+    let available_rubies = latest_patch_version(rubies_for_this_platform);
+
+    debug!(
+        "Found {} available rubies for platform {}/{}",
+        available_rubies.len(),
+        desired_os,
+        desired_arch
+    );
+    available_rubies
+}
+
 fn latest_patch_version(rubies_for_this_platform: Vec<Ruby>) -> Vec<Ruby> {
     #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
     struct NonPatchRelease {
@@ -407,35 +765,249 @@ fn latest_patch_version(rubies_for_this_platform: Vec<Ruby>) -> Vec<Ruby> {
     available_rubies.into_values().collect()
 }
 
-fn print_entries(entries: &[JsonRubyEntry], format: OutputFormat) -> Result<()> {
+fn print_entries(
+    entries: &[JsonRubyEntry],
+    release: Option<&ReleaseInfo>,
+    format: OutputFormat,
+    show_url: bool,
+    group_by: Option<GroupBy>,
+) -> Result<()> {
     match format {
         OutputFormat::Text => {
+            if let Some(release) = release {
+                println!("{}", format!("Available from release {release}").dimmed());
+            }
             let width = entries
                 .iter()
                 .map(|e| e.details.display_name().len())
                 .max()
                 .unwrap_or(0);
-            for entry in entries {
-                println!("{}", format_ruby_entry(entry, width));
+            match group_by {
+                Some(GroupBy::Minor) => {
+                    for (minor, entries) in group_by_minor(entries) {
+                        println!("{}", minor_header(&minor).bold());
+                        for entry in entries {
+                            println!("  {}", format_ruby_entry(entry, width, show_url));
+                        }
+                    }
+                }
+                None => {
+                    for entry in entries {
+                        println!("{}", format_ruby_entry(entry, width, show_url));
+                    }
+                }
             }
         }
         OutputFormat::Json => {
-            serde_json::to_writer_pretty(io::stdout(), entries)?;
+            // Unlike YAML, the JSON writer doesn't end the document with a newline.
+            let mut stdout = io::stdout().lock();
+            serde_json::to_writer_pretty(
+                &mut stdout,
+                &JsonRubyList {
+                    release,
+                    rubies: entries,
+                },
+            )?;
+            writeln!(stdout)?;
+        }
+        OutputFormat::Yaml => serde_yaml::to_writer(
+            io::stdout(),
+            &JsonRubyList {
+                release,
+                rubies: entries,
+            },
+        )?,
+        OutputFormat::Table => {
+            if let Some(release) = release {
+                println!("{}", format!("Available from release {release}").dimmed());
+            }
+            for line in format_ruby_table(entries, show_url, group_by) {
+                println!("{line}");
+            }
+        }
+        OutputFormat::Ndjson => {
+            let mut stdout = io::stdout().lock();
+            for entry in entries {
+                serde_json::to_writer(&mut stdout, entry)?;
+                writeln!(stdout)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Groups the entries by their minor version, ordered by version rather than by name.
+fn group_by_minor(entries: &[JsonRubyEntry]) -> BTreeMap<RubyVersion, Vec<&JsonRubyEntry>> {
+    let mut groups: BTreeMap<RubyVersion, Vec<&JsonRubyEntry>> = BTreeMap::new();
+    for entry in entries {
+        let version = &entry.details.version;
+        let minor = RubyVersion {
+            engine: version.engine.clone(),
+            major: version.major,
+            minor: version.minor,
+            ..Default::default()
+        };
+        groups.entry(minor).or_default().push(entry);
+    }
+    groups
+}
+
+/// The header of a `--group-by minor` group: just the number for Ruby, e.g. `3.3`, but
+/// `jruby-9.4` for other engines.
+fn minor_header(minor: &RubyVersion) -> String {
+    if minor.engine == RubyEngine::Ruby {
+        minor.number()
+    } else {
+        minor.to_string()
+    }
+}
+
+/// How many of the listed rubies are in each state, for `--count`.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct RubyCounts {
+    installed: usize,
+    available: usize,
+    active: usize,
+}
+
+impl RubyCounts {
+    fn of(entries: &[JsonRubyEntry]) -> Self {
+        let installed = entries.iter().filter(|entry| entry.installed).count();
+        RubyCounts {
+            installed,
+            available: entries.len() - installed,
+            active: entries.iter().filter(|entry| entry.active).count(),
         }
     }
+}
+
+fn print_counts(counts: &RubyCounts, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Text | OutputFormat::Table => println!(
+            "installed={} available={} active={}",
+            counts.installed, counts.available, counts.active
+        ),
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            serde_json::to_writer(io::stdout(), counts)?;
+            println!();
+        }
+        OutputFormat::Yaml => serde_yaml::to_writer(io::stdout(), counts)?,
+    }
     Ok(())
 }
 
+/// Formats the entries for table output, each column padded to its own widest cell.
+fn format_ruby_table(
+    entries: &[JsonRubyEntry],
+    show_url: bool,
+    group_by: Option<GroupBy>,
+) -> Vec<String> {
+    let header = ["ACTIVE", "NAME", "STATUS", "PATH"].map(str::to_owned);
+    let cells = |entry: &JsonRubyEntry| -> [String; 4] {
+        let (status, path) = if entry.installed {
+            ("installed", entry.details.executable_path().into_string())
+        } else if show_url {
+            ("available", entry.details.path.to_string())
+        } else {
+            ("available", String::new())
+        };
+        [
+            if entry.active { "*" } else { "" }.to_owned(),
+            entry.details.display_name(),
+            status.to_owned(),
+            path,
+        ]
+    };
+
+    // The last column isn't padded, so it doesn't count.
+    let mut widths = header.clone().map(|cell| cell.len());
+    for row in entries.iter().map(cells) {
+        for (width, cell) in widths.iter_mut().zip(&row[..3]) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    // Pad before coloring, escape codes would throw off the widths otherwise.
+    let pad = |cell: &str, column: usize| format!("{cell:width$}", width = widths[column]);
+    let format_row = |entry: &JsonRubyEntry| {
+        let [marker, name, status, path] = cells(entry);
+        let (status, path) = if entry.installed {
+            (pad(&status, 2).green().to_string(), path.cyan().to_string())
+        } else {
+            (
+                pad(&status, 2).dimmed().to_string(),
+                path.dimmed().to_string(),
+            )
+        };
+        let line = format!(
+            "{} {} {status} {path}{}",
+            pad(&marker, 0),
+            pad(&name, 1),
+            annotations(entry)
+        );
+        line.trim_end().to_owned()
+    };
+
+    let mut lines = vec![
+        format!(
+            "{} {} {} {}",
+            pad(&header[0], 0),
+            pad(&header[1], 1),
+            pad(&header[2], 2),
+            header[3]
+        )
+        .bold()
+        .to_string(),
+    ];
+    match group_by {
+        Some(GroupBy::Minor) => {
+            for (minor, entries) in group_by_minor(entries) {
+                lines.push(minor_header(&minor).bold().to_string());
+                lines.extend(entries.into_iter().map(format_row));
+            }
+        }
+        None => lines.extend(entries.iter().map(format_row)),
+    }
+    lines
+}
+
+/// The notes after an installed entry: whether it has gems, and a newer patch if
+/// `--updatable` found one.
+fn annotations(entry: &JsonRubyEntry) -> String {
+    if !entry.installed {
+        return String::new();
+    }
+    let gems = if entry.details.gem_root().is_some() {
+        format!(" {}", "(gems)".dimmed())
+    } else {
+        String::new()
+    };
+    let update = match &entry.updatable_to {
+        Some(version) => format!(" {}", format!("({} available)", version.number()).yellow()),
+        None => String::new(),
+    };
+    format!("{gems}{update}")
+}
+
 /// Formats a single entry for text output.
-fn format_ruby_entry(entry: &JsonRubyEntry, width: usize) -> String {
+///
+/// With `show_url`, available entries end with their download URL, which is what `path` holds
+/// for rubies that aren't installed.
+fn format_ruby_entry(entry: &JsonRubyEntry, width: usize, show_url: bool) -> String {
     let marker = if entry.active { "*" } else { " " };
     let name = entry.details.display_name();
 
     if entry.installed {
         format!(
-            "{marker} {name:width$} {} {}",
+            "{marker} {name:width$} {} {}{}",
             "[installed]".green(),
-            entry.details.executable_path().cyan()
+            entry.details.executable_path().cyan(),
+            annotations(entry)
+        )
+    } else if show_url {
+        format!(
+            "{marker} {name:width$} {} {}",
+            "[available]".dimmed(),
+            entry.details.path.dimmed()
         )
     } else {
         format!("{marker} {name:width$} {}", "[available]".dimmed())
@@ -446,17 +1018,8 @@ fn format_ruby_entry(entry: &JsonRubyEntry, width: usize) -> String {
 mod tests {
     use super::*;
     use camino::Utf8PathBuf;
-    use rv_ruby::version::RubyVersion;
     use std::str::FromStr as _;
 
-    #[test]
-    fn test_parse_cache_header() {
-        let input_header = "Cache-Control: max-age=3600, must-revalidate";
-        let actual = parse_max_age(input_header).unwrap();
-        let expected = Duration::from_secs(3600);
-        assert_eq!(actual, expected);
-    }
-
     #[test]
     fn test_deser_release() {
         let jtxt = std::fs::read_to_string("../../testdata/api.json").unwrap();
@@ -481,6 +1044,65 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_deser_engine_prefixed_assets() {
+        let jtxt = r#"{
+            "name": "20251006",
+            "tag_name": "20251006",
+            "assets": [
+                {
+                    "name": "jruby-9.4.12.0.arm64_linux.tar.gz",
+                    "browser_download_url": "https://github.com/spinel-coop/rv-ruby/releases/download/20251006/jruby-9.4.12.0.arm64_linux.tar.gz"
+                },
+                {
+                    "name": "truffleruby-24.1.2.x86_64_linux.tar.gz",
+                    "browser_download_url": "https://github.com/spinel-coop/rv-ruby/releases/download/20251006/truffleruby-24.1.2.x86_64_linux.tar.gz"
+                },
+                {
+                    "name": "truffleruby-24.1.2.arm64_sonoma.tar.gz",
+                    "browser_download_url": "https://github.com/spinel-coop/rv-ruby/releases/download/20251006/truffleruby-24.1.2.arm64_sonoma.tar.gz"
+                }
+            ]
+        }"#;
+        let release: Release = serde_json::from_str(jtxt).unwrap();
+        let actual: Vec<_> = release
+            .assets
+            .iter()
+            .map(|asset| {
+                let ruby = ruby_from_asset(asset).unwrap();
+                (ruby.key, ruby.version.engine, ruby.os, ruby.arch)
+            })
+            .collect();
+        let expected = [
+            (
+                "jruby-9.4.12.0-linux-aarch64",
+                RubyEngine::JRuby,
+                "linux",
+                "aarch64",
+            ),
+            (
+                "truffleruby-24.1.2-linux-x86_64",
+                RubyEngine::TruffleRuby,
+                "linux",
+                "x86_64",
+            ),
+            (
+                "truffleruby-24.1.2-macos-aarch64",
+                RubyEngine::TruffleRuby,
+                "macos",
+                "aarch64",
+            ),
+        ]
+        .map(|(key, engine, os, arch)| (key.to_owned(), engine, os.to_owned(), arch.to_owned()));
+        assert_eq!(actual, expected);
+
+        let available = available_rubies_for_platform(&release, "arm64_sonoma");
+        let names: Vec<_> = available.iter().map(|ruby| ruby.display_name()).collect();
+        assert_eq!(names, ["truffleruby-24.1.2"]);
+    }
+
+    // Installed or not, these rubies have a URL as their path, so `rubies_to_show` has to
+    // know which ones are installed without looking at the path.
     fn ruby(version: &str) -> Ruby {
         let version = RubyVersion::from_str(version).unwrap();
         let version_str = version.to_string();
@@ -497,6 +1119,259 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_stale_by() {
+        let now = SystemTime::now();
+        let two_hours = Duration::from_secs(2 * 3600);
+
+        assert_eq!(stale_by(now - two_hours, now), two_hours);
+        assert_eq!(stale_by(now, now), Duration::ZERO);
+        // Clock skew can put the expiry in the future.
+        assert_eq!(stale_by(now + two_hours, now), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(Duration::ZERO), "0s");
+        assert_eq!(format_age(Duration::from_secs(59)), "59s");
+        assert_eq!(format_age(Duration::from_secs(60)), "1m");
+        assert_eq!(format_age(Duration::from_secs(2 * 3600 + 59 * 60)), "2h");
+        assert_eq!(format_age(Duration::from_secs(3 * 86400)), "3d");
+    }
+
+    #[test]
+    fn test_limit_available_keeps_newest_and_installed() {
+        let entry = |version: &str, installed: bool| JsonRubyEntry {
+            details: ruby(version),
+            installed,
+            active: false,
+            updatable_to: None,
+        };
+        let entries = vec![
+            entry("ruby-3.1.7", true),
+            entry("ruby-3.2.9", false),
+            entry("ruby-3.3.9", false),
+            entry("ruby-3.4.5", false),
+            entry("ruby-3.5.0-preview1", false),
+        ];
+
+        let versions = |entries: Vec<JsonRubyEntry>| -> Vec<String> {
+            entries
+                .iter()
+                .map(|entry| entry.details.version.to_string())
+                .collect()
+        };
+
+        let limited = limit_available(entries, 2);
+        assert_eq!(
+            versions(limited),
+            ["ruby-3.1.7", "ruby-3.4.5", "ruby-3.5.0-preview1"]
+        );
+    }
+
+    #[test]
+    fn test_limit_available_zero_keeps_only_installed() {
+        let entries = vec![
+            JsonRubyEntry {
+                details: ruby("ruby-3.4.5"),
+                installed: false,
+                active: false,
+                updatable_to: None,
+            },
+            JsonRubyEntry {
+                details: ruby("ruby-3.3.0"),
+                installed: false,
+                active: true,
+                updatable_to: None,
+            },
+        ];
+
+        let limited = limit_available(entries, 0);
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].details.version.to_string(), "ruby-3.3.0");
+    }
+
+    #[test]
+    fn test_rubies_to_show_is_deterministic_with_many_assets() {
+        let assets: Vec<Asset> = (0..20)
+            .flat_map(|minor| (0..10).map(move |patch| (minor, patch)))
+            .flat_map(|(minor, patch)| {
+                ["arm64_sonoma", "x86_64_linux", "arm64_linux"].map(|arch| Asset {
+                    name: format!("ruby-3.{minor}.{patch}.{arch}.tar.gz"),
+                    browser_download_url: format!("https://example.com/3.{minor}.{patch}/{arch}"),
+                })
+            })
+            .collect();
+        let release = Release {
+            name: "latest".to_owned(),
+            tag_name: None,
+            assets,
+        };
+
+        let first = rubies_to_show(release.clone(), Vec::new(), None, "arm64_linux", false);
+        let versions: Vec<String> = first
+            .iter()
+            .map(|entry| entry.details.version.to_string())
+            .collect();
+        let expected: Vec<String> = (0..20).map(|minor| format!("ruby-3.{minor}.9")).collect();
+        assert_eq!(versions.len(), 20);
+        assert!(versions.iter().all(|v| expected.contains(v)));
+
+        for _ in 0..10 {
+            let again = rubies_to_show(release.clone(), Vec::new(), None, "arm64_linux", false);
+            assert_eq!(again, first);
+        }
+    }
+
+    #[test]
+    fn test_rubies_to_show_hides_available_prereleases() {
+        let release = Release {
+            name: "latest".to_owned(),
+            tag_name: None,
+            assets: ["3.4.1", "3.5.0-preview1"]
+                .map(|version| Asset {
+                    name: format!("ruby-{version}.arm64_sonoma.tar.gz"),
+                    browser_download_url: format!("https://example.com/{version}"),
+                })
+                .into(),
+        };
+        let installed = vec![ruby("ruby-3.6.0-preview2")];
+        let shown = |prereleases: bool| -> Vec<(String, bool)> {
+            rubies_to_show(
+                release.clone(),
+                installed.clone(),
+                None,
+                "arm64_sonoma",
+                prereleases,
+            )
+            .into_iter()
+            .map(|entry| (entry.details.display_name(), entry.installed))
+            .collect()
+        };
+
+        assert_eq!(
+            shown(false),
+            [
+                ("ruby-3.4.1".to_owned(), false),
+                ("ruby-3.6.0-preview2".to_owned(), true),
+            ]
+        );
+        assert_eq!(
+            shown(true),
+            [
+                ("ruby-3.4.1".to_owned(), false),
+                ("ruby-3.5.0-preview1".to_owned(), false),
+                ("ruby-3.6.0-preview2".to_owned(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rubies_to_show_sorts_versions_semantically() {
+        let release = Release {
+            name: "latest".to_owned(),
+            tag_name: None,
+            assets: ["3.2.9", "3.10.0"]
+                .map(|version| Asset {
+                    name: format!("ruby-{version}.arm64_sonoma.tar.gz"),
+                    browser_download_url: format!("https://example.com/{version}"),
+                })
+                .into(),
+        };
+        let installed = ruby("ruby-3.2.0");
+
+        let entries = rubies_to_show(
+            release,
+            vec![installed.clone()],
+            Some(installed),
+            "arm64_sonoma",
+            false,
+        );
+        let shown: Vec<(String, bool, bool)> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.details.version.to_string(),
+                    entry.installed,
+                    entry.active,
+                )
+            })
+            .collect();
+        assert_eq!(
+            shown,
+            [
+                ("ruby-3.2.0".to_owned(), true, true),
+                ("ruby-3.2.9".to_owned(), false, false),
+                ("ruby-3.10.0".to_owned(), false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mark_updatable() {
+        let entry = |version: &str, installed: bool| JsonRubyEntry {
+            details: ruby(version),
+            installed,
+            active: false,
+            updatable_to: None,
+        };
+        let mut entries = vec![
+            entry("ruby-3.3.4", true),
+            entry("ruby-3.3.7", false),
+            entry("ruby-3.4.1", true),
+            entry("ruby-3.4.0", false),
+            entry("jruby-9.4.12.0", true),
+        ];
+
+        mark_updatable(&mut entries);
+        let updatable: Vec<Option<String>> = entries
+            .iter()
+            .map(|entry| entry.updatable_to.as_ref().map(ToString::to_string))
+            .collect();
+        assert_eq!(
+            updatable,
+            [Some("ruby-3.3.7".to_owned()), None, None, None, None]
+        );
+    }
+
+    #[test]
+    fn test_sort_entries() {
+        let entry = |version: &str, installed: bool| JsonRubyEntry {
+            details: ruby(version),
+            installed,
+            active: false,
+            updatable_to: None,
+        };
+        let mut entries = vec![
+            entry("ruby-3.10.0", false),
+            entry("ruby-3.9.1", true),
+            entry("jruby-9.4.12.0", true),
+            entry("ruby-3.8.0", false),
+        ];
+        let names = |entries: &[JsonRubyEntry]| -> Vec<String> {
+            entries
+                .iter()
+                .map(|entry| entry.details.display_name())
+                .collect()
+        };
+
+        sort_entries(&mut entries, SortBy::Version);
+        assert_eq!(
+            names(&entries),
+            ["ruby-3.8.0", "ruby-3.9.1", "ruby-3.10.0", "jruby-9.4.12.0"]
+        );
+        sort_entries(&mut entries, SortBy::Name);
+        assert_eq!(
+            names(&entries),
+            ["jruby-9.4.12.0", "ruby-3.10.0", "ruby-3.8.0", "ruby-3.9.1"]
+        );
+        sort_entries(&mut entries, SortBy::Installed);
+        assert_eq!(
+            names(&entries),
+            ["ruby-3.9.1", "jruby-9.4.12.0", "ruby-3.8.0", "ruby-3.10.0"]
+        );
+    }
+
     #[test]
     fn test_rubies_to_show() {
         struct Test {
@@ -520,6 +1395,7 @@ mod tests {
                 test_name: "no local installs",
                 release: Release {
                     name: "latest".to_owned(),
+                    tag_name: None,
                     assets: vec![Asset {
                         name: "ruby-3.3.0.arm64_sonoma.tar.gz".to_owned(),
                         browser_download_url: u("3.3.0"),
@@ -532,6 +1408,7 @@ mod tests {
                     details: ruby("ruby-3.3.0"),
                     installed: false,
                     active: false,
+                    updatable_to: None,
                 }],
             },
             // Nothing weird should happen if there's no remotely-available versions.
@@ -539,6 +1416,7 @@ mod tests {
                 test_name: "only local installs, no remote available",
                 release: Release {
                     name: "latest".to_owned(),
+                    tag_name: None,
                     assets: Vec::new(),
                 },
                 installed_rubies: vec![ruby("ruby-3.3.0")],
@@ -546,8 +1424,9 @@ mod tests {
                 current_platform_arch: "arm64_sonoma",
                 expected: vec![JsonRubyEntry {
                     details: ruby("ruby-3.3.0"),
-                    installed: false,
+                    installed: true,
                     active: false,
+                    updatable_to: None,
                 }],
             },
             // Locally-installed and remotely-available both get merged together.
@@ -555,6 +1434,7 @@ mod tests {
                 test_name: "both local and remote, different minor versions",
                 release: Release {
                     name: "latest".to_owned(),
+                    tag_name: None,
                     assets: vec![Asset {
                         name: "ruby-3.4.0.arm64_sonoma.tar.gz".to_owned(),
                         browser_download_url: u("3.4.0"),
@@ -566,13 +1446,15 @@ mod tests {
                 expected: vec![
                     JsonRubyEntry {
                         details: ruby("ruby-3.3.0"),
-                        installed: false,
+                        installed: true,
                         active: false,
+                        updatable_to: None,
                     },
                     JsonRubyEntry {
                         details: ruby("ruby-3.4.0"),
                         installed: false,
                         active: false,
+                        updatable_to: None,
                     },
                 ],
             },
@@ -583,6 +1465,7 @@ mod tests {
                 test_name: "both local and remote, different patch versions",
                 release: Release {
                     name: "latest".to_owned(),
+                    tag_name: None,
                     assets: vec![Asset {
                         name: "ruby-3.4.0.arm64_sonoma.tar.gz".to_owned(),
                         browser_download_url: u("3.4.0"),
@@ -596,11 +1479,13 @@ mod tests {
                         details: ruby("ruby-3.4.0"),
                         installed: false,
                         active: false,
+                        updatable_to: None,
                     },
                     JsonRubyEntry {
                         details: ruby("ruby-3.4.1"),
-                        installed: false,
+                        installed: true,
                         active: false,
+                        updatable_to: None,
                     },
                 ],
             },
@@ -609,6 +1494,7 @@ mod tests {
                 test_name: "both local and remote, different patch versions",
                 release: Release {
                     name: "latest".to_owned(),
+                    tag_name: None,
                     assets: vec![
                         Asset {
                             name: "ruby-3.4.0.arm64_sonoma.tar.gz".to_owned(),
@@ -626,13 +1512,50 @@ mod tests {
                 expected: vec![
                     JsonRubyEntry {
                         details: ruby("ruby-3.3.1"),
-                        installed: false,
+                        installed: true,
                         active: false,
+                        updatable_to: None,
                     },
                     JsonRubyEntry {
                         details: ruby("ruby-3.4.1"),
                         installed: false,
                         active: false,
+                        updatable_to: None,
+                    },
+                ],
+            },
+            // The active ruby is found by resolving the project's request, so it can differ
+            // from the discovered install in fields that don't identify the install.
+            Test {
+                test_name: "active install found through a symlink",
+                release: Release {
+                    name: "latest".to_owned(),
+                    tag_name: None,
+                    assets: vec![Asset {
+                        name: "ruby-3.4.1.arm64_sonoma.tar.gz".to_owned(),
+                        browser_download_url: u("3.4.1"),
+                    }],
+                },
+                installed_rubies: vec![ruby("ruby-3.3.1"), ruby("ruby-3.4.1")],
+                active_ruby: Some(Ruby {
+                    key: "ruby-3.4.1-alias".to_owned(),
+                    symlink: Some("/opt/rubies/3.4".into()),
+                    gem_root: Some("/tmp/home/.gem/ruby/3.4.1".into()),
+                    ..ruby("ruby-3.4.1")
+                }),
+                current_platform_arch: "arm64_sonoma",
+                expected: vec![
+                    JsonRubyEntry {
+                        details: ruby("ruby-3.3.1"),
+                        installed: true,
+                        active: false,
+                        updatable_to: None,
+                    },
+                    JsonRubyEntry {
+                        details: ruby("ruby-3.4.1"),
+                        installed: true,
+                        active: true,
+                        updatable_to: None,
                     },
                 ],
             },
@@ -652,6 +1575,7 @@ mod tests {
                 installed_rubies,
                 active_ruby,
                 current_platform_arch,
+                false,
             );
             pretty_assertions::assert_eq!(actual, expected, "failed test case '{test_name}'");
         }
@@ -1,6 +1,10 @@
 use anstream::println;
 use miette::Diagnostic;
 use owo_colors::OwoColorize;
+use rv_ruby::{
+    engine::RubyEngine,
+    request::{RequestError, RubyRequest},
+};
 
 use crate::config::{self, Config};
 
@@ -10,24 +14,122 @@ pub enum Error {
     ConfigError(#[from] config::Error),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    RequestError(#[from] RequestError),
 }
 
 type Result<T> = miette::Result<T, Error>;
 
-pub fn pin(config: &Config, version: Option<String>) -> Result<()> {
+pub fn pin(
+    config: &Config,
+    version: Option<String>,
+    unset: bool,
+    list_candidates: bool,
+) -> Result<()> {
+    if unset {
+        return unset_pinned_ruby(config);
+    }
+
     match version {
         None => show_pinned_ruby(config),
+        Some(version) if list_candidates => list_pin_candidates(config, version),
         Some(version) => set_pinned_ruby(config, version),
     }
 }
 
+/// Prints the installed rubies that satisfy `version`, the one that would be used first.
+fn list_pin_candidates(config: &Config, version: String) -> Result<()> {
+    let request: RubyRequest = version.parse()?;
+    let candidates = config.matching_rubies(&request);
+
+    if candidates.is_empty() {
+        println!(
+            "No installed Ruby matches {0}, it can still be pinned and installed later",
+            canonical_request(&request).cyan()
+        );
+        return Ok(());
+    }
+
+    println!("Pinning {0} would use:", canonical_request(&request).cyan());
+    for (i, ruby) in candidates.iter().enumerate() {
+        let marker = if i == 0 { "*" } else { " " };
+        println!(
+            "{marker} {0} {1}",
+            ruby.display_name(),
+            ruby.executable_path().cyan()
+        );
+    }
+    Ok(())
+}
+
 fn set_pinned_ruby(config: &Config, version: String) -> Result<()> {
     let project_dir = config.project_dir.as_ref().unwrap_or(&config.current_dir);
 
-    let ruby_version_path = project_dir.join(".ruby-version");
+    let version = canonical_request(&version.parse()?);
+
+    let ruby_version_path = config.ruby_version_path(project_dir);
+    let previous = std::fs::read_to_string(&ruby_version_path)
+        .ok()
+        .map(|content| {
+            config::version_file_request(&content)
+                .unwrap_or_default()
+                .to_owned()
+        });
     std::fs::write(ruby_version_path, format!("{version}\n"))?;
 
-    println!("{0} pinned to Ruby {1}", project_dir.cyan(), version.cyan());
+    if config.quiet {
+        return Ok(());
+    }
+    match previous {
+        None => println!(
+            "{0} pinned to Ruby {1} (created {2})",
+            project_dir.cyan(),
+            version.cyan(),
+            config.ruby_version_file
+        ),
+        Some(previous) if previous != version => println!(
+            "{0} pinned to Ruby {1} -> {2}",
+            project_dir.cyan(),
+            previous.red(),
+            version.green()
+        ),
+        Some(_) => println!("{0} pinned to Ruby {1}", project_dir.cyan(), version.cyan()),
+    }
+
+    Ok(())
+}
+
+/// Formats a request the way it is written to `.ruby-version`: bare for MRI (`3.3.0`),
+/// engine-prefixed for everything else (`jruby-9.4`).
+fn canonical_request(request: &RubyRequest) -> String {
+    if request.engine == RubyEngine::Ruby && request.major.is_some() {
+        request.number()
+    } else {
+        request.to_string()
+    }
+}
+
+fn unset_pinned_ruby(config: &Config) -> Result<()> {
+    let project_dir = config.project_dir.as_ref().unwrap_or(&config.current_dir);
+    let ruby_version_path = config.ruby_version_path(project_dir);
+
+    if !ruby_version_path.exists() {
+        if !config.quiet {
+            println!("{0} has no pinned Ruby version", project_dir.cyan());
+        }
+        return Ok(());
+    }
+
+    let ruby_version = std::fs::read_to_string(&ruby_version_path)?;
+    std::fs::remove_file(&ruby_version_path)?;
+
+    if !config.quiet {
+        println!(
+            "Removed {0} (was pinned to Ruby {1})",
+            ruby_version_path.cyan(),
+            ruby_version.trim().cyan()
+        );
+    }
 
     Ok(())
 }
@@ -38,7 +140,7 @@ fn show_pinned_ruby(config: &Config) -> Result<()> {
             current_dir: config.current_dir.clone(),
         })
     })?;
-    let path = project_dir.join(".ruby-version");
+    let path = config.ruby_version_path(project_dir);
     let ruby_version = std::fs::read_to_string(path)?;
 
     println!(
@@ -66,13 +168,9 @@ mod tests {
         let current_dir = root.join("project");
 
         let config = Config {
-            ruby_dirs: vec![ruby_dir],
-            gemfile: None,
-            current_exe: root.join("bin").join("rv"),
-            project_dir: Some(project_dir),
+            ruby_dirs: [ruby_dir].into(),
             current_dir,
-            cache: rv_cache::Cache::temp().unwrap(),
-            root,
+            ..config::tests::test_config(&root, Some(project_dir))
         };
 
         Ok(config)
@@ -84,9 +182,9 @@ mod tests {
 
         let ruby_version_file = config.project_dir.as_ref().unwrap().join(".ruby-version");
         std::fs::write(&ruby_version_file, "3.2.0").unwrap();
-        pin(&config, None).unwrap();
+        pin(&config, None, false, false).unwrap();
         std::fs::write(&ruby_version_file, "3.2.0").unwrap();
-        pin(&config, None).unwrap();
+        pin(&config, None, false, false).unwrap();
     }
 
     #[test]
@@ -95,7 +193,7 @@ mod tests {
         let version = "3.2.0".to_string();
 
         // Should not panic - basic smoke test
-        pin(&config, Some(version.clone())).unwrap();
+        pin(&config, Some(version.clone()), false, false).unwrap();
 
         // Verify the file was created
         let ruby_version_path = config.project_dir.unwrap().join(".ruby-version");
@@ -111,10 +209,10 @@ mod tests {
         let second_version = "3.2.0".to_string();
 
         // Pin first version
-        pin(&config, Some(first_version)).unwrap();
+        pin(&config, Some(first_version), false, false).unwrap();
 
         // Pin second version (should overwrite)
-        pin(&config, Some(second_version.clone())).unwrap();
+        pin(&config, Some(second_version.clone()), false, false).unwrap();
 
         // Verify the file contains the second version
         let ruby_version_path = config.project_dir.unwrap().join(".ruby-version");
@@ -127,7 +225,7 @@ mod tests {
         let config = test_config().unwrap();
         let version = "3.3.0-preview1".to_string();
 
-        pin(&config, Some(version.clone())).unwrap();
+        pin(&config, Some(version.clone()), false, false).unwrap();
 
         let ruby_version_path = config.project_dir.unwrap().join(".ruby-version");
         let content = std::fs::read_to_string(ruby_version_path).unwrap();
@@ -139,10 +237,98 @@ mod tests {
         let config = test_config().unwrap();
         let version = "1.9.2-p0".to_string();
 
-        pin(&config, Some(version.clone())).unwrap();
+        pin(&config, Some(version.clone()), false, false).unwrap();
 
         let ruby_version_path = config.project_dir.unwrap().join(".ruby-version");
         let content = std::fs::read_to_string(ruby_version_path).unwrap();
         assert_eq!(content, format!("{version}\n"));
     }
+
+    #[test]
+    fn test_pin_unset_removes_file() {
+        let config = test_config().unwrap();
+        pin(&config, Some("3.2.0".to_string()), false, false).unwrap();
+
+        pin(&config, None, true, false).unwrap();
+
+        let ruby_version_path = config.project_dir.unwrap().join(".ruby-version");
+        assert!(!ruby_version_path.exists());
+    }
+
+    #[test]
+    fn test_pin_unset_without_pin_is_noop() {
+        let config = test_config().unwrap();
+
+        pin(&config, None, true, false).unwrap();
+        pin(&config, None, true, false).unwrap();
+    }
+
+    #[test]
+    fn test_pin_ruby_with_engine_prefix() {
+        let config = test_config().unwrap();
+
+        pin(&config, Some("jruby-9.4".to_string()), false, false).unwrap();
+
+        let ruby_version_path = config.project_dir.as_ref().unwrap().join(".ruby-version");
+        let content = std::fs::read_to_string(ruby_version_path).unwrap();
+        assert_eq!(content, "jruby-9.4\n");
+
+        let request = config.ruby_request().unwrap();
+        assert_eq!(request.engine, RubyEngine::JRuby);
+        assert_eq!(request.number(), "9.4");
+    }
+
+    #[test]
+    fn test_pin_ruby_prefixed_and_bare_requests_round_trip_the_same() {
+        let config = test_config().unwrap();
+        let ruby_version_path = config.project_dir.as_ref().unwrap().join(".ruby-version");
+
+        pin(&config, Some("ruby-3.3.0".to_string()), false, false).unwrap();
+        let prefixed_content = std::fs::read_to_string(&ruby_version_path).unwrap();
+        let prefixed = config.ruby_request().unwrap();
+
+        pin(&config, Some("3.3.0".to_string()), false, false).unwrap();
+        let bare_content = std::fs::read_to_string(&ruby_version_path).unwrap();
+        let bare = config.ruby_request().unwrap();
+
+        assert_eq!(prefixed_content, "3.3.0\n");
+        assert_eq!(prefixed_content, bare_content);
+        assert_eq!(prefixed, bare);
+    }
+
+    #[test]
+    fn test_pin_ruby_reads_back_hand_written_prefixed_request() {
+        let config = test_config().unwrap();
+        let ruby_version_path = config.project_dir.as_ref().unwrap().join(".ruby-version");
+
+        std::fs::write(&ruby_version_path, "ruby-3.3.0\n").unwrap();
+        let prefixed = config.ruby_request().unwrap();
+        std::fs::write(&ruby_version_path, "3.3.0\n").unwrap();
+        let bare = config.ruby_request().unwrap();
+
+        assert_eq!(prefixed, bare);
+    }
+
+    #[test]
+    fn test_pin_ruby_keeps_non_mri_engine_prefix() {
+        let config = test_config().unwrap();
+
+        pin(&config, Some("jruby-9.4.0.0".to_string()), false, false).unwrap();
+
+        let ruby_version_path = config.project_dir.as_ref().unwrap().join(".ruby-version");
+        let content = std::fs::read_to_string(ruby_version_path).unwrap();
+        assert_eq!(content, "jruby-9.4.0.0\n");
+        assert_eq!(
+            config.ruby_request().unwrap(),
+            "jruby-9.4.0.0".parse::<RubyRequest>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pin_ruby_rejects_invalid_request() {
+        let config = test_config().unwrap();
+
+        let err = pin(&config, Some("3.x".to_string()), false, false).unwrap_err();
+        assert!(matches!(err, Error::RequestError(_)));
+    }
 }
@@ -0,0 +1,25 @@
+use anstream::println;
+use owo_colors::OwoColorize;
+
+use crate::config::Config;
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum Error {
+    #[error("Could not clear the cached Ruby information")]
+    IoError(#[from] std::io::Error),
+}
+
+type Result<T> = miette::Result<T, Error>;
+
+/// Drop the cached information about every Ruby installation and probe them all again.
+///
+/// Unlike `rv cache clean`, this keeps everything else in the cache, like downloaded tarballs.
+pub fn reshim(config: &Config) -> Result<()> {
+    config.clear_cached_rubies()?;
+    let rubies = config.rubies();
+
+    if !config.quiet {
+        println!("Refreshed {} rubies", rubies.len().cyan());
+    }
+    Ok(())
+}
@@ -16,11 +16,16 @@ pub enum Error {
 
 type Result<T> = miette::Result<T, Error>;
 
-pub fn run(config: &Config, request: &RubyRequest, args: &[String]) -> Result<()> {
-    let Some(ruby) = config.matching_ruby(request) else {
+/// Runs `ruby` with `args`, picking the ruby from `request` or else from the project's pin.
+pub fn run(config: &Config, request: Option<RubyRequest>, args: &[String]) -> Result<()> {
+    let request = match request {
+        Some(request) => request,
+        None => config.ruby_request()?,
+    };
+    let Some(ruby) = config.matching_ruby(&request) else {
         return Err(Error::NoMatchingRuby);
     };
-    let (unset, set) = config::env_for(Some(&ruby))?;
+    let (unset, set) = config::env_for(Some(&ruby), config.gemfile.as_deref())?;
     let mut cmd = Command::new(ruby.executable_path());
     cmd.args(args);
     for var in unset {
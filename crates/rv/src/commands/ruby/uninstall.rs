@@ -24,7 +24,9 @@ type Result<T> = miette::Result<T, Error>;
 pub async fn uninstall(config: &Config, request: RubyRequest) -> Result<()> {
     if let Some(ruby) = config.matching_ruby(&request) {
         let ruby_path = ruby.path;
-        println!("Deleting {}", ruby_path.cyan());
+        if !config.quiet {
+            println!("Deleting {}", ruby_path.cyan());
+        }
 
         // Delete the dir at this Ruby version's path.
         fs_err::remove_dir_all(&ruby_path).map_err(|error| Error::IoError {
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use anstream::println;
+use owo_colors::OwoColorize;
+use rv_ruby::request::RubyRequest;
+use tracing::debug;
+
+use super::{install, pin};
+use crate::config::{self, Config};
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum Error {
+    #[error(transparent)]
+    ConfigError(#[from] config::Error),
+    #[error(transparent)]
+    InstallError(#[from] install::Error),
+    #[error(transparent)]
+    PinError(#[from] pin::Error),
+    #[error("The pinned request {0} has no minor version to upgrade within")]
+    NoMinorVersion(RubyRequest),
+}
+
+type Result<T> = miette::Result<T, Error>;
+
+/// Upgrades the project's Ruby to the latest patch of its pinned minor version, installing it
+/// if needed and pinning the project to it. With `all`, installs the latest patch of every
+/// installed minor version instead, and leaves the pin alone.
+pub async fn upgrade(config: &Config, all: bool) -> Result<()> {
+    if all {
+        return upgrade_all(config).await;
+    }
+
+    let project_dir = config
+        .project_dir
+        .as_ref()
+        .ok_or_else(|| config::Error::NoProjectDir {
+            current_dir: config.current_dir.clone(),
+        })?;
+    let pinned = config.ruby_request()?;
+    if pinned.minor.is_none() {
+        return Err(Error::NoMinorVersion(pinned));
+    }
+
+    let latest = upgrade_minor(config, &pinned).await?;
+    debug!("Pinning {project_dir} to {latest}");
+    pin::pin(config, Some(latest.to_string()), false, false)?;
+    Ok(())
+}
+
+/// Installs the latest patch of each minor version that has an install.
+async fn upgrade_all(config: &Config) -> Result<()> {
+    // The newest install of each minor version.
+    let mut minors: BTreeMap<RubyRequest, RubyRequest> = BTreeMap::new();
+    for ruby in config.rubies() {
+        let minor = minor_request(&ruby.version);
+        if minors
+            .get(&minor)
+            .is_none_or(|newest| newest < &ruby.version)
+        {
+            minors.insert(minor, ruby.version);
+        }
+    }
+
+    for minor in minors.keys() {
+        match upgrade_minor(config, minor).await {
+            Ok(_) => {}
+            // Not every installed engine or version is released by rv.
+            Err(Error::InstallError(install::Error::NoMatchingRelease { requested, .. })) => {
+                debug!("No release of {requested} to upgrade to");
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+/// Installs the latest available patch of the minor version of `request`, unless it already is.
+async fn upgrade_minor(config: &Config, request: &RubyRequest) -> Result<RubyRequest> {
+    let minor = minor_request(request);
+    let latest = install::resolve_partial_request(config, &minor, None).await?;
+
+    if config.matching_ruby(&latest).is_some() {
+        if !config.quiet {
+            println!(
+                "Ruby {} is already the latest patch of {}",
+                latest.to_string().cyan(),
+                minor.to_string().cyan()
+            );
+        }
+    } else {
+        let args = install::InstallArgs {
+            version: Some(latest.clone()),
+            ..Default::default()
+        };
+        install::install(config, args).await?;
+    }
+    Ok(latest)
+}
+
+/// `request` without anything more specific than its minor version, e.g. `ruby-3.3`.
+fn minor_request(request: &RubyRequest) -> RubyRequest {
+    RubyRequest {
+        engine: request.engine.clone(),
+        major: request.major,
+        minor: request.minor,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minor_request() {
+        let request: RubyRequest = "3.3.4".parse().unwrap();
+        assert_eq!(minor_request(&request).to_string(), "ruby-3.3");
+
+        let request: RubyRequest = "jruby-9.4.12.0".parse().unwrap();
+        assert_eq!(minor_request(&request).to_string(), "jruby-9.4");
+    }
+}
@@ -16,17 +16,43 @@ pub enum ShellCommand {
     #[command(about = "Configure your shell to use rv")]
     Init {
         /// The shell to initialize (zsh, bash and fish so far)
-        shell: Shell,
+        #[arg(required_unless_present = "print_path")]
+        shell: Option<Shell>,
+
+        /// Print the PATH entries rv would set for the active Ruby, one per line, instead
+        #[arg(long, conflicts_with = "shell")]
+        print_path: bool,
     },
     #[command(about = "Configure shell completions to use rv")]
     Completions {
         /// The shell to print completions for (zsh, bash and fish so far)
         shell: Shell,
+
+        /// Write the completions to where the shell loads them from, instead of printing them
+        #[arg(long)]
+        install: bool,
+
+        /// Overwrite completions that were installed before
+        #[arg(long, requires = "install")]
+        force: bool,
     },
     #[command(hide = true)]
     Env {
         /// The shell to configure (zsh, bash and fish so far)
         shell: Shell,
+
+        /// Deactivate any Ruby, unsetting all of rv's variables and removing its PATH entries
+        #[arg(long)]
+        unset_all: bool,
+
+        /// Also set RV_RUBY_SOURCE to the file the active Ruby's version came from, or `default`
+        #[arg(long)]
+        source: bool,
+
+        /// For Nushell, print `{"unset": [...], "set": {...}}` so the unset variables can be
+        /// removed with `hide-env`, instead of a single record for `load-env`
+        #[arg(long)]
+        hide_env: bool,
     },
 }
 
@@ -37,4 +63,6 @@ pub enum Shell {
     Bash,
     Fish,
     Nu,
+    #[value(name = "powershell")]
+    PowerShell,
 }
@@ -1,27 +1,120 @@
-use std::io::stdout;
+use std::env;
+use std::io::{Write, stdout};
 
+use anstream::println;
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::ValueEnum as _;
 use clap_complete::{Shell as ClapCompleteShell, generate};
+use owo_colors::OwoColorize;
 
 use super::Shell;
+use crate::config::{self, Config};
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum Error {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("Could not find your home directory")]
+    NoHomeDir,
+    #[error(
+        "rv doesn't know where {0} loads completions from, redirect the output of `rv shell completions {0}` instead"
+    )]
+    NoCompletionsDir(String),
+    #[error("{0} already exists, use --force to overwrite it")]
+    AlreadyExists(Utf8PathBuf),
+}
+
+type Result<T> = miette::Result<T, Error>;
 
 pub fn shell_completions(cmd: &mut clap::Command, shell: Shell) {
+    write_completions(cmd, shell, &mut stdout());
+}
+
+/// Writes the completions for `shell` to where it loads them from by default.
+pub fn install_completions(
+    config: &Config,
+    cmd: &mut clap::Command,
+    shell: Shell,
+    force: bool,
+) -> Result<()> {
+    let path = completions_path(&config.root, &shell)?;
+    if path.exists() && !force {
+        return Err(Error::AlreadyExists(path));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut script = Vec::new();
+    write_completions(cmd, shell.clone(), &mut script);
+    std::fs::write(&path, script)?;
+
+    if !config.quiet {
+        println!(
+            "Installed {} completions to {}",
+            shell_name(&shell),
+            path.cyan()
+        );
+        if let Shell::Zsh = shell {
+            println!(
+                "Make sure {} is in your fpath before compinit runs",
+                path.parent().unwrap_or(&path).cyan()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The conventional per-user location of the completions script for `shell`.
+fn completions_path(root: &Utf8Path, shell: &Shell) -> Result<Utf8PathBuf> {
+    let home = config::home_dir_in(root).ok_or(Error::NoHomeDir)?;
+    // Like other XDG variables, these are ignored unless they are absolute paths.
+    let xdg_dir = |var: &str, default: &str| {
+        env::var(var)
+            .ok()
+            .filter(|dir| Utf8Path::new(dir).is_absolute())
+            .map(|dir| root.join(dir.trim_start_matches('/')))
+            .unwrap_or_else(|| home.join(default))
+    };
+
+    match shell {
+        Shell::Zsh => Ok(home.join(".zsh/completions/_rv")),
+        Shell::Bash => {
+            Ok(xdg_dir("XDG_DATA_HOME", ".local/share").join("bash-completion/completions/rv"))
+        }
+        Shell::Fish => Ok(xdg_dir("XDG_CONFIG_HOME", ".config").join("fish/completions/rv.fish")),
+        Shell::Nu | Shell::PowerShell => Err(Error::NoCompletionsDir(shell_name(shell))),
+    }
+}
+
+fn shell_name(shell: &Shell) -> String {
+    shell
+        .to_possible_value()
+        .map_or_else(String::new, |value| value.get_name().to_owned())
+}
+
+fn write_completions(cmd: &mut clap::Command, shell: Shell, out: &mut dyn Write) {
     let name = cmd.get_name().to_owned();
     match shell {
         Shell::Zsh => {
             let clap_complete_shell: ClapCompleteShell = ClapCompleteShell::Zsh;
-            generate(clap_complete_shell, cmd, name, &mut stdout());
+            generate(clap_complete_shell, cmd, name, out);
         }
         Shell::Bash => {
             let clap_complete_shell: ClapCompleteShell = ClapCompleteShell::Bash;
-            generate(clap_complete_shell, cmd, name, &mut stdout());
+            generate(clap_complete_shell, cmd, name, out);
         }
         Shell::Fish => {
             let clap_complete_shell: ClapCompleteShell = ClapCompleteShell::Fish;
-            generate(clap_complete_shell, cmd, name, &mut stdout());
+            generate(clap_complete_shell, cmd, name, out);
         }
         Shell::Nu => {
             let clap_complete_shell = clap_complete_nushell::Nushell;
-            generate(clap_complete_shell, cmd, name, &mut stdout());
+            generate(clap_complete_shell, cmd, name, out);
+        }
+        Shell::PowerShell => {
+            let clap_complete_shell: ClapCompleteShell = ClapCompleteShell::PowerShell;
+            generate(clap_complete_shell, cmd, name, out);
         }
     }
 }
@@ -1,3 +1,5 @@
+use std::env::split_paths;
+
 use super::Shell;
 use crate::config;
 
@@ -13,9 +15,26 @@ pub enum Error {
 
 type Result<T> = miette::Result<T, Error>;
 
-pub fn env(config: &config::Config, shell: Shell) -> Result<()> {
-    let ruby = config.project_ruby();
-    let (unset, set) = config::env_for(ruby.as_ref())?;
+pub fn env(
+    config: &config::Config,
+    shell: Shell,
+    unset_all: bool,
+    source: bool,
+    hide_env: bool,
+) -> Result<()> {
+    // Without a Ruby, `env_for` only undoes what a previous activation set.
+    let ruby = if unset_all {
+        None
+    } else {
+        config.project_ruby()
+    };
+    let (mut unset, mut set) = config::env_for(ruby.as_ref(), config.gemfile.as_deref())?;
+    if source {
+        match ruby.and_then(|_| config.active_source()) {
+            Some(source) => set.push(("RV_RUBY_SOURCE", source.to_string())),
+            None => unset.push("RV_RUBY_SOURCE"),
+        }
+    }
 
     match shell {
         Shell::Zsh | Shell::Bash => {
@@ -40,13 +59,28 @@ pub fn env(config: &config::Config, shell: Shell) -> Result<()> {
             Ok(())
         }
         Shell::Nu => {
-            // Emit JSON which will be run by `load-env`.
+            // Emit JSON which will be run by `load-env`, or by `hide-env` and `load-env`.
+            // A hook can't evaluate `$env.VAR = ...` lines printed by a command, Nushell
+            // only sources files known when the hook is parsed.
             // See <https://www.nushell.sh/commands/docs/load-env.html>
-            let env_json = nu_env(unset, set);
+            let env_json = if hide_env {
+                nu_env_with_unset(unset, set)
+            } else {
+                nu_env(unset, set)
+            };
             let serialized = serde_json::to_string(&env_json).expect("serializing JSON");
             println!("{}", serialized);
             Ok(())
         }
+        Shell::PowerShell => {
+            for var in unset {
+                println!("Remove-Item Env:\\{var} -ErrorAction SilentlyContinue");
+            }
+            for (var, val) in set {
+                println!("$env:{var} = \"{}\"", powershell_var_escape(val));
+            }
+            Ok(())
+        }
     }
 }
 
@@ -60,12 +94,38 @@ fn nu_env(unset: Vec<&str>, set: Vec<(&str, String)>) -> serde_json::Value {
             serde_json::Value::Object(Default::default()),
         );
     }
-    for (var, val) in set {
-        env_changes.insert(var.to_owned(), serde_json::Value::String(val));
-    }
+    env_changes.extend(nu_env_set(set));
     serde_json::Value::Object(env_changes)
 }
 
+/// Like [`nu_env`], but with the variables to remove with `hide-env` kept apart from the ones
+/// to set with `load-env`, for `--hide-env`.
+fn nu_env_with_unset(unset: Vec<&str>, set: Vec<(&str, String)>) -> serde_json::Value {
+    serde_json::json!({
+        "unset": unset,
+        "set": nu_env_set(set),
+    })
+}
+
+/// The variables to set, for `load-env`.
+///
+/// Nushell's PATH is a list, so it's emitted as one rather than a joined string.
+fn nu_env_set(set: Vec<(&str, String)>) -> serde_json::Map<String, serde_json::Value> {
+    set.into_iter()
+        .map(|(var, val)| {
+            let val = if var == "PATH" {
+                split_paths(&val)
+                    .filter(|path| !path.as_os_str().is_empty())
+                    .map(|path| serde_json::Value::String(path.to_string_lossy().into_owned()))
+                    .collect()
+            } else {
+                serde_json::Value::String(val)
+            };
+            (var.to_owned(), val)
+        })
+        .collect()
+}
+
 // From uv's crates/uv-shell/src/lib.rs
 // Assumes strings will be outputed as "str", so escapes any \ or " character
 fn fish_var_escape(s: String) -> String {
@@ -80,6 +140,19 @@ fn fish_var_escape(s: String) -> String {
     escaped
 }
 
+// Assumes strings will be outputed as "str", so escapes any `, " or $ character
+fn powershell_var_escape(s: String) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '`' | '"' | '$' => escaped.push('`'),
+            _ => {}
+        }
+        escaped.push(c)
+    }
+    escaped
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,7 +160,7 @@ mod tests {
 
     #[test]
     fn nushell_env_serializes_changes() {
-        let unset = vec!["RUBY_ROOT", "GEM_PATH"];
+        let unset = vec!["RUBYOPT", "GEM_PATH"];
         let set = vec![
             ("PATH", "/tmp/bin".to_owned()),
             ("RUBY_ROOT", "/new/ruby".to_owned()),
@@ -96,13 +169,53 @@ mod tests {
         let env_json = nu_env(unset, set);
 
         let expected = json!({
-            "RUBY_ROOT": "/new/ruby",
+            "RUBYOPT": {},
             "GEM_PATH": {},
-            "PATH": "/tmp/bin",
+            "RUBY_ROOT": "/new/ruby",
+            "PATH": ["/tmp/bin"],
         });
 
         assert_eq!(env_json, expected);
         let _ = serde_json::to_string(&env_json)
             .expect("Serializing the Nushell env changes to JSON should always succeed");
     }
+
+    #[test]
+    fn nushell_env_with_unset_separates_changes() {
+        let unset = vec!["RUBYOPT", "GEM_PATH"];
+        let set = vec![
+            ("PATH", "/tmp/bin".to_owned()),
+            ("RUBY_ROOT", "/new/ruby".to_owned()),
+        ];
+
+        let env_json = nu_env_with_unset(unset, set);
+
+        let expected = json!({
+            "unset": ["RUBYOPT", "GEM_PATH"],
+            "set": {
+                "RUBY_ROOT": "/new/ruby",
+                "PATH": ["/tmp/bin"],
+            },
+        });
+
+        assert_eq!(env_json, expected);
+    }
+
+    #[test]
+    fn nushell_env_emits_path_as_list() {
+        let path = std::env::join_paths(["/a/bin", "/b/bin"]).unwrap();
+        let set = vec![("PATH", path.into_string().unwrap())];
+
+        let env_json = nu_env(vec![], set);
+
+        assert_eq!(env_json["PATH"], json!(["/a/bin", "/b/bin"]));
+    }
+
+    #[test]
+    fn powershell_escapes_special_characters() {
+        assert_eq!(
+            powershell_var_escape(r#"C:\Ruby "$HOME" `tick`"#.to_owned()),
+            r#"C:\Ruby `"`$HOME`" ``tick``"#
+        );
+    }
 }
@@ -1,5 +1,5 @@
 use super::Shell;
-use crate::config::Config;
+use crate::config::{self, Config};
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
 pub enum Error {
@@ -10,16 +10,22 @@ pub enum Error {
 type Result<T> = miette::Result<T, Error>;
 
 pub fn init(config: &Config, shell: Shell) -> Result<()> {
+    // The POSIX and fish snippets are wrapped in a guard so that sourcing them twice (e.g. by
+    // re-reading the rc file) doesn't install the hooks twice. The guard variable is deliberately
+    // not exported, so nested shells, which don't inherit the hook functions, still set them up.
     match shell {
         Shell::Zsh => {
             print!(
                 concat!(
+                    "if [ -z \"${{__RV_INITIALIZED:-}}\" ]; then\n",
+                    "__RV_INITIALIZED=1\n",
                     "autoload -U add-zsh-hook\n",
                     "_rv_autoload_hook () {{\n",
                     "    eval \"$({} shell env zsh)\"\n",
                     "}}\n",
                     "add-zsh-hook chpwd _rv_autoload_hook\n",
                     "_rv_autoload_hook\n",
+                    "fi\n",
                 ),
                 config.current_exe
             );
@@ -28,6 +34,8 @@ pub fn init(config: &Config, shell: Shell) -> Result<()> {
         Shell::Bash => {
             print!(
                 concat!(
+                    "if [ -z \"${{__RV_INITIALIZED:-}}\" ]; then\n",
+                    "__RV_INITIALIZED=1\n",
                     "_rv_autoload_hook() {{\n",
                     "    eval \"$({} shell env bash)\"\n",
                     "}}\n",
@@ -40,6 +48,7 @@ pub fn init(config: &Config, shell: Shell) -> Result<()> {
                     "}}\n",
                     "_OLDPWD=\"$PWD\"\n",
                     "PROMPT_COMMAND=\"_chpwd_hook${{PROMPT_COMMAND:+; $PROMPT_COMMAND}}\"\n",
+                    "fi\n",
                 ),
                 config.current_exe
             );
@@ -48,11 +57,14 @@ pub fn init(config: &Config, shell: Shell) -> Result<()> {
         Shell::Fish => {
             print!(
                 concat!(
+                    "if not set -q __RV_INITIALIZED\n",
+                    "set -g __RV_INITIALIZED 1\n",
                     "function _rv_autoload_hook --on-variable PWD --description 'Change Ruby version on directory change using rv'\n",
                     "    status --is-command-substitution; and return\n",
                     "    {} shell env fish | source\n",
                     "end\n",
-                    "_rv_autoload_hook\n"
+                    "_rv_autoload_hook\n",
+                    "end\n",
                 ),
                 config.current_exe
             );
@@ -66,7 +78,10 @@ pub fn init(config: &Config, shell: Shell) -> Result<()> {
                     "$env.config = ($env.config | upsert hooks.env_change.PWD {{\n",
                     "    [\n",
                     "        {{\n",
-                    "            |_, _| {} shell env nu | from json | load-env\n",
+                    "            |_, _|\n",
+                    "            let rv_env = ({} shell env nu --hide-env | from json)\n",
+                    "            hide-env --ignore-errors ...$rv_env.unset\n",
+                    "            load-env $rv_env.set\n",
                     "        }}\n",
                     "    ]\n",
                     "}})\n",
@@ -75,5 +90,30 @@ pub fn init(config: &Config, shell: Shell) -> Result<()> {
             );
             Ok(())
         }
+        Shell::PowerShell => {
+            // PowerShell 7.3+ runs `LocationChangedAction` after every change of directory.
+            print!(
+                concat!(
+                    "function global:_rv_autoload_hook {{\n",
+                    "    & '{}' shell env powershell | Out-String | Invoke-Expression\n",
+                    "}}\n",
+                    "$ExecutionContext.SessionState.InvokeCommand.LocationChangedAction = {{\n",
+                    "    _rv_autoload_hook\n",
+                    "}}\n",
+                    "_rv_autoload_hook\n",
+                ),
+                config.current_exe
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Prints the PATH that `rv shell env` would set for the active Ruby, one entry per line,
+/// annotated with where each entry comes from.
+pub fn print_path(config: &Config) {
+    let ruby = config.project_ruby();
+    for (path, source) in config::path_for(ruby.as_ref()) {
+        println!("{} ({source})", path.display());
     }
 }
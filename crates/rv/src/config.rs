@@ -1,22 +1,61 @@
 use std::{
+    collections::{BTreeMap, HashSet},
     env::{self, JoinPathsError, join_paths, split_paths},
+    fmt,
     path::{Path, PathBuf},
+    sync::{Once, OnceLock},
+    time::Duration,
 };
 
 use camino::{Utf8Path, Utf8PathBuf};
-use tracing::{debug, instrument};
+use indexmap::IndexSet;
+use tracing::{debug, instrument, warn};
 
 use rv_ruby::{
     Ruby,
     request::{RequestError, RubyRequest},
 };
 
+mod global_config;
+mod project_config;
 mod ruby_cache;
 
+pub use global_config::{GLOBAL_CONFIG_FILE, GlobalConfig};
+pub use project_config::{PROJECT_CONFIG_FILE, ProjectConfig};
+
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
 pub enum Error {
     #[error("No project was found in the parents of {}", current_dir)]
     NoProjectDir { current_dir: Utf8PathBuf },
+    #[error("No Ruby version found in {}", path)]
+    NoRequest { path: Utf8PathBuf },
+    #[error("Invalid project configuration in {}", path)]
+    InvalidProjectConfig {
+        path: Utf8PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("Invalid configuration in {}", path)]
+    InvalidGlobalConfig {
+        path: Utf8PathBuf,
+        // Boxed, so the error stays small enough to return everywhere.
+        #[source]
+        source: Box<toml::de::Error>,
+    },
+    #[error("Could not read configuration file {}", path)]
+    UnreadableGlobalConfig {
+        path: Utf8PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Could not expand Ruby directory {path}")]
+    UnexpandableRubyDir {
+        path: Utf8PathBuf,
+        #[source]
+        source: shellexpand::LookupError<env::VarError>,
+    },
+    #[error("Your current directory no longer exists, cd somewhere valid and try again")]
+    NoCurrentDir(#[source] std::io::Error),
     #[error("Ruby cache miss or invalid cache for {}", ruby_path)]
     RubyCacheMiss { ruby_path: Utf8PathBuf },
     #[error(transparent)]
@@ -31,29 +70,83 @@ pub enum Error {
 
 type Result<T> = miette::Result<T, Error>;
 
+pub const DEFAULT_RUBY_VERSION_FILE: &str = ".ruby-version";
+
+/// Returns the process' working directory, which can fail when it was deleted out from under
+/// a long-running shell.
+pub fn current_dir() -> Result<std::path::PathBuf> {
+    std::env::current_dir().map_err(|err| match err.kind() {
+        std::io::ErrorKind::NotFound => Error::NoCurrentDir(err),
+        _ => Error::IoError(err),
+    })
+}
+
 #[derive(Debug)]
 pub struct Config {
-    pub ruby_dirs: Vec<Utf8PathBuf>,
+    /// Directories searched for Ruby installations, in order of precedence.
+    pub ruby_dirs: IndexSet<Utf8PathBuf>,
+    /// Ruby installations listed in chruby's `RUBIES`, searched after `ruby_dirs`.
+    pub chruby_rubies: Vec<Utf8PathBuf>,
     pub gemfile: Option<Utf8PathBuf>,
     pub root: Utf8PathBuf,
     pub current_dir: Utf8PathBuf,
     pub project_dir: Option<Utf8PathBuf>,
+    /// Name of the file that pins a project's Ruby version, `.ruby-version` by default.
+    pub ruby_version_file: String,
+    /// Settings from the project's `rv.toml`, if it has one.
+    pub project_config: ProjectConfig,
     pub cache: rv_cache::Cache,
     pub current_exe: Utf8PathBuf,
+    /// Never make network requests, only use what is already cached.
+    pub offline: bool,
+    /// How long network requests may stall before giving up.
+    pub timeout: Duration,
+    /// How long the cached list of available rubies stays fresh, instead of the server's `max-age`.
+    pub releases_ttl: Option<Duration>,
+    /// Shared by every network request of this run, see [`Config::http_client`].
+    pub http_client: OnceLock<reqwest::Client>,
+    /// Suppress hints, confirmations and other decorations, leaving the requested output and errors.
+    pub quiet: bool,
 }
 
 impl Config {
     #[instrument(skip_all)]
     pub fn rubies(&self) -> Vec<Ruby> {
-        self.discover_rubies()
+        let rubies = self.discover_rubies();
+        static WARN_SHADOWED: Once = Once::new();
+        WARN_SHADOWED.call_once(|| warn_shadowed_rubies(&rubies, &self.ruby_dirs));
+        rubies
     }
 
+    /// Returns the installed Ruby with the greatest version that satisfies `request`.
+    ///
+    /// When the same version is installed in several Ruby directories, the one in the
+    /// directory that comes first in `ruby_dirs` wins. Directories passed with `--ruby-dir`
+    /// (or the defaults) come first, followed by any added with `--add-ruby-dir`.
     pub fn matching_ruby(&self, request: &RubyRequest) -> Option<Ruby> {
-        let rubies = self.rubies();
-        rubies
-            .into_iter()
-            .rev()
-            .find(|ruby| request.satisfied_by(ruby))
+        self.matching_rubies(request).into_iter().next()
+    }
+
+    /// Returns every installed Ruby that satisfies `request`, newest first.
+    ///
+    /// Installs of the same version are ordered like in [`Config::matching_ruby`].
+    pub fn matching_rubies(&self, request: &RubyRequest) -> Vec<Ruby> {
+        sorted_matching_rubies(self.rubies(), request, &self.ruby_dirs)
+    }
+
+    /// The HTTP client for all network requests, built on first use and reused after that, so
+    /// requests share its connection pool.
+    pub fn http_client(&self) -> reqwest::Result<&reqwest::Client> {
+        if let Some(client) = self.http_client.get() {
+            return Ok(client);
+        }
+        let client = crate::http::client(self.timeout)?;
+        Ok(self.http_client.get_or_init(|| client))
+    }
+
+    /// The version file in `dir`, named after `ruby_version_file`.
+    pub fn ruby_version_path(&self, dir: &Utf8Path) -> Utf8PathBuf {
+        dir.join(&self.ruby_version_file)
     }
 
     pub fn project_ruby(&self) -> Option<Ruby> {
@@ -65,32 +158,85 @@ impl Config {
     }
 
     pub fn ruby_request(&self) -> Result<RubyRequest> {
+        self.requested_ruby().map(|(request, _)| request)
+    }
+
+    /// Where the active Ruby's version came from, or `None` if no installed Ruby matches it.
+    pub fn active_source(&self) -> Option<Source> {
+        let (request, source) = self.requested_ruby().ok()?;
+        self.matching_ruby(&request).map(|_| source)
+    }
+
+    /// The project's Ruby request, along with where it came from.
+    fn requested_ruby(&self) -> Result<(RubyRequest, Source)> {
         if let Some(project_dir) = &self.project_dir {
-            let rv_file = project_dir.join(".ruby-version");
+            let rv_file = self.ruby_version_path(project_dir);
+            // The version file is what `rv ruby pin` writes, so it wins over `rv.toml`.
+            if !rv_file.exists()
+                && let Some(request) = &self.project_config.ruby
+            {
+                let source = Source::ProjectConfig(project_dir.join(PROJECT_CONFIG_FILE));
+                return Ok((request.parse::<RubyRequest>()?, source));
+            }
 
-            std::fs::read_to_string(&rv_file)
-                .map_err(Error::from)
-                .and_then(|s| Ok(s.parse::<RubyRequest>()?))
+            let content = std::fs::read_to_string(&rv_file)?;
+            let request = version_file_request(&content).ok_or(Error::NoRequest {
+                path: rv_file.clone(),
+            })?;
+            Ok((
+                without_gemset(request).parse::<RubyRequest>()?,
+                Source::VersionFile(rv_file),
+            ))
         } else {
-            Ok(RubyRequest::default())
+            Ok((RubyRequest::default(), Source::Default))
         }
     }
 }
 
+/// Extracts the request from the contents of a `.ruby-version` file: the first line that
+/// isn't blank or a comment, without any trailing `# comment`.
+pub(crate) fn version_file_request(content: &str) -> Option<&str> {
+    content
+        .lines()
+        .map(|line| {
+            line.split_once('#')
+                .map_or(line, |(request, _)| request)
+                .trim()
+        })
+        .find(|line| !line.is_empty())
+}
+
+/// Drops the `@gemset` suffix of RVM-style requests like `ruby-3.3.0@app`. rv has no
+/// gemsets, so only the version is used.
+fn without_gemset(request: &str) -> &str {
+    let Some((request, gemset)) = request.split_once('@') else {
+        return request;
+    };
+    static WARN_GEMSET: Once = Once::new();
+    WARN_GEMSET.call_once(|| warn!("Ignoring gemset {gemset}, rv doesn't support gemsets"));
+    request
+}
+
+/// The per-user install location, `$XDG_DATA_HOME/rv/rubies`.
+///
+/// Like other XDG variables, `XDG_DATA_HOME` is ignored unless it is an absolute path.
 fn xdg_env_var_path() -> Option<String> {
     let xdg_data_home = env::var("XDG_DATA_HOME").ok()?;
-    let path_buf = Path::new(&xdg_data_home).join("rv/rubies");
-    Some(path_buf.to_str()?.to_owned())
+    let xdg_data_home = Path::new(&xdg_data_home);
+    if !xdg_data_home.is_absolute() {
+        return None;
+    }
+    Some(xdg_data_home.join("rv/rubies").to_str()?.to_owned())
 }
 
-struct PathInfo<'a> {
-    path: &'a str,
+struct PathInfo {
+    path: Utf8PathBuf,
     // Always include, even if it doesn't exist yet.
     always_include: bool,
 }
 
-impl<'a> PathInfo<'a> {
-    pub fn new(path: &'a str, always_include: bool) -> Self {
+impl PathInfo {
+    pub fn new(path: Utf8PathBuf, always_include: bool) -> Self {
         Self {
             path,
             always_include,
@@ -99,49 +245,152 @@ impl<'a> PathInfo<'a> {
 }
 
 /// Default Ruby installation directories
+///
+/// The first one is where `rv ruby install` puts rubies by default: `$XDG_DATA_HOME/rv/rubies`,
+/// falling back to `~/.local/share/rv/rubies`. The rest are only searched if they exist.
+/// Like every other path, the directories in the home directory are placed under `root`.
 pub fn default_ruby_dirs(root: &Utf8Path) -> Vec<Utf8PathBuf> {
+    let home = home_dir_in(root);
     let mut paths: Vec<PathInfo> = vec![];
-    let xdg_path = xdg_env_var_path();
-    if let Some(xdg_path) = &xdg_path {
-        paths.push(PathInfo::new(xdg_path, true));
+    let user_path = xdg_env_var_path()
+        .map(|path| root.join(path.strip_prefix('/').unwrap_or(&path)))
+        .or_else(|| {
+            home.as_ref()
+                .map(|home| home.join(".local/share/rv/rubies"))
+        });
+    if let Some(user_path) = user_path {
+        paths.push(PathInfo::new(user_path, true));
     }
-    let default_path = shellexpand::tilde("~/.data/rv/rubies");
-    paths.push(PathInfo::new(default_path.as_ref(), true));
 
-    // TODO: The paths below are never used, even if they exist (and the ones above don't).
-    let legacy_default_path = shellexpand::tilde("~/.rubies");
-    paths.push(PathInfo::new(legacy_default_path.as_ref(), false));
-    paths.push(PathInfo::new("/opt/rubies", false));
-    paths.push(PathInfo::new("/usr/local/rubies", false));
+    if let Some(home) = &home {
+        // Where rv used to install rubies before following the XDG layout.
+        paths.push(PathInfo::new(home.join(".data/rv/rubies"), false));
+
+        // Shared with chruby and other version managers.
+        paths.push(PathInfo::new(home.join(".rubies"), false));
+    }
+    paths.push(PathInfo::new(root.join("opt/rubies"), false));
+    paths.push(PathInfo::new(root.join("usr/local/rubies"), false));
 
     paths
         .into_iter()
         .filter_map(|path_info| {
-            let joinable_path = path_info.path.strip_prefix("/").unwrap();
-            let joined_path = root.join(joinable_path);
             if path_info.always_include {
-                Some(joined_path)
+                Some(path_info.path)
             } else {
-                joined_path.canonicalize_utf8().ok()
+                path_info.path.canonicalize_utf8().ok()
             }
         })
         .collect()
 }
 
-pub fn find_project_dir(current_dir: Utf8PathBuf, root: Utf8PathBuf) -> Option<Utf8PathBuf> {
+/// Resolves a Ruby directory given by the user, expanding `~` and environment variables.
+///
+/// Like the default directories, the result is placed under `root`. Relative paths are relative
+/// to `root` as well.
+pub fn expand_ruby_dir(root: &Utf8Path, path: &Utf8Path) -> Result<Utf8PathBuf> {
+    let expanded =
+        shellexpand::full_with_context(path.as_str(), home_dir, |var| env::var(var).map(Some))
+            .map_err(|source| Error::UnexpandableRubyDir {
+                path: path.to_path_buf(),
+                source,
+            })?;
+    let joinable_path = expanded.strip_prefix('/').unwrap_or(&expanded);
+    Ok(root.join(joinable_path))
+}
+
+/// Parses chruby's `RUBIES`, a list of Ruby installations separated by colons or newlines.
+///
+/// Shells can't export chruby's `RUBIES` array, so it only reaches rv as one of these lists,
+/// like `RUBIES="$(printf '%s\n' "${RUBIES[@]}")"`. Each entry is expanded like a Ruby directory.
+pub fn parse_chruby_rubies(root: &Utf8Path, value: &str) -> Result<Vec<Utf8PathBuf>> {
+    value
+        .split([':', '\n'])
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(|path| expand_ruby_dir(root, Utf8Path::new(path)))
+        .collect()
+}
+
+/// Searches `current_dir` and its parents for a `version_file` (usually `.ruby-version`) or
+/// `rv.toml` file.
+///
+/// The search stops at `root`, at the user's home directory when starting from inside it,
+/// and, if `stop_at_git` is set, at the first directory containing `.git`.
+pub fn find_project_dir(
+    current_dir: Utf8PathBuf,
+    root: Utf8PathBuf,
+    stop_at_git: bool,
+    version_file: &str,
+) -> Option<Utf8PathBuf> {
+    let home = home_dir_in(&root);
+    search_project_dir(
+        current_dir,
+        &root,
+        home.as_deref(),
+        stop_at_git,
+        version_file,
+    )
+}
+
+/// The user's home directory, `RV_HOME` if it's set, so sandboxes can have a home of their own.
+fn home_dir() -> Option<Utf8PathBuf> {
+    let home = match env::var_os("RV_HOME").filter(|home| !home.is_empty()) {
+        Some(home) => home.into(),
+        None => env::home_dir()?,
+    };
+    Utf8PathBuf::from_path_buf(home).ok()
+}
+
+/// The user's home directory, relative to `root`.
+pub(crate) fn home_dir_in(root: &Utf8Path) -> Option<Utf8PathBuf> {
+    let home = home_dir()?;
+    let relative_home = home.strip_prefix("/").unwrap_or(&home);
+    Some(root.join(relative_home))
+}
+
+fn search_project_dir(
+    current_dir: Utf8PathBuf,
+    root: &Utf8Path,
+    home: Option<&Utf8Path>,
+    stop_at_git: bool,
+    version_file: &str,
+) -> Option<Utf8PathBuf> {
     debug!("Searching for project directory in {}", current_dir);
+    // Only treat home as a boundary when we start inside it.
+    let home = home.filter(|home| current_dir.starts_with(home));
     let mut project_dir = current_dir.clone();
+    let mut git_root = None;
 
     loop {
-        let ruby_version = project_dir.join(".ruby-version");
-        if ruby_version.exists() {
+        if project_dir.join(version_file).exists() || project_dir.join(PROJECT_CONFIG_FILE).exists()
+        {
             debug!("Found project directory {}", project_dir);
             return Some(project_dir);
         }
 
+        if git_root.is_none() && project_dir.join(".git").exists() {
+            git_root = Some(project_dir.clone());
+            if stop_at_git {
+                debug!(
+                    "Reached git repository {} without finding a project directory",
+                    project_dir
+                );
+                break;
+            }
+        }
+
+        if home.is_some_and(|home| project_dir == home) {
+            debug!(
+                "Reached home {} without finding a project directory",
+                project_dir
+            );
+            break;
+        }
+
         if project_dir == root {
             debug!("Reached root {} without finding a project directory", root);
-            return None;
+            break;
         }
 
         if let Some(parent_dir) = project_dir.parent() {
@@ -151,11 +400,38 @@ pub fn find_project_dir(current_dir: Utf8PathBuf, root: Utf8PathBuf) -> Option<U
                 "Ran out of parents of {} without finding a project directory",
                 project_dir
             );
-            return None;
+            break;
         }
     }
+
+    // A linked worktree usually lacks untracked files like `.ruby-version`, fall back to
+    // the main worktree of its repository.
+    let main_worktree = main_worktree_dir(&git_root?)?;
+    if main_worktree.join(version_file).exists() {
+        debug!("Found project directory {} of main worktree", main_worktree);
+        return Some(main_worktree);
+    }
+    None
+}
+
+/// The main worktree of the repository `git_root` is a linked worktree of, if it is one.
+///
+/// The `.git` of a linked worktree is a file like `gitdir: /repo/.git/worktrees/<name>`.
+fn main_worktree_dir(git_root: &Utf8Path) -> Option<Utf8PathBuf> {
+    let content = std::fs::read_to_string(git_root.join(".git")).ok()?;
+    let gitdir = git_root.join(content.trim().strip_prefix("gitdir:")?.trim());
+    let gitdir = gitdir.canonicalize_utf8().ok()?;
+    let worktrees = gitdir.parent()?;
+    if worktrees.file_name() != Some("worktrees") {
+        return None;
+    }
+    Some(worktrees.parent()?.parent()?.to_owned())
 }
 
+/// Set along with `BUNDLE_GEMFILE` to the Gemfile rv exported, so that a later activation can
+/// tell it apart from a `BUNDLE_GEMFILE` the user set.
+const RV_BUNDLE_GEMFILE: &str = "RV_BUNDLE_GEMFILE";
+
 const ENV_VARS: [&str; 7] = [
     "RUBY_ROOT",
     "RUBY_ENGINE",
@@ -166,10 +442,35 @@ const ENV_VARS: [&str; 7] = [
     "GEM_PATH",
 ];
 
+/// The environment variables to unset and to set to activate `ruby`, or to deactivate any Ruby.
+///
+/// With a `gemfile`, `BUNDLE_GEMFILE` points at it, and gems that Bundler vendored into
+/// `vendor/bundle` next to it come first in `GEM_PATH`. Without one, a `BUNDLE_GEMFILE` that
+/// rv exported earlier is unset.
 #[allow(clippy::type_complexity)]
-pub fn env_for(ruby: Option<&Ruby>) -> Result<(Vec<&'static str>, Vec<(&'static str, String)>)> {
+pub fn env_for(
+    ruby: Option<&Ruby>,
+    gemfile: Option<&Utf8Path>,
+) -> Result<(Vec<&'static str>, Vec<(&'static str, String)>)> {
+    env_for_with(ruby, gemfile, &process_env())
+}
+
+/// Like [`env_for`], but relative to the variables in `env` instead of rv's own environment,
+/// e.g. to undo what an earlier activation added to `env`'s PATH.
+#[allow(clippy::type_complexity)]
+pub fn env_for_with(
+    ruby: Option<&Ruby>,
+    gemfile: Option<&Utf8Path>,
+    env: &BTreeMap<String, String>,
+) -> Result<(Vec<&'static str>, Vec<(&'static str, String)>)> {
     let mut unset: Vec<_> = ENV_VARS.into();
     let mut set: Vec<(&'static str, String)> = vec![];
+    if let Some(exported) = env.get(RV_BUNDLE_GEMFILE) {
+        if env.get("BUNDLE_GEMFILE") == Some(exported) {
+            unset.push("BUNDLE_GEMFILE");
+        }
+        unset.push(RV_BUNDLE_GEMFILE);
+    }
 
     let mut insert = |var: &'static str, val: String| {
         // PATH is never in the list to unset
@@ -180,47 +481,786 @@ pub fn env_for(ruby: Option<&Ruby>) -> Result<(Vec<&'static str>, Vec<(&'static
         set.push((var, val));
     };
 
-    let pathstr = std::env::var("PATH").unwrap_or_else(|_| String::new());
-    let mut paths = split_paths(&pathstr).collect::<Vec<_>>();
-
-    let old_ruby_paths: Vec<PathBuf> = ["RUBY_ROOT", "GEM_ROOT", "GEM_HOME"]
-        .iter()
-        .filter_map(|var| std::env::var(var).ok())
-        .map(|p| std::path::Path::new(&p).join("bin"))
-        .collect();
-
-    let old_gem_paths: Vec<PathBuf> =
-        std::env::var("GEM_PATH").map_or_else(|_| vec![], |p| split_paths(&p).collect::<Vec<_>>());
-
-    // Remove old Ruby and Gem paths from PATH
-    paths.retain(|p| !old_ruby_paths.contains(p) && !old_gem_paths.contains(p));
-
     if let Some(ruby) = ruby {
         let mut gem_paths = vec![];
-        paths.insert(0, ruby.bin_path().into());
         insert("RUBY_ROOT", ruby.path.to_string());
         insert("RUBY_ENGINE", ruby.version.engine.name().into());
         insert("RUBY_VERSION", ruby.version.number());
         if let Some(gem_home) = ruby.gem_home() {
-            paths.insert(0, gem_home.join("bin").into());
             gem_paths.insert(0, gem_home.clone());
             insert("GEM_HOME", gem_home.into_string());
         }
         if let Some(gem_root) = ruby.gem_root() {
-            paths.insert(0, gem_root.join("bin").into());
             gem_paths.insert(0, gem_root.clone());
             insert("GEM_ROOT", gem_root.into_string());
         }
+        if let Some(gemfile) = gemfile {
+            if let Some(vendor_dir) = vendored_gem_dir(ruby, gemfile) {
+                gem_paths.insert(0, vendor_dir);
+            }
+            insert("BUNDLE_GEMFILE", gemfile.to_string());
+            insert(RV_BUNDLE_GEMFILE, gemfile.to_string());
+        }
         let gem_path = join_paths(gem_paths)?;
         if let Some(gem_path) = gem_path.to_str() {
             insert("GEM_PATH", gem_path.into());
         }
     }
 
-    let path = join_paths(paths)?;
+    let pathstr = env.get("PATH").map(String::as_str).unwrap_or_default();
+    let paths = assemble_path(pathstr, |var| env.get(var).cloned(), ruby);
+    let path = join_paths(paths.into_iter().map(|(path, _)| path))?;
     if let Some(path) = path.to_str() {
         insert("PATH", path.into());
     }
 
     Ok((unset, set))
 }
+
+/// Where the Ruby request returned by [`Config::ruby_request`] came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The project's `.ruby-version`, or whichever file `--ruby-version-file` names.
+    VersionFile(Utf8PathBuf),
+    /// The `ruby` setting in the project's `rv.toml`.
+    ProjectConfig(Utf8PathBuf),
+    /// No project, so the latest installed Ruby.
+    Default,
+}
+
+/// The file the request was read from, or `default`.
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::VersionFile(path) | Self::ProjectConfig(path) => write!(f, "{path}"),
+            Self::Default => f.write_str("default"),
+        }
+    }
+}
+
+/// The variables of rv's own environment, leaving out any that aren't UTF-8.
+fn process_env() -> BTreeMap<String, String> {
+    env::vars_os()
+        .filter_map(|(var, val)| Some((var.into_string().ok()?, val.into_string().ok()?)))
+        .collect()
+}
+
+/// Where `bundle config set path vendor/bundle` installs the gems of `gemfile` for `ruby`, like
+/// `vendor/bundle/ruby/3.3.0`, if that directory exists.
+fn vendored_gem_dir(ruby: &Ruby, gemfile: &Utf8Path) -> Option<Utf8PathBuf> {
+    // The last part of the gem root is the ABI version Bundler names the directory after.
+    let gem_root = ruby.gem_root()?;
+    let dir = gemfile
+        .parent()?
+        .join("vendor/bundle")
+        .join(ruby.version.engine.name())
+        .join(gem_root.file_name()?);
+    dir.is_dir().then_some(dir)
+}
+
+/// Why an entry is part of the PATH computed by [`env_for`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathSource {
+    RubyBin,
+    GemHomeBin,
+    GemRootBin,
+    Existing,
+}
+
+impl fmt::Display for PathSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match self {
+            Self::RubyBin => "ruby bin",
+            Self::GemHomeBin => "gem_home bin",
+            Self::GemRootBin => "gem_root bin",
+            Self::Existing => "existing",
+        };
+        f.write_str(source)
+    }
+}
+
+/// The ordered PATH entries [`env_for`] sets for `ruby`, each with where it comes from.
+pub fn path_for(ruby: Option<&Ruby>) -> Vec<(PathBuf, PathSource)> {
+    let pathstr = std::env::var("PATH").unwrap_or_else(|_| String::new());
+    assemble_path(&pathstr, |var| std::env::var(var).ok(), ruby)
+}
+
+/// Rebuilds `pathstr` for `ruby`, first dropping the bin dirs a previous activation added.
+///
+/// Those are found through the variables it set, read with `var`. The gem home is also
+/// derived from `RUBY_ENGINE` and `RUBY_VERSION`, so its bin dir goes away even if `GEM_HOME`
+/// was changed since. Other bin dirs, like those of the user's own `GEM_PATH`, are kept.
+fn assemble_path(
+    pathstr: &str,
+    var: impl Fn(&str) -> Option<String>,
+    ruby: Option<&Ruby>,
+) -> Vec<(PathBuf, PathSource)> {
+    let mut old_ruby_dirs: Vec<Utf8PathBuf> = ["RUBY_ROOT", "GEM_ROOT", "GEM_HOME"]
+        .iter()
+        .filter_map(|name| var(name))
+        .map(Utf8PathBuf::from)
+        .collect();
+    if let (Some(engine), Some(version)) = (var("RUBY_ENGINE"), var("RUBY_VERSION")) {
+        old_ruby_dirs.extend(rv_ruby::gem_home_dir(&engine, &version));
+    }
+    let old_ruby_paths: Vec<PathBuf> = old_ruby_dirs
+        .iter()
+        .map(|dir| rv_ruby::bin_dir(dir).into())
+        .collect();
+
+    // Remove old Ruby and Gem paths from PATH
+    let mut paths: Vec<(PathBuf, PathSource)> = split_paths(pathstr)
+        .filter(|p| !old_ruby_paths.contains(p))
+        .map(|p| (p, PathSource::Existing))
+        .collect();
+
+    if let Some(ruby) = ruby {
+        paths.insert(0, (ruby.bin_path().into(), PathSource::RubyBin));
+        if let Some(gem_home) = ruby.gem_home() {
+            paths.insert(
+                0,
+                (rv_ruby::bin_dir(&gem_home).into(), PathSource::GemHomeBin),
+            );
+        }
+        if let Some(gem_root) = ruby.gem_root() {
+            paths.insert(
+                0,
+                (rv_ruby::bin_dir(&gem_root).into(), PathSource::GemRootBin),
+            );
+        }
+    }
+
+    // Drop repeated entries, keeping the first occurrence of each
+    let mut seen = HashSet::new();
+    paths.retain(|(p, _)| seen.insert(p.clone()));
+
+    paths
+}
+
+/// Position of the directory containing `ruby` in `ruby_dirs`, lower is preferred.
+fn dir_precedence(ruby: &Ruby, ruby_dirs: &IndexSet<Utf8PathBuf>) -> usize {
+    ruby.path
+        .parent()
+        .and_then(|dir| ruby_dirs.get_index_of(dir))
+        .unwrap_or(usize::MAX)
+}
+
+/// Installs that share a display name with another install at a different path.
+/// Each entry is `(shadowed, winner)`, where the winner is the install a request
+/// for that name would resolve to.
+fn shadowed_rubies<'a>(
+    rubies: &'a [Ruby],
+    ruby_dirs: &IndexSet<Utf8PathBuf>,
+) -> Vec<(&'a Ruby, &'a Ruby)> {
+    let mut by_name: BTreeMap<String, Vec<&Ruby>> = BTreeMap::new();
+    for ruby in rubies {
+        by_name.entry(ruby.display_name()).or_default().push(ruby);
+    }
+
+    let mut shadowed = Vec::new();
+    for group in by_name.values().filter(|group| group.len() > 1) {
+        // Same tie-break as `sorted_matching_rubies`: earlier dir first, then last discovered.
+        let Some(winner) = group
+            .iter()
+            .rev()
+            .min_by_key(|ruby| dir_precedence(ruby, ruby_dirs))
+        else {
+            continue;
+        };
+        shadowed.extend(
+            group
+                .iter()
+                .filter(|ruby| ruby.path != winner.path)
+                .map(|ruby| (*ruby, *winner)),
+        );
+    }
+    shadowed
+}
+
+fn warn_shadowed_rubies(rubies: &[Ruby], ruby_dirs: &IndexSet<Utf8PathBuf>) {
+    for (shadowed, winner) in shadowed_rubies(rubies, ruby_dirs) {
+        warn!(
+            "{} at {} is shadowed by {}",
+            shadowed.display_name(),
+            shadowed.path,
+            winner.path
+        );
+    }
+}
+
+fn sorted_matching_rubies(
+    rubies: Vec<Ruby>,
+    request: &RubyRequest,
+    ruby_dirs: &IndexSet<Utf8PathBuf>,
+) -> Vec<Ruby> {
+    let precedence = |ruby: &Ruby| dir_precedence(ruby, ruby_dirs);
+
+    // Among otherwise equal installs, the last one discovered has always won; reversing
+    // before the stable sort keeps that.
+    let mut matching: Vec<Ruby> = rubies
+        .into_iter()
+        .rev()
+        .filter(|ruby| request.satisfied_by(ruby))
+        .collect();
+    matching.sort_by(|a, b| {
+        b.version
+            .cmp(&a.version)
+            .then_with(|| precedence(a).cmp(&precedence(b)))
+    });
+    matching
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::str::FromStr as _;
+
+    fn ruby_with_gem_root() -> Ruby {
+        Ruby {
+            key: "ruby-3.3.5-macos-aarch64".to_owned(),
+            version: rv_ruby::version::RubyVersion::from_str("ruby-3.3.5").unwrap(),
+            path: Utf8PathBuf::from("/opt/rubies/ruby-3.3.5"),
+            symlink: None,
+            arch: "aarch64".to_owned(),
+            os: "macos".to_owned(),
+            gem_root: Some(Utf8PathBuf::from(
+                "/opt/rubies/ruby-3.3.5/lib/ruby/gems/3.3.0",
+            )),
+        }
+    }
+
+    fn ruby(version: &str) -> Ruby {
+        Ruby {
+            key: format!("{version}-macos-aarch64"),
+            version: rv_ruby::version::RubyVersion::from_str(version).unwrap(),
+            path: Utf8PathBuf::from(format!("/opt/rubies/{version}")),
+            symlink: None,
+            arch: "aarch64".to_owned(),
+            os: "macos".to_owned(),
+            gem_root: None,
+        }
+    }
+
+    #[test]
+    fn test_sorted_matching_rubies_picks_highest_patch() {
+        let rubies = vec![
+            ruby("ruby-3.3.10"),
+            ruby("ruby-3.3.2"),
+            ruby("ruby-3.4.1"),
+            ruby("ruby-3.3.9"),
+        ];
+        let request = RubyRequest::from_str("3.3").unwrap();
+
+        let found = sorted_matching_rubies(rubies, &request, &IndexSet::new()).remove(0);
+        assert_eq!(found.version.to_string(), "ruby-3.3.10");
+    }
+
+    #[test]
+    fn test_sorted_matching_rubies_ignores_input_order() {
+        let mut rubies = vec![ruby("ruby-3.3.9"), ruby("ruby-3.3.1"), ruby("ruby-3.3.5")];
+        let request = RubyRequest::from_str("3").unwrap();
+
+        let forward = sorted_matching_rubies(rubies.clone(), &request, &IndexSet::new());
+        rubies.reverse();
+        let backward = sorted_matching_rubies(rubies, &request, &IndexSet::new());
+        assert_eq!(forward[0].version.to_string(), "ruby-3.3.9");
+        assert_eq!(forward, backward);
+    }
+
+    #[test]
+    fn test_sorted_matching_rubies_returns_none_without_match() {
+        let rubies = vec![ruby("ruby-3.3.9"), ruby("ruby-3.4.1")];
+        let request = RubyRequest::from_str("3.2").unwrap();
+
+        assert!(sorted_matching_rubies(rubies, &request, &IndexSet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_sorted_matching_rubies_prefers_earlier_ruby_dir() {
+        let mut first = ruby("ruby-3.3.5");
+        first.path = Utf8PathBuf::from("/first/ruby-3.3.5");
+        let mut second = ruby("ruby-3.3.5");
+        second.path = Utf8PathBuf::from("/second/ruby-3.3.5");
+        let request = RubyRequest::from_str("3.3.5").unwrap();
+
+        let ruby_dirs = IndexSet::from(["/first".into(), "/second".into()]);
+        let found = sorted_matching_rubies(vec![first, second.clone()], &request, &ruby_dirs);
+        assert_eq!(found[0].path, "/first/ruby-3.3.5");
+
+        let ruby_dirs = IndexSet::from(["/second".into(), "/first".into()]);
+        let found = sorted_matching_rubies(
+            vec![ruby("ruby-3.3.5"), second.clone()],
+            &request,
+            &ruby_dirs,
+        );
+        assert_eq!(found[0].path, second.path);
+    }
+
+    #[test]
+    fn test_sorted_matching_rubies_returns_all_matches_newest_first() {
+        let rubies = vec![
+            ruby("ruby-3.3.2"),
+            ruby("ruby-3.4.1"),
+            ruby("ruby-3.3.10"),
+            ruby("ruby-3.3.9"),
+        ];
+        let request = RubyRequest::from_str("3.3").unwrap();
+
+        let versions: Vec<String> = sorted_matching_rubies(rubies, &request, &IndexSet::new())
+            .iter()
+            .map(|ruby| ruby.version.to_string())
+            .collect();
+        assert_eq!(versions, ["ruby-3.3.10", "ruby-3.3.9", "ruby-3.3.2"]);
+    }
+
+    #[test]
+    fn test_shadowed_rubies_reports_losing_installs() {
+        let mut first = ruby("ruby-3.3.5");
+        first.path = Utf8PathBuf::from("/first/ruby-3.3.5");
+        let mut second = ruby("ruby-3.3.5");
+        second.path = Utf8PathBuf::from("/second/ruby-3.3.5");
+        let rubies = vec![second.clone(), ruby("ruby-3.4.1"), first.clone()];
+
+        let ruby_dirs = IndexSet::from(["/first".into(), "/second".into()]);
+        let shadowed = shadowed_rubies(&rubies, &ruby_dirs);
+        assert_eq!(shadowed, [(&second, &first)]);
+
+        let ruby_dirs = IndexSet::from(["/second".into(), "/first".into()]);
+        let shadowed = shadowed_rubies(&rubies, &ruby_dirs);
+        assert_eq!(shadowed, [(&first, &second)]);
+    }
+
+    #[test]
+    fn test_shadowed_rubies_ignores_unique_names() {
+        let rubies = vec![ruby("ruby-3.3.5"), ruby("ruby-3.4.1")];
+        assert!(shadowed_rubies(&rubies, &IndexSet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_expand_ruby_dir_places_paths_under_root() {
+        let root = Utf8Path::new("/root");
+        assert_eq!(
+            expand_ruby_dir(root, Utf8Path::new("/opt/rubies")).unwrap(),
+            "/root/opt/rubies"
+        );
+        assert_eq!(
+            expand_ruby_dir(root, Utf8Path::new("rubies")).unwrap(),
+            "/root/rubies"
+        );
+        assert!(matches!(
+            expand_ruby_dir(root, Utf8Path::new("$RV_TEST_SURELY_UNSET/rubies")),
+            Err(Error::UnexpandableRubyDir { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_chruby_rubies() {
+        let root = Utf8Path::new("/root");
+        assert_eq!(
+            parse_chruby_rubies(root, "/opt/rubies/ruby-3.3.0:/opt/rubies/ruby-3.4.1").unwrap(),
+            ["/root/opt/rubies/ruby-3.3.0", "/root/opt/rubies/ruby-3.4.1"]
+        );
+        assert_eq!(
+            parse_chruby_rubies(root, "/opt/rubies/ruby-3.3.0\n\n/opt/jruby\n").unwrap(),
+            ["/root/opt/rubies/ruby-3.3.0", "/root/opt/jruby"]
+        );
+        assert!(parse_chruby_rubies(root, "").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_version_file_request_plain() {
+        assert_eq!(version_file_request("3.3.0"), Some("3.3.0"));
+        assert_eq!(version_file_request("3.3.0\n"), Some("3.3.0"));
+        assert_eq!(version_file_request("3.3.0\r\n"), Some("3.3.0"));
+    }
+
+    #[test]
+    fn test_version_file_request_trims_whitespace_and_blank_lines() {
+        assert_eq!(version_file_request("\n\n  3.3.0  \n\n"), Some("3.3.0"));
+        assert_eq!(version_file_request("\tjruby-9.4\t\n"), Some("jruby-9.4"));
+    }
+
+    #[test]
+    fn test_version_file_request_strips_comments() {
+        assert_eq!(version_file_request("3.3.0 # project ruby"), Some("3.3.0"));
+        assert_eq!(
+            version_file_request("# pinned for CI\n\n3.3.0\n3.4.0\n"),
+            Some("3.3.0")
+        );
+    }
+
+    #[test]
+    fn test_version_file_request_without_request() {
+        assert_eq!(version_file_request(""), None);
+        assert_eq!(version_file_request("\n  \n"), None);
+        assert_eq!(
+            version_file_request("# just a comment\n   # another\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ruby_request_errors_on_comment_only_file() {
+        let (_temp_dir, root) = project_tree();
+        std::fs::write(root.join(".ruby-version"), "# nothing here\n").unwrap();
+        let config = test_config(&root, Some(root.clone()));
+
+        assert!(matches!(
+            config.ruby_request(),
+            Err(Error::NoRequest { path }) if path == root.join(".ruby-version")
+        ));
+
+        std::fs::write(root.join(".ruby-version"), "  3.3.0 # project ruby\n").unwrap();
+        assert_eq!(
+            config.ruby_request().unwrap(),
+            RubyRequest::from_str("3.3.0").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ruby_request_ignores_gemset() {
+        let (_temp_dir, root) = project_tree();
+        let config = test_config(&root, Some(root.clone()));
+        let expected = RubyRequest::from_str("3.3.0").unwrap();
+
+        std::fs::write(root.join(".ruby-version"), "ruby-3.3.0@app\n").unwrap();
+        assert_eq!(config.ruby_request().unwrap(), expected);
+
+        std::fs::write(root.join(".ruby-version"), "3.3.0@app\n").unwrap();
+        assert_eq!(config.ruby_request().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_http_client_is_shared() {
+        let (_temp_dir, root) = project_tree();
+        let config = test_config(&root, None);
+
+        let first = config.http_client().unwrap();
+        let second = config.http_client().unwrap();
+        assert!(std::ptr::eq(first, second));
+    }
+
+    /// A configuration for tests, rooted at `root` and without any Ruby directories.
+    pub(crate) fn test_config(root: &Utf8Path, project_dir: Option<Utf8PathBuf>) -> Config {
+        Config {
+            ruby_dirs: IndexSet::new(),
+            chruby_rubies: Vec::new(),
+            gemfile: None,
+            root: root.to_owned(),
+            current_dir: root.to_owned(),
+            project_dir,
+            ruby_version_file: DEFAULT_RUBY_VERSION_FILE.to_string(),
+            project_config: ProjectConfig::default(),
+            cache: rv_cache::Cache::temp().unwrap(),
+            current_exe: root.join("bin").join("rv"),
+            offline: false,
+            timeout: Duration::from_secs(30),
+            releases_ttl: None,
+            http_client: Default::default(),
+            quiet: false,
+        }
+    }
+
+    fn project_tree() -> (assert_fs::TempDir, Utf8PathBuf) {
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+        std::fs::write(root.join(".ruby-version"), "3.3.5\n").unwrap();
+        std::fs::create_dir_all(root.join("home/user/repo/.git")).unwrap();
+        std::fs::create_dir_all(root.join("home/user/repo/lib/nested")).unwrap();
+        (temp_dir, root)
+    }
+
+    #[test]
+    fn test_find_project_dir_walks_up_to_root() {
+        let (_temp_dir, root) = project_tree();
+        let current_dir = root.join("home/user/repo/lib/nested");
+
+        let found = search_project_dir(current_dir, &root, None, false, DEFAULT_RUBY_VERSION_FILE);
+        assert_eq!(found, Some(root));
+    }
+
+    #[test]
+    fn test_find_project_dir_stops_at_git() {
+        let (_temp_dir, root) = project_tree();
+        let current_dir = root.join("home/user/repo/lib/nested");
+
+        let found = search_project_dir(
+            current_dir.clone(),
+            &root,
+            None,
+            true,
+            DEFAULT_RUBY_VERSION_FILE,
+        );
+        assert_eq!(found, None);
+
+        let repo = root.join("home/user/repo");
+        std::fs::write(repo.join(".ruby-version"), "3.4.1\n").unwrap();
+        let found = search_project_dir(current_dir, &root, None, true, DEFAULT_RUBY_VERSION_FILE);
+        assert_eq!(found, Some(repo));
+    }
+
+    #[test]
+    fn test_find_project_dir_falls_back_to_main_worktree() {
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+        let main = root.join("src/app");
+        let worktree = root.join("src/app-feature");
+        std::fs::create_dir_all(main.join(".git/worktrees/app-feature")).unwrap();
+        std::fs::create_dir_all(worktree.join("lib/nested")).unwrap();
+        std::fs::write(
+            worktree.join(".git"),
+            "gitdir: ../app/.git/worktrees/app-feature\n",
+        )
+        .unwrap();
+        let current_dir = worktree.join("lib/nested");
+
+        let found = search_project_dir(
+            current_dir.clone(),
+            &root,
+            None,
+            true,
+            DEFAULT_RUBY_VERSION_FILE,
+        );
+        assert_eq!(found, None);
+
+        std::fs::write(main.join(".ruby-version"), "3.4.1\n").unwrap();
+        let main = main.canonicalize_utf8().unwrap();
+        let found = search_project_dir(
+            current_dir.clone(),
+            &root,
+            None,
+            true,
+            DEFAULT_RUBY_VERSION_FILE,
+        );
+        assert_eq!(found, Some(main.clone()));
+        let found = search_project_dir(
+            current_dir.clone(),
+            &root,
+            None,
+            false,
+            DEFAULT_RUBY_VERSION_FILE,
+        );
+        assert_eq!(found, Some(main));
+
+        // The worktree's own `.ruby-version` still comes first.
+        std::fs::write(worktree.join(".ruby-version"), "3.3.5\n").unwrap();
+        let found = search_project_dir(current_dir, &root, None, true, DEFAULT_RUBY_VERSION_FILE);
+        assert_eq!(found, Some(worktree));
+    }
+
+    #[test]
+    fn test_find_project_dir_ignores_git_in_subdirectories() {
+        let (_temp_dir, root) = project_tree();
+        let current_dir = root.join("home/user");
+
+        let found = search_project_dir(current_dir, &root, None, true, DEFAULT_RUBY_VERSION_FILE);
+        assert_eq!(found, Some(root));
+    }
+
+    #[test]
+    fn test_find_project_dir_stops_at_home() {
+        let (_temp_dir, root) = project_tree();
+        let home = root.join("home/user");
+
+        let found = search_project_dir(
+            root.join("home/user/repo/lib"),
+            &root,
+            Some(&home),
+            false,
+            DEFAULT_RUBY_VERSION_FILE,
+        );
+        assert_eq!(found, None);
+
+        std::fs::write(home.join(".ruby-version"), "3.4.1\n").unwrap();
+        let found = search_project_dir(
+            root.join("home/user/repo/lib"),
+            &root,
+            Some(&home),
+            false,
+            DEFAULT_RUBY_VERSION_FILE,
+        );
+        assert_eq!(found, Some(home.clone()));
+
+        // Starting above home, the boundary doesn't apply.
+        let found = search_project_dir(
+            root.join("home"),
+            &root,
+            Some(&home),
+            false,
+            DEFAULT_RUBY_VERSION_FILE,
+        );
+        assert_eq!(found, Some(root));
+    }
+
+    #[test]
+    fn test_env_for_joins_gem_path_with_os_separator() {
+        let ruby = ruby_with_gem_root();
+        let (_unset, set) = env_for(Some(&ruby), None).unwrap();
+        let gem_path = set
+            .iter()
+            .find_map(|(var, val)| (*var == "GEM_PATH").then_some(val))
+            .expect("GEM_PATH should be set");
+
+        let mut expected = vec![ruby.gem_root().unwrap()];
+        expected.extend(ruby.gem_home());
+        let expected = join_paths(expected).unwrap();
+        assert_eq!(gem_path, expected.to_str().unwrap());
+
+        let separator = if cfg!(windows) { ';' } else { ':' };
+        if ruby.gem_home().is_some() {
+            assert!(gem_path.contains(separator));
+        }
+    }
+
+    #[test]
+    fn test_env_for_with_gemfile() {
+        let ruby = ruby_with_gem_root();
+        let project = assert_fs::TempDir::new().unwrap();
+        let project = Utf8Path::from_path(project.path()).unwrap();
+        let gemfile = project.join("Gemfile");
+        let gem_path = |set: &[(&str, String)]| {
+            set.iter()
+                .find_map(|(var, val)| (*var == "GEM_PATH").then(|| val.clone()))
+                .unwrap()
+        };
+
+        let (unset, set) = env_for(Some(&ruby), Some(&gemfile)).unwrap();
+        assert!(set.contains(&("BUNDLE_GEMFILE", gemfile.to_string())));
+        assert!(!unset.contains(&"BUNDLE_GEMFILE"));
+        let (_unset, without_gemfile) = env_for(Some(&ruby), None).unwrap();
+        assert_eq!(gem_path(&set), gem_path(&without_gemfile));
+
+        let vendor_dir = project.join("vendor/bundle/ruby/3.3.0");
+        std::fs::create_dir_all(&vendor_dir).unwrap();
+        let (_unset, set) = env_for(Some(&ruby), Some(&gemfile)).unwrap();
+        let gem_paths: Vec<PathBuf> = split_paths(&gem_path(&set)).collect();
+        assert_eq!(gem_paths[0], PathBuf::from(vendor_dir));
+
+        // Nothing is left to point at after deactivating.
+        let (_unset, set) = env_for(None, Some(&gemfile)).unwrap();
+        assert!(set.iter().all(|(var, _)| *var != "BUNDLE_GEMFILE"));
+    }
+
+    #[test]
+    fn test_env_for_unsets_only_the_gemfile_rv_exported() {
+        let ruby = ruby_with_gem_root();
+        let exported = |gemfile: &str| {
+            BTreeMap::from([
+                ("BUNDLE_GEMFILE".to_owned(), gemfile.to_owned()),
+                (RV_BUNDLE_GEMFILE.to_owned(), "/first/Gemfile".to_owned()),
+            ])
+        };
+
+        // The previous project's Gemfile goes when the next one has none.
+        let (unset, set) = env_for_with(Some(&ruby), None, &exported("/first/Gemfile")).unwrap();
+        assert!(unset.contains(&"BUNDLE_GEMFILE"));
+        assert!(unset.contains(&RV_BUNDLE_GEMFILE));
+        assert!(set.iter().all(|(var, _)| *var != "BUNDLE_GEMFILE"));
+        let (unset, _set) = env_for_with(None, None, &exported("/first/Gemfile")).unwrap();
+        assert!(unset.contains(&"BUNDLE_GEMFILE"));
+
+        // A Gemfile the user picked since then is left alone.
+        let (unset, _set) = env_for_with(Some(&ruby), None, &exported("/mine/Gemfile")).unwrap();
+        assert!(!unset.contains(&"BUNDLE_GEMFILE"));
+        assert!(unset.contains(&RV_BUNDLE_GEMFILE));
+        let user_set = BTreeMap::from([("BUNDLE_GEMFILE".to_owned(), "/mine/Gemfile".to_owned())]);
+        let (unset, _set) = env_for_with(Some(&ruby), None, &user_set).unwrap();
+        assert!(!unset.contains(&"BUNDLE_GEMFILE"));
+
+        // An explicit Gemfile replaces the exported one.
+        let gemfile = Utf8Path::new("/second/Gemfile");
+        let (unset, set) =
+            env_for_with(Some(&ruby), Some(gemfile), &exported("/first/Gemfile")).unwrap();
+        assert!(!unset.contains(&"BUNDLE_GEMFILE"));
+        assert!(set.contains(&("BUNDLE_GEMFILE", gemfile.to_string())));
+        assert!(set.contains(&(RV_BUNDLE_GEMFILE, gemfile.to_string())));
+    }
+
+    #[test]
+    fn test_env_for_with_strips_previous_ruby_from_base_path() {
+        let ruby = ruby_with_gem_root();
+        let env = BTreeMap::from([
+            (
+                "PATH".to_owned(),
+                join_paths(["/opt/rubies/ruby-3.2.0/bin", "/usr/bin"])
+                    .unwrap()
+                    .into_string()
+                    .unwrap(),
+            ),
+            ("RUBY_ROOT".to_owned(), "/opt/rubies/ruby-3.2.0".to_owned()),
+        ]);
+
+        let (_unset, set) = env_for_with(Some(&ruby), None, &env).unwrap();
+        let path = set
+            .iter()
+            .find_map(|(var, val)| (*var == "PATH").then_some(val))
+            .unwrap();
+        let paths: Vec<PathBuf> = split_paths(path).collect();
+        assert!(!paths.contains(&PathBuf::from("/opt/rubies/ruby-3.2.0/bin")));
+        assert!(paths.contains(&ruby.bin_path().into()));
+        assert_eq!(paths.last(), Some(&PathBuf::from("/usr/bin")));
+
+        let (unset, set) = env_for_with(None, None, &env).unwrap();
+        assert!(unset.contains(&"RUBY_ROOT"));
+        assert_eq!(set, [("PATH", "/usr/bin".to_owned())]);
+    }
+
+    #[test]
+    fn test_env_for_puts_bin_paths_first() {
+        let ruby = ruby_with_gem_root();
+        let (_unset, set) = env_for(Some(&ruby), None).unwrap();
+        let path = set
+            .iter()
+            .find_map(|(var, val)| (*var == "PATH").then_some(val))
+            .expect("PATH should be set");
+
+        let paths: Vec<PathBuf> = split_paths(path).collect();
+        let gem_root_bin: PathBuf = rv_ruby::bin_dir(&ruby.gem_root().unwrap()).into();
+        let ruby_bin: PathBuf = ruby.bin_path().into();
+        assert_eq!(paths[0], gem_root_bin);
+        assert!(paths.contains(&ruby_bin));
+    }
+
+    #[test]
+    fn test_assemble_path_deactivation_removes_what_activation_added() {
+        let ruby = ruby_with_gem_root();
+        // The user's own gems are on PATH too, and must stay there.
+        let original = join_paths(["/usr/bin", "/opt/gems/bin", "/tmp/bin"]).unwrap();
+        let original = original.to_str().unwrap();
+        let join = |paths: Vec<(PathBuf, PathSource)>| {
+            join_paths(paths.into_iter().map(|(path, _)| path))
+                .unwrap()
+                .into_string()
+                .unwrap()
+        };
+
+        let activated = join(assemble_path(original, |_| None, Some(&ruby)));
+        assert_ne!(activated, original);
+
+        // What activating set, except that GEM_HOME was changed afterwards, e.g. by Bundler.
+        let gem_paths = ["/opt/gems".into()]
+            .into_iter()
+            .chain(ruby.gem_root())
+            .chain(ruby.gem_home());
+        let vars = HashMap::from([
+            ("RUBY_ROOT", ruby.path.to_string()),
+            ("RUBY_ENGINE", ruby.version.engine.name().to_owned()),
+            ("RUBY_VERSION", ruby.version.number()),
+            ("GEM_ROOT", ruby.gem_root().unwrap().into_string()),
+            ("GEM_HOME", "/project/vendor/bundle".to_owned()),
+            (
+                "GEM_PATH",
+                join_paths::<_, Utf8PathBuf>(gem_paths)
+                    .unwrap()
+                    .into_string()
+                    .unwrap(),
+            ),
+        ]);
+        let var = |name: &str| vars.get(name).cloned();
+
+        let deactivated = join(assemble_path(&activated, var, None));
+        assert_eq!(deactivated, original);
+        assert_eq!(join(assemble_path(&deactivated, var, None)), original);
+    }
+}
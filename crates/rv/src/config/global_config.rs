@@ -0,0 +1,129 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+use tracing::debug;
+
+use super::{Error, home_dir_in};
+use crate::ColorMode;
+
+/// The name of rv's own configuration file, in `$XDG_CONFIG_HOME/rv`.
+pub const GLOBAL_CONFIG_FILE: &str = "config.toml";
+
+/// User-wide settings from `~/.config/rv/config.toml`, or the file given with `--config`.
+///
+/// They only replace the built-in defaults, so a setting is taken from the first of:
+/// the flag, its environment variable, this file, and the built-in default.
+///
+/// ```toml
+/// ruby-dirs = ["~/.rubies"]
+/// color = "never"
+/// timeout = 60
+/// trusted-projects = ["~/work/app"]
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct GlobalConfig {
+    /// Ruby directories to search instead of the defaults, like `--ruby-dir`.
+    pub ruby_dirs: Vec<Utf8PathBuf>,
+    /// When to use colors, like `--color`.
+    pub(crate) color: Option<ColorMode>,
+    /// Seconds to wait for the network, like `--timeout`.
+    pub timeout: Option<u64>,
+    /// Projects whose `rv.toml` may add Ruby directories. Rubies found there are run to
+    /// inspect them, so a project checked out from elsewhere can't add any until trusted here.
+    pub trusted_projects: Vec<Utf8PathBuf>,
+}
+
+impl GlobalConfig {
+    /// Reads the configuration file at `path`, which has to exist.
+    pub fn load(path: &Utf8Path) -> Result<Self, Error> {
+        let content =
+            std::fs::read_to_string(path).map_err(|source| Error::UnreadableGlobalConfig {
+                path: path.to_owned(),
+                source,
+            })?;
+        Self::parse(path, &content)
+    }
+
+    /// Reads the configuration file in the user's config directory under `root`, or returns
+    /// the defaults if there isn't one.
+    pub fn load_default(root: &Utf8Path) -> Result<Self, Error> {
+        let Some(path) = default_path(root) else {
+            return Ok(Self::default());
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&path, &content),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(source) => Err(Error::UnreadableGlobalConfig { path, source }),
+        }
+    }
+
+    fn parse(path: &Utf8Path, content: &str) -> Result<Self, Error> {
+        debug!("Loading configuration from {}", path);
+        toml::from_str(content).map_err(|source| Error::InvalidGlobalConfig {
+            path: path.to_owned(),
+            source: Box::new(source),
+        })
+    }
+}
+
+/// `$XDG_CONFIG_HOME/rv/config.toml`, falling back to `~/.config/rv/config.toml`.
+///
+/// Like other XDG variables, `XDG_CONFIG_HOME` is ignored unless it is an absolute path.
+fn default_path(root: &Utf8Path) -> Option<Utf8PathBuf> {
+    let config_dir = match std::env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if Utf8Path::new(&dir).is_absolute() => {
+            root.join(dir.strip_prefix('/').unwrap_or(&dir))
+        }
+        _ => home_dir_in(root)?.join(".config"),
+    };
+    Some(config_dir.join("rv").join(GLOBAL_CONFIG_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+
+    fn config_path(temp_dir: &TempDir) -> Utf8PathBuf {
+        Utf8PathBuf::from(temp_dir.path().to_str().unwrap()).join(GLOBAL_CONFIG_FILE)
+    }
+
+    #[test]
+    fn test_load_global_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = config_path(&temp_dir);
+        std::fs::write(
+            &path,
+            "ruby-dirs = [\"~/.rubies\"]\ncolor = \"never\"\ntimeout = 60\n",
+        )
+        .unwrap();
+
+        let config = GlobalConfig::load(&path).unwrap();
+        assert_eq!(
+            config,
+            GlobalConfig {
+                ruby_dirs: vec!["~/.rubies".into()],
+                color: Some(ColorMode::Never),
+                timeout: Some(60),
+                trusted_projects: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_load_missing_global_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = GlobalConfig::load(&config_path(&temp_dir));
+        assert!(matches!(result, Err(Error::UnreadableGlobalConfig { .. })));
+    }
+
+    #[test]
+    fn test_load_global_config_rejects_unknown_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = config_path(&temp_dir);
+        std::fs::write(&path, "ruby = \"3.4\"\n").unwrap();
+
+        let result = GlobalConfig::load(&path);
+        assert!(matches!(result, Err(Error::InvalidGlobalConfig { .. })));
+    }
+}
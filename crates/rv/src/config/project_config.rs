@@ -0,0 +1,94 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+use tracing::debug;
+
+use super::Error;
+
+/// The name of the project configuration file.
+pub const PROJECT_CONFIG_FILE: &str = "rv.toml";
+
+/// Per-project settings from `rv.toml`, layered under the CLI flags and environment.
+///
+/// ```toml
+/// ruby = "3.4"
+/// ruby-dirs = ["vendor/rubies"]
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ProjectConfig {
+    /// The Ruby version request for the project, used when there is no `.ruby-version`.
+    pub ruby: Option<String>,
+    /// Extra directories to search for rubies, relative to the project directory. Only used
+    /// for projects in the global config's `trusted-projects`.
+    pub ruby_dirs: Vec<Utf8PathBuf>,
+}
+
+impl ProjectConfig {
+    /// Reads `rv.toml` from `project_dir`, or returns the defaults if there isn't one.
+    pub fn load(project_dir: &Utf8Path) -> Result<Self, Error> {
+        let path = project_dir.join(PROJECT_CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+        debug!("Loading project configuration from {}", path);
+
+        let mut config: Self = toml::from_str(&content)
+            .map_err(|source| Error::InvalidProjectConfig { path, source })?;
+        config.ruby_dirs = config
+            .ruby_dirs
+            .iter()
+            .map(|dir| project_dir.join(dir))
+            .collect();
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+
+    fn project_dir(temp_dir: &TempDir) -> Utf8PathBuf {
+        Utf8PathBuf::from(temp_dir.path().to_str().unwrap())
+    }
+
+    #[test]
+    fn test_load_missing_project_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ProjectConfig::load(&project_dir(&temp_dir)).unwrap();
+        assert_eq!(config, ProjectConfig::default());
+    }
+
+    #[test]
+    fn test_load_project_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = project_dir(&temp_dir);
+        std::fs::write(
+            project_dir.join("rv.toml"),
+            "ruby = \"jruby-9.4\"\nruby-dirs = [\"vendor/rubies\", \"/opt/team-rubies\"]\n",
+        )
+        .unwrap();
+
+        let config = ProjectConfig::load(&project_dir).unwrap();
+        assert_eq!(config.ruby.as_deref(), Some("jruby-9.4"));
+        assert_eq!(
+            config.ruby_dirs,
+            [
+                project_dir.join("vendor/rubies"),
+                Utf8PathBuf::from("/opt/team-rubies")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_project_config_rejects_unknown_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = project_dir(&temp_dir);
+        std::fs::write(project_dir.join("rv.toml"), "rubby = \"3.4\"\n").unwrap();
+
+        let result = ProjectConfig::load(&project_dir);
+        assert!(matches!(result, Err(Error::InvalidProjectConfig { .. })));
+    }
+}
@@ -1,13 +1,55 @@
-use camino::Utf8Path;
+use std::{fmt, io};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use indexmap::IndexSet;
 use miette::{IntoDiagnostic, Result};
 use rayon::prelude::*;
 use rayon_tracing::TracedIndexedParallelIterator;
-use tracing::debug;
+use tracing::{debug, warn};
 
-use rv_ruby::Ruby;
+use rv_ruby::{Ruby, RubyError, Validity};
 
 use super::{Config, Error};
 
+/// Bumped whenever the information gathered from a Ruby changes (like when its gem root started
+/// being detected), so that interpreters cached by older versions of rv are probed again.
+const RUBY_INFO_VERSION: u32 = 1;
+
+/// Why a cached interpreter entry can no longer be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidReason {
+    /// The entry doesn't contain Ruby information.
+    Unreadable,
+    /// The Ruby installation is gone or no longer works.
+    Missing(Utf8PathBuf),
+    /// The Ruby installation changed since it was cached, or was cached by another version of rv.
+    Stale(Utf8PathBuf),
+}
+
+impl fmt::Display for InvalidReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unreadable => write!(f, "unreadable entry"),
+            Self::Missing(path) => write!(f, "{path} no longer exists"),
+            Self::Stale(path) => write!(f, "{path} has changed"),
+        }
+    }
+}
+
+/// A cached interpreter entry that failed verification.
+#[derive(Debug)]
+pub struct InvalidCachedRuby {
+    pub entry: Utf8PathBuf,
+    pub reason: InvalidReason,
+}
+
+/// Outcome of checking the whole interpreter cache.
+#[derive(Debug, Default)]
+pub struct InterpreterCacheReport {
+    pub checked: usize,
+    pub invalid: Vec<InvalidCachedRuby>,
+}
+
 impl Config {
     /// Get cached Ruby information for a specific Ruby installation if valid
     fn get_cached_ruby(&self, ruby_path: &Utf8Path) -> Result<Ruby> {
@@ -23,15 +65,17 @@ impl Config {
                 match serde_json::from_str::<Ruby>(&content) {
                     Ok(cached_ruby) => {
                         // Verify cached Ruby installation still exists and is valid
-                        if cached_ruby.is_valid() {
-                            Ok(cached_ruby)
-                        } else {
-                            // Ruby is no longer valid, remove cache entry
-                            let _ = fs_err::remove_file(cache_entry.path());
-                            Err(Error::RubyCacheMiss {
-                                ruby_path: ruby_path.to_path_buf(),
+                        match cached_ruby.validity() {
+                            Validity::Valid => Ok(cached_ruby),
+                            reason => {
+                                // Ruby is no longer valid, remove cache entry
+                                debug!("Cached ruby at {ruby_path} is invalid: {reason}");
+                                let _ = fs_err::remove_file(cache_entry.path());
+                                Err(Error::RubyCacheMiss {
+                                    ruby_path: ruby_path.to_path_buf(),
+                                }
+                                .into())
                             }
-                            .into())
                         }
                     }
                     Err(_) => {
@@ -51,6 +95,63 @@ impl Config {
         }
     }
 
+    /// Forget everything cached about Ruby installations, so the next discovery probes every
+    /// directory again.
+    pub fn clear_cached_rubies(&self) -> io::Result<()> {
+        for shard in ["interpreters", "non-rubies"] {
+            let dir = self
+                .cache
+                .shard(rv_cache::CacheBucket::Ruby, shard)
+                .into_path_buf();
+            match fs_err::remove_dir_all(&dir) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Check every entry of the interpreter cache, the way `get_cached_ruby` checks a single one.
+    pub fn verify_cached_rubies(&self) -> io::Result<InterpreterCacheReport> {
+        let interpreters = self
+            .cache
+            .shard(rv_cache::CacheBucket::Ruby, "interpreters")
+            .into_path_buf();
+        let mut report = InterpreterCacheReport::default();
+
+        let entries = match interpreters.read_dir_utf8() {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(report),
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            report.checked += 1;
+
+            let cached_ruby = fs_err::read_to_string(entry.path())
+                .ok()
+                .and_then(|content| serde_json::from_str::<Ruby>(&content).ok());
+            let reason = match cached_ruby {
+                None => InvalidReason::Unreadable,
+                Some(ruby) if !ruby.is_valid() => InvalidReason::Missing(ruby.path),
+                Some(ruby) => match self.ruby_path_cache_key(&ruby.path) {
+                    Ok(cache_key) if cache_key == entry.file_name() => continue,
+                    _ => InvalidReason::Stale(ruby.path),
+                },
+            };
+            report.invalid.push(InvalidCachedRuby {
+                entry: entry.into_path(),
+                reason,
+            });
+        }
+
+        report.invalid.sort_by(|a, b| a.entry.cmp(&b.entry));
+        Ok(report)
+    }
+
     /// Cache Ruby information for a specific Ruby installation
     fn cache_ruby(&self, ruby: &Ruby) -> Result<()> {
         // Use both path-based key (for lookup) and instance-based key (for comprehensive caching)
@@ -72,47 +173,78 @@ impl Config {
     }
 
     /// Generate a cache key for a specific Ruby installation path (used for cache lookup)
+    ///
+    /// Symlinks are resolved, so the key follows the real Ruby executable: pointing a
+    /// symlinked install (or its `bin/ruby`) at another build produces a different key.
     fn ruby_path_cache_key(&self, ruby_path: &Utf8Path) -> Result<String, Error> {
-        let ruby_bin = ruby_path.join("bin").join("ruby");
-        if !ruby_bin.exists() {
-            return Err(Error::RubyCacheMiss {
-                ruby_path: ruby_path.to_path_buf(),
-            });
-        }
+        let ruby_bin = rv_ruby::ruby_executable(ruby_path);
+        let cache_miss = || Error::RubyCacheMiss {
+            ruby_path: ruby_path.to_path_buf(),
+        };
 
-        let ruby_timestamp = match rv_cache::Timestamp::from_path(ruby_bin.as_std_path()) {
-            Ok(timestamp) => timestamp,
-            Err(_) => {
-                return Err(Error::RubyCacheMiss {
-                    ruby_path: ruby_path.to_path_buf(),
-                });
-            }
+        let real_ruby_bin = ruby_bin.canonicalize_utf8().map_err(|_| cache_miss())?;
+        let ruby_timestamp = rv_cache::Timestamp::from_path(real_ruby_bin.as_std_path())
+            .map_err(|_| cache_miss())?;
+        Ok(rv_cache::cache_digest((
+            RUBY_INFO_VERSION,
+            ruby_path,
+            real_ruby_bin,
+            ruby_timestamp,
+        )))
+    }
+
+    /// Generate a cache key for a directory that is not a Ruby installation. Any change to the
+    /// directory, its `bin` directory or its Ruby executable produces a different key.
+    fn non_ruby_cache_key(&self, path: &Utf8Path) -> Option<String> {
+        let timestamp = |path: &Utf8Path| rv_cache::Timestamp::from_path(path.as_std_path()).ok();
+        let dir_timestamp = timestamp(path)?;
+        let bin_timestamp = timestamp(&rv_ruby::bin_dir(path));
+        let ruby_timestamp = timestamp(&rv_ruby::ruby_executable(path));
+        Some(rv_cache::cache_digest((
+            path,
+            dir_timestamp,
+            bin_timestamp,
+            ruby_timestamp,
+        )))
+    }
+
+    /// Check whether this directory was already found not to be a Ruby installation
+    fn is_known_non_ruby(&self, path: &Utf8Path) -> bool {
+        self.non_ruby_cache_key(path).is_some_and(|cache_key| {
+            self.cache
+                .entry(rv_cache::CacheBucket::Ruby, "non-rubies", cache_key)
+                .path()
+                .exists()
+        })
+    }
+
+    /// Remember that this directory is not a Ruby installation
+    fn cache_non_ruby(&self, path: &Utf8Path) -> Result<()> {
+        let Some(cache_key) = self.non_ruby_cache_key(path) else {
+            return Ok(());
         };
-        Ok(rv_cache::cache_digest((ruby_path, ruby_timestamp)))
+        let cache_entry = self
+            .cache
+            .entry(rv_cache::CacheBucket::Ruby, "non-rubies", cache_key);
+
+        fs_err::create_dir_all(cache_entry.dir()).into_diagnostic()?;
+        fs_err::write(cache_entry.path(), "").into_diagnostic()?;
+
+        Ok(())
     }
 
     /// Discover all Ruby installations from configured directories with caching
     pub fn discover_rubies(&self) -> Vec<Ruby> {
-        // Collect all potential Ruby paths first
+        // Collect all potential Ruby paths first. Rubies from `RUBIES` are often in a Ruby
+        // directory as well, and shouldn't be found twice.
         let ruby_paths: Vec<_> = self
             .ruby_dirs
             .iter()
             .filter(|ruby_dir| ruby_dir.exists())
-            .flat_map(|ruby_dir| {
-                ruby_dir
-                    .read_dir_utf8()
-                    .into_iter()
-                    .flatten()
-                    .filter_map(|entry| {
-                        entry.ok().and_then(|entry| {
-                            entry
-                                .metadata()
-                                .ok()
-                                .filter(|metadata| metadata.is_dir())
-                                .map(|_| entry.path().to_path_buf())
-                        })
-                    })
-            })
+            .flat_map(|ruby_dir| candidate_ruby_paths(ruby_dir))
+            .chain(self.chruby_rubies.iter().cloned())
+            .collect::<IndexSet<_>>()
+            .into_iter()
             .collect();
 
         // Process Ruby paths in parallel for better performance
@@ -123,25 +255,37 @@ impl Config {
                 // Try to get Ruby from cache first
                 match self.get_cached_ruby(&ruby_path) {
                     Ok(cached_ruby) => Some(cached_ruby),
+                    Err(_) if self.is_known_non_ruby(&ruby_path) => {
+                        debug!("Skipping {}, known not to be a ruby", ruby_path);
+                        None
+                    }
                     Err(_) => {
                         // Cache miss or invalid, create Ruby and cache it
                         match Ruby::from_dir(ruby_path.to_path_buf()) {
-                            Ok(ruby) if ruby.is_valid() => {
-                                // Cache the Ruby (ignore errors during caching to not fail discovery)
-                                if let Err(err) = self.cache_ruby(&ruby) {
-                                    debug!("Failed to cache ruby at {}: {err}", ruby.path.as_str());
+                            Ok(ruby) => match ruby.validity() {
+                                Validity::Valid => {
+                                    // Cache the Ruby (ignore errors during caching to not fail discovery)
+                                    if let Err(err) = self.cache_ruby(&ruby) {
+                                        debug!(
+                                            "Failed to cache ruby at {}: {err}",
+                                            ruby.path.as_str()
+                                        );
+                                    }
+                                    return Some(ruby);
                                 }
-                                Some(ruby)
-                            }
-                            Ok(_) => {
-                                debug!("Ruby at {} is invalid", ruby_path);
-                                None
-                            }
-                            Err(err) => {
-                                debug!("Failed to get ruby from {}: {err}", ruby_path);
-                                None
+                                reason => debug!("Ruby at {} is invalid: {reason}", ruby_path),
+                            },
+                            // Left behind when the Ruby it pointed at was deleted, worth telling.
+                            Err(RubyError::Invalid(reason @ Validity::DanglingSymlink { .. })) => {
+                                warn!("Skipping ruby at {}: {reason}", ruby_path)
                             }
+                            Err(err) => debug!("Failed to get ruby from {}: {err}", ruby_path),
                         }
+                        // Remember the miss so we don't probe this directory again until it changes
+                        if let Err(err) = self.cache_non_ruby(&ruby_path) {
+                            debug!("Failed to cache non-ruby {}: {err}", ruby_path);
+                        }
+                        None
                     }
                 }
             })
@@ -153,12 +297,46 @@ impl Config {
     }
 }
 
+/// The directories in `ruby_dir` that may be Ruby installations.
+///
+/// Symlinks to directories elsewhere are followed, but aliases of another install in
+/// `ruby_dir`, like the ones `rv ruby install --alias` makes, are left out so that install
+/// isn't found twice.
+fn candidate_ruby_paths(ruby_dir: &Utf8Path) -> Vec<Utf8PathBuf> {
+    let real_ruby_dir = ruby_dir.canonicalize_utf8().ok();
+    ruby_dir
+        .read_dir_utf8()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let path = entry.path();
+            if !entry.file_type().ok()?.is_symlink() {
+                return entry
+                    .metadata()
+                    .ok()
+                    .filter(|metadata| metadata.is_dir())
+                    .map(|_| path.to_path_buf());
+            }
+
+            let target = path
+                .canonicalize_utf8()
+                .ok()
+                .filter(|target| target.is_dir())?;
+            if target.parent() == real_ruby_dir.as_deref() {
+                debug!("Skipping {path}, an alias of {target}");
+                return None;
+            }
+            Some(path.to_path_buf())
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use assert_fs::TempDir;
     use camino::Utf8PathBuf;
-    use rv_cache::Cache;
     use std::fs;
 
     fn create_test_config() -> (Config, TempDir) {
@@ -168,13 +346,8 @@ mod tests {
         fs::create_dir_all(&ruby_dir).unwrap();
 
         let config = Config {
-            ruby_dirs: vec![ruby_dir],
-            gemfile: None,
-            root: root.clone(),
-            current_dir: root.clone(),
-            project_dir: None,
-            cache: Cache::temp().unwrap(),
-            current_exe: root.join("bin").join("rv"),
+            ruby_dirs: [ruby_dir].into(),
+            ..crate::config::tests::test_config(&root, None)
         };
 
         (config, temp_dir)
@@ -247,6 +420,52 @@ mod tests {
         assert_eq!(cache_key, cache_key2);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_cache_key_follows_symlinked_install() {
+        let (config, _temp_dir) = create_test_config();
+        let ruby_dir = &config.ruby_dirs[0];
+
+        for build in ["ruby-3.3.5-a", "ruby-3.3.5-b"] {
+            let bin_dir = ruby_dir.join(build).join("bin");
+            fs::create_dir_all(&bin_dir).unwrap();
+            fs::write(bin_dir.join("ruby"), "#!/bin/bash\necho test").unwrap();
+        }
+
+        let ruby_path = ruby_dir.join("ruby-3.3.5");
+        std::os::unix::fs::symlink(ruby_dir.join("ruby-3.3.5-a"), &ruby_path).unwrap();
+        let cache_key = config.ruby_path_cache_key(&ruby_path).unwrap();
+
+        // Flip the symlink to the other build
+        fs::remove_file(&ruby_path).unwrap();
+        std::os::unix::fs::symlink(ruby_dir.join("ruby-3.3.5-b"), &ruby_path).unwrap();
+        let flipped_cache_key = config.ruby_path_cache_key(&ruby_path).unwrap();
+
+        assert_ne!(cache_key, flipped_cache_key);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_candidate_ruby_paths_skips_aliases() {
+        let (config, temp_dir) = create_test_config();
+        let ruby_dir = &config.ruby_dirs[0];
+        let elsewhere = Utf8PathBuf::from(temp_dir.path().to_str().unwrap()).join("elsewhere");
+        fs::create_dir_all(ruby_dir.join("ruby-3.3.5")).unwrap();
+        fs::create_dir_all(&elsewhere).unwrap();
+        fs::write(ruby_dir.join("notes.txt"), "").unwrap();
+
+        std::os::unix::fs::symlink("ruby-3.3.5", ruby_dir.join("current")).unwrap();
+        std::os::unix::fs::symlink(&elsewhere, ruby_dir.join("ruby-3.4.1")).unwrap();
+        std::os::unix::fs::symlink(ruby_dir.join("deleted"), ruby_dir.join("old")).unwrap();
+
+        let mut paths = candidate_ruby_paths(ruby_dir);
+        paths.sort();
+        assert_eq!(
+            paths,
+            [ruby_dir.join("ruby-3.3.5"), ruby_dir.join("ruby-3.4.1")]
+        );
+    }
+
     #[test]
     fn test_cache_key_missing_ruby_executable() {
         let (config, _temp_dir) = create_test_config();
@@ -262,6 +481,42 @@ mod tests {
         assert!(matches!(result.unwrap_err(), Error::RubyCacheMiss { .. }));
     }
 
+    #[test]
+    fn test_non_ruby_dirs_are_cached_until_changed() {
+        let (config, _temp_dir) = create_test_config();
+        let ruby_dir = &config.ruby_dirs[0];
+
+        let ruby_path = ruby_dir.join("ruby-3.1.0");
+        fs::create_dir_all(&ruby_path).unwrap();
+        fs::write(ruby_path.join("README"), "not a ruby").unwrap();
+        assert!(!config.is_known_non_ruby(&ruby_path));
+
+        assert!(config.discover_rubies().is_empty());
+        assert!(config.is_known_non_ruby(&ruby_path));
+
+        // Adding an executable changes the directory, so it gets probed again.
+        let bin_dir = ruby_path.join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        let ruby_exe = bin_dir.join("ruby");
+        fs::write(
+            &ruby_exe,
+            "#!/bin/bash\necho ruby\necho 3.1.0\necho x86_64-linux\necho x86_64\necho linux\necho\n",
+        )
+        .unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = fs::metadata(&ruby_exe).unwrap().permissions();
+            perms.set_mode(0o755);
+            fs::set_permissions(&ruby_exe, perms).unwrap();
+        }
+
+        assert!(!config.is_known_non_ruby(&ruby_path));
+        #[cfg(unix)]
+        assert_eq!(config.discover_rubies().len(), 1);
+    }
+
     #[test]
     fn test_get_cached_ruby_miss() {
         let (config, _temp_dir) = create_test_config();
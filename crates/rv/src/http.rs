@@ -0,0 +1,471 @@
+use std::io;
+use std::time::{Duration, SystemTime};
+
+use bytes::Bytes;
+use current_platform::CURRENT_PLATFORM;
+use fs_err as fs;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{Client, ClientBuilder, NoProxy, Proxy, StatusCode};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Don't re-check a cached response more than once a minute, whatever the server says.
+const MINIMUM_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// How long a response is fresh when the server doesn't send a `max-age`.
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+static PARSE_MAX_AGE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"max-age=(\d+)").unwrap());
+
+/// Sent with every request, like `rv/0.1.0 (darwin/aarch64)`.
+static USER_AGENT: Lazy<String> = Lazy::new(|| user_agent(CURRENT_PLATFORM));
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum Error {
+    #[error(transparent)]
+    RequestError(#[from] reqwest::Error),
+    #[error("Could not connect to {host}")]
+    ConnectError {
+        host: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("Timed out waiting for {url}")]
+    Timeout {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("{url} is not in the cache, and rv is offline")]
+    NotCached { url: String },
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+}
+
+/// How [`cached_get`] may use the cached response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CachePolicy {
+    /// Never make a request, and use the cached response however old it is.
+    pub offline: bool,
+    /// Re-validate the cached response with the server, even if it is still fresh.
+    pub revalidate: bool,
+    /// Don't send the cached ETag, so the server has to send the full response again.
+    pub ignore_etag: bool,
+    /// How long a response stays fresh, overriding the `max-age` sent by the server.
+    pub ttl: Option<Duration>,
+}
+
+/// A response body stored in the cache, with what is needed to re-validate it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub expires_at: SystemTime,
+    /// When the response was last fetched or re-validated, missing in older caches.
+    #[serde(default)]
+    pub fetched_at: Option<SystemTime>,
+    pub etag: Option<String>,
+    pub body: String,
+}
+
+/// Reads the response cached under `key` in `bucket` by [`cached_get`], however old it is.
+pub fn cached_response(
+    cache: &rv_cache::Cache,
+    bucket: rv_cache::CacheBucket,
+    key: &str,
+) -> Option<CachedResponse> {
+    let content = fs::read_to_string(cache.entry(bucket, "http", key).path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Like [`cached_response`], but only while the response is fresh under the given `ttl`.
+pub fn fresh_cached_response(
+    cache: &rv_cache::Cache,
+    bucket: rv_cache::CacheBucket,
+    key: &str,
+    ttl: Option<Duration>,
+) -> Option<CachedResponse> {
+    cached_response(cache, bucket, key).filter(|cached| is_fresh(cached, ttl, SystemTime::now()))
+}
+
+/// Drops the response cached under `key` in `bucket`, e.g. because it turned out to be unusable.
+pub fn remove_cached_response(cache: &rv_cache::Cache, bucket: rv_cache::CacheBucket, key: &str) {
+    let _ = fs::remove_file(cache.entry(bucket, "http", key).path());
+}
+
+/// GETs `url`, caching successful responses under `key` in `bucket`.
+///
+/// A cached response is used as is until the `max-age` the server sent with it (or the policy's
+/// `ttl`) runs out, and re-validated with its ETag after that. The status is `304 Not Modified` whenever the body
+/// comes from the cache. Responses other than `200 OK` are returned without being cached.
+pub async fn cached_get(
+    client: &Client,
+    cache: &rv_cache::Cache,
+    bucket: rv_cache::CacheBucket,
+    key: &str,
+    url: &str,
+    policy: CachePolicy,
+    timeout: Duration,
+) -> Result<(StatusCode, Bytes), Error> {
+    let cache_entry = cache.entry(bucket, "http", key);
+    let cached = cached_response(cache, bucket, key);
+
+    if policy.offline {
+        debug!("Offline, using the cached response for {url} if there is one.");
+        return match cached {
+            Some(cached) => Ok((StatusCode::NOT_MODIFIED, Bytes::from(cached.body))),
+            None => Err(Error::NotCached {
+                url: url.to_owned(),
+            }),
+        };
+    }
+
+    if let Some(cached) = &cached {
+        if policy.revalidate {
+            debug!("Re-validating the cached response for {url}.");
+        } else if is_fresh(cached, policy.ttl, SystemTime::now()) {
+            debug!("Using the cached response for {url}.");
+            return Ok((StatusCode::NOT_MODIFIED, Bytes::from(cached.body.clone())));
+        } else {
+            debug!("The cached response for {url} is stale, re-validating.");
+        }
+    }
+
+    let mut request = client.get(url).timeout(timeout);
+    if let Some(etag) = cached
+        .as_ref()
+        .filter(|_| !policy.ignore_etag)
+        .and_then(|cached| cached.etag.as_ref())
+    {
+        debug!("Using ETag to make a conditional request: {etag}");
+        request = request.header("If-None-Match", etag);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|err| request_error(err, url))?;
+
+    let status = response.status();
+    let fetched_at = SystemTime::now();
+    let ttl = policy
+        .ttl
+        .unwrap_or_else(|| max_age(response.headers()).max(MINIMUM_CACHE_TTL));
+    let expires_at = fetched_at + ttl;
+    match status {
+        StatusCode::NOT_MODIFIED => {
+            debug!("{url} is unchanged (304 Not Modified).");
+            let mut cached =
+                cached.ok_or_else(|| io::Error::other("304 response without prior cache"))?;
+            cached.expires_at = expires_at;
+            cached.fetched_at = Some(fetched_at);
+            fs::write(cache_entry.path(), serde_json::to_string(&cached)?)?;
+            Ok((status, Bytes::from(cached.body)))
+        }
+        StatusCode::OK => {
+            debug!("Received a new response from {url} (200 OK).");
+            let etag = response
+                .headers()
+                .get("ETag")
+                .and_then(|v| v.to_str().ok())
+                .map(String::from);
+            let body = response
+                .bytes()
+                .await
+                .map_err(|err| request_error(err, url))?;
+
+            // Only text responses are cached, others are still returned.
+            if let Ok(text) = std::str::from_utf8(&body) {
+                let cached = CachedResponse {
+                    expires_at,
+                    fetched_at: Some(fetched_at),
+                    etag,
+                    body: text.to_owned(),
+                };
+                fs::create_dir_all(cache_entry.dir())?;
+                fs::write(cache_entry.path(), serde_json::to_string(&cached)?)?;
+            }
+            Ok((status, body))
+        }
+        status => Ok((
+            status,
+            response
+                .bytes()
+                .await
+                .map_err(|err| request_error(err, url))?,
+        )),
+    }
+}
+
+/// Whether `cached` can be used without re-validating it.
+///
+/// A `ttl` counts from when the response was fetched, so that it also applies to responses
+/// cached before it was set.
+fn is_fresh(cached: &CachedResponse, ttl: Option<Duration>, now: SystemTime) -> bool {
+    match ttl {
+        Some(ttl) => cached
+            .fetched_at
+            .is_some_and(|fetched_at| now < fetched_at + ttl),
+        None => now < cached.expires_at,
+    }
+}
+
+/// Tells timeouts and connection failures for `url` apart from other request errors.
+pub fn request_error(err: reqwest::Error, url: &str) -> Error {
+    // Connect timeouts are also connect errors, but are reported as timeouts.
+    if err.is_timeout() {
+        Error::Timeout {
+            url: url.to_owned(),
+            source: err,
+        }
+    } else if err.is_connect() {
+        let host = err
+            .url()
+            .and_then(|url| url.host_str())
+            .unwrap_or(url)
+            .to_owned();
+        Error::ConnectError { host, source: err }
+    } else {
+        err.into()
+    }
+}
+
+/// How long the response with these headers stays fresh, from its `Cache-Control` header.
+fn max_age(headers: &reqwest::header::HeaderMap) -> Duration {
+    headers
+        .get("Cache-Control")
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_max_age)
+        .unwrap_or(DEFAULT_CACHE_TTL)
+}
+
+/// The User-Agent for an rv built for `platform`, a target triple like `aarch64-apple-darwin`.
+fn user_agent(platform: &str) -> String {
+    let mut parts = platform.split('-');
+    let arch = parts.next().unwrap_or("unknown");
+    let os = parts.nth(1).unwrap_or("unknown");
+    format!("rv/{} ({os}/{arch})", env!("CARGO_PKG_VERSION"))
+}
+
+/// Parses the `max-age` value from a `Cache-Control` header.
+fn parse_max_age(header: &str) -> Option<Duration> {
+    PARSE_MAX_AGE_REGEX
+        .captures(header)
+        .and_then(|caps| caps.get(1))
+        .and_then(|age| age.as_str().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Builds the HTTP client used for all network requests, usually through [`Config::http_client`].
+///
+/// Proxies are taken from `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY` (or their lowercase
+/// variants), skipping any hosts listed in `NO_PROXY`. Without those, reqwest's defaults apply,
+/// like the system's proxy settings. Setting `RV_NO_PROXY` forces a direct connection.
+///
+/// Connecting, and waiting for each read, give up after `timeout`, so a stalled connection
+/// can't hang rv. Downloads may take longer than that overall, as long as data keeps arriving.
+pub fn client(timeout: Duration) -> reqwest::Result<Client> {
+    let builder = Client::builder()
+        .user_agent(USER_AGENT.as_str())
+        .connect_timeout(timeout)
+        .read_timeout(timeout);
+    configure_proxies(builder, |name| std::env::var(name).ok())?.build()
+}
+
+/// The proxy settings in the environment.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ProxyEnv {
+    http: Option<String>,
+    https: Option<String>,
+    all: Option<String>,
+    no_proxy: Option<String>,
+}
+
+impl ProxyEnv {
+    /// Reads the proxy settings through `env`, or `None` if `RV_NO_PROXY` asks to connect
+    /// directly.
+    fn read(env: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let is_set = |name: &str| {
+            env(name)
+                .is_some_and(|value| !matches!(value.as_str(), "" | "0" | "false" | "no" | "off"))
+        };
+        if is_set("RV_NO_PROXY") {
+            return None;
+        }
+
+        // The uppercase name wins when both are set, and empty values are ignored.
+        let var = |name: &str| {
+            [name.to_owned(), name.to_lowercase()]
+                .into_iter()
+                .filter_map(|name| env(&name))
+                .find(|value| !value.is_empty())
+        };
+        Some(Self {
+            http: var("HTTP_PROXY"),
+            https: var("HTTPS_PROXY"),
+            all: var("ALL_PROXY"),
+            no_proxy: var("NO_PROXY"),
+        })
+    }
+
+    fn has_proxies(&self) -> bool {
+        self.http.is_some() || self.https.is_some() || self.all.is_some()
+    }
+}
+
+fn configure_proxies(
+    builder: ClientBuilder,
+    env: impl Fn(&str) -> Option<String>,
+) -> reqwest::Result<ClientBuilder> {
+    let Some(proxies) = ProxyEnv::read(env) else {
+        debug!("RV_NO_PROXY is set, connecting directly");
+        return Ok(builder.no_proxy());
+    };
+    if !proxies.has_proxies() {
+        return Ok(builder);
+    }
+
+    // Replace reqwest's own proxies, so they can't take precedence over the ones below.
+    let mut builder = builder.no_proxy();
+    let no_proxy = proxies.no_proxy.as_deref().and_then(NoProxy::from_string);
+    if let Some(url) = &proxies.http {
+        debug!("Using HTTP proxy {url}");
+        builder = builder.proxy(Proxy::http(url)?.no_proxy(no_proxy.clone()));
+    }
+    if let Some(url) = &proxies.https {
+        debug!("Using HTTPS proxy {url}");
+        builder = builder.proxy(Proxy::https(url)?.no_proxy(no_proxy.clone()));
+    }
+    if let Some(url) = &proxies.all {
+        debug!("Using proxy {url}");
+        builder = builder.proxy(Proxy::all(url)?.no_proxy(no_proxy));
+    }
+
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_proxy_env() {
+        assert_eq!(ProxyEnv::read(env(&[])), Some(ProxyEnv::default()));
+        assert_eq!(
+            ProxyEnv::read(env(&[
+                ("HTTP_PROXY", "http://proxy:1"),
+                ("HTTPS_PROXY", "http://proxy:2"),
+                ("ALL_PROXY", "socks5://proxy:3"),
+                ("NO_PROXY", "localhost,.example.com"),
+            ])),
+            Some(ProxyEnv {
+                http: Some("http://proxy:1".to_owned()),
+                https: Some("http://proxy:2".to_owned()),
+                all: Some("socks5://proxy:3".to_owned()),
+                no_proxy: Some("localhost,.example.com".to_owned()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_proxy_env_lowercase() {
+        assert_eq!(
+            ProxyEnv::read(env(&[
+                ("https_proxy", "http://proxy:2"),
+                ("all_proxy", "http://proxy:3"),
+                ("no_proxy", "localhost"),
+            ])),
+            Some(ProxyEnv {
+                http: None,
+                https: Some("http://proxy:2".to_owned()),
+                all: Some("http://proxy:3".to_owned()),
+                no_proxy: Some("localhost".to_owned()),
+            })
+        );
+
+        // The uppercase variable wins, unless it's empty.
+        let proxies = ProxyEnv::read(env(&[
+            ("HTTP_PROXY", "http://upper:1"),
+            ("http_proxy", "http://lower:1"),
+            ("NO_PROXY", ""),
+            ("no_proxy", "localhost"),
+        ]))
+        .unwrap();
+        assert_eq!(proxies.http.as_deref(), Some("http://upper:1"));
+        assert_eq!(proxies.no_proxy.as_deref(), Some("localhost"));
+    }
+
+    #[test]
+    fn test_rv_no_proxy() {
+        let vars = |flag| [("RV_NO_PROXY", flag), ("HTTP_PROXY", "http://proxy:1")];
+        assert_eq!(ProxyEnv::read(env(&vars("1"))), None);
+        assert_eq!(ProxyEnv::read(env(&vars("true"))), None);
+        for flag in ["", "0", "false"] {
+            let proxies = ProxyEnv::read(env(&vars(flag))).unwrap();
+            assert_eq!(proxies.http.as_deref(), Some("http://proxy:1"));
+        }
+    }
+
+    #[test]
+    fn test_no_proxy_alone_keeps_default_proxies() {
+        let proxies = ProxyEnv::read(env(&[("NO_PROXY", "localhost")])).unwrap();
+        assert!(!proxies.has_proxies());
+        assert!(
+            configure_proxies(Client::builder(), env(&[("NO_PROXY", "localhost")]))
+                .and_then(ClientBuilder::build)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_parse_cache_header() {
+        let input_header = "Cache-Control: max-age=3600, must-revalidate";
+        let actual = parse_max_age(input_header).unwrap();
+        let expected = Duration::from_secs(3600);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_ttl_overrides_expiry() {
+        let now = SystemTime::now();
+        let cached = CachedResponse {
+            expires_at: now + Duration::from_secs(60),
+            fetched_at: Some(now - Duration::from_secs(3600)),
+            etag: None,
+            body: String::new(),
+        };
+
+        assert!(is_fresh(&cached, None, now));
+        assert!(!is_fresh(&cached, Some(Duration::ZERO), now));
+        assert!(!is_fresh(&cached, Some(Duration::from_secs(60)), now));
+        assert!(is_fresh(&cached, Some(Duration::from_secs(86400)), now));
+
+        let legacy = CachedResponse {
+            fetched_at: None,
+            ..cached
+        };
+        assert!(!is_fresh(&legacy, Some(Duration::from_secs(86400)), now));
+    }
+
+    #[test]
+    fn test_user_agent() {
+        let version = env!("CARGO_PKG_VERSION");
+        assert_eq!(
+            user_agent("aarch64-apple-darwin"),
+            format!("rv/{version} (darwin/aarch64)")
+        );
+        assert_eq!(
+            user_agent("x86_64-unknown-linux-gnu"),
+            format!("rv/{version} (linux/x86_64)")
+        );
+    }
+}
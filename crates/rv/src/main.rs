@@ -3,29 +3,45 @@ use camino::{FromPathBufError, Utf8PathBuf};
 use clap::builder::Styles;
 use clap::builder::styling::AnsiColor;
 use clap::{ArgAction, CommandFactory, Parser, Subcommand};
-use config::Config;
+use config::{Config, GlobalConfig, ProjectConfig};
+use indexmap::IndexSet;
 use miette::Report;
 use rv_cache::CacheArgs;
+use std::time::Duration;
 use tokio::main;
 use tracing_indicatif::IndicatifLayer;
-use tracing_subscriber::{EnvFilter, layer::SubscriberExt as _, util::SubscriberInitExt as _};
+use tracing_subscriber::{
+    EnvFilter, filter::LevelFilter, layer::SubscriberExt as _, util::SubscriberInitExt as _,
+};
 
 pub mod commands;
 pub mod config;
-
-use crate::commands::cache::{CacheCommand, CacheCommandArgs, cache_clean, cache_dir, cache_prune};
+pub mod http;
+pub mod signature;
+pub mod update_check;
+
+use crate::commands::cache::{
+    CacheCommand, CacheCommandArgs, cache_clean, cache_dir, cache_prune, cache_verify,
+};
+use crate::commands::doctor::doctor;
+use crate::commands::ruby::current::current as ruby_current;
 use crate::commands::ruby::dir::dir as ruby_dir;
 use crate::commands::ruby::find::find as ruby_find;
+use crate::commands::ruby::info::info as ruby_info;
 use crate::commands::ruby::install::install as ruby_install;
+use crate::commands::ruby::list::OutputFormat;
 use crate::commands::ruby::list::list as ruby_list;
 use crate::commands::ruby::pin::pin as ruby_pin;
+use crate::commands::ruby::reshim::reshim as ruby_reshim;
 #[cfg(unix)]
 use crate::commands::ruby::run::run as ruby_run;
 use crate::commands::ruby::uninstall::uninstall as ruby_uninstall;
-use crate::commands::ruby::{RubyArgs, RubyCommand};
-use crate::commands::shell::completions::shell_completions;
+use crate::commands::ruby::upgrade::upgrade as ruby_upgrade;
+use crate::commands::ruby::{RubyArgs, RubyCommand, install, list};
+use crate::commands::shell::completions::{install_completions, shell_completions};
 use crate::commands::shell::env::env as shell_env;
 use crate::commands::shell::init::init as shell_init;
+use crate::commands::shell::init::print_path as shell_print_path;
 use crate::commands::shell::{ShellArgs, ShellCommand};
 
 const STYLES: Styles = Styles::styled()
@@ -44,15 +60,67 @@ const STYLES: Styles = Styles::styled()
 #[command(version)]
 #[command(disable_help_flag = true)]
 struct Cli {
-    /// Ruby directories to search for installations
+    /// Ruby directories to search for installations, replacing the defaults
     #[arg(long = "ruby-dir")]
     ruby_dir: Vec<Utf8PathBuf>,
 
+    /// Additional Ruby directories to search, after the defaults or any `--ruby-dir`
+    #[arg(long = "add-ruby-dir")]
+    add_ruby_dir: Vec<Utf8PathBuf>,
+
+    /// Also search the Ruby installations in chruby's `RUBIES`, separated by colons or newlines.
+    /// They come after the directories from `--ruby-dir` and `--add-ruby-dir`, which they don't
+    /// replace
+    #[arg(
+        long,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        env = "RV_CHRUBY_COMPAT"
+    )]
+    chruby_compat: bool,
+
     #[arg(long = "project-dir")]
     project_dir: Option<Utf8PathBuf>,
 
-    /// Path to Gemfile
-    #[arg(long, env = "BUNDLE_GEMFILE")]
+    /// Ignore any project and its pinned Ruby, using the default Ruby instead
+    #[arg(long, conflicts_with = "project_dir")]
+    no_project: bool,
+
+    /// Stop searching for a project at the first directory containing `.git`
+    #[arg(
+        long,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        env = "RV_STOP_AT_GIT"
+    )]
+    stop_at_git: bool,
+
+    /// Name of the file that pins a project's Ruby version
+    #[arg(
+        long,
+        env = "RV_RUBY_VERSION_FILE",
+        value_name = "NAME",
+        default_value = config::DEFAULT_RUBY_VERSION_FILE
+    )]
+    ruby_version_file: String,
+
+    /// Never access the network, only use cached downloads and release lists
+    #[arg(
+        long,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        env = "RV_OFFLINE"
+    )]
+    offline: bool,
+
+    /// Seconds to wait for a network connection or response before giving up [default: 30]
+    #[arg(long, env = "RV_TIMEOUT", value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Seconds the cached list of available rubies stays fresh, overriding the server's `max-age`
+    #[arg(long, env = "RV_RELEASES_TTL", value_name = "SECONDS")]
+    releases_ttl: Option<u64>,
+
+    /// Path to Gemfile, exported as BUNDLE_GEMFILE along with the Ruby. An inherited
+    /// BUNDLE_GEMFILE isn't used, as it may belong to the project rv activated before
+    #[arg(long)]
     gemfile: Option<Utf8PathBuf>,
 
     #[command(flatten)]
@@ -65,6 +133,15 @@ struct Cli {
     #[arg(long, env = "RV_COLOR")]
     color: Option<ColorMode>,
 
+    /// Format of log messages
+    #[arg(long, env = "RV_LOG_FORMAT", value_enum, default_value = "text")]
+    log_format: LogFormat,
+
+    /// Settings file to use instead of `~/.config/rv/config.toml`. Flags and environment
+    /// variables win over the settings in it
+    #[arg(long = "config", env = "RV_CONFIG", value_name = "FILE")]
+    config_file: Option<Utf8PathBuf>,
+
     #[command(flatten)]
     cache_args: CacheArgs,
 
@@ -81,26 +158,102 @@ struct Cli {
 }
 
 impl Cli {
-    fn config(&self) -> Result<Config> {
-        let root = if self.root_dir.is_some() {
-            self.root_dir.clone().unwrap()
-        } else {
-            "/".into()
-        };
+    /// Whether `-q`/`--quiet` was given.
+    fn quiet(&self) -> bool {
+        self.verbose.tracing_level_filter() < LevelFilter::INFO
+    }
 
-        let current_dir: Utf8PathBuf = std::env::current_dir()?.try_into()?;
-        let project_dir = if let Some(project_dir) = &self.project_dir {
+    /// The `--format` of the command, for the commands that have one.
+    fn output_format(&self) -> Option<&OutputFormat> {
+        match self.command.as_ref()? {
+            Commands::Ruby(ruby) => match &ruby.command {
+                RubyCommand::List(args) => Some(&args.format),
+                RubyCommand::Current { format }
+                | RubyCommand::Find { format, .. }
+                | RubyCommand::Info { format, .. } => Some(format),
+                _ => None,
+            },
+            Commands::Cache(cache) => match &cache.command {
+                CacheCommand::Dir { format } => Some(format),
+                _ => None,
+            },
+            Commands::Shell(_) | Commands::Doctor => None,
+        }
+    }
+
+    fn root(&self) -> Utf8PathBuf {
+        self.root_dir.clone().unwrap_or_else(|| "/".into())
+    }
+
+    /// The settings from `--config`, or the default config file if there is one.
+    fn global_config(&self) -> std::result::Result<GlobalConfig, config::Error> {
+        match &self.config_file {
+            Some(path) => GlobalConfig::load(path),
+            None => GlobalConfig::load_default(&self.root()),
+        }
+    }
+
+    /// Builds the configuration from the flags, falling back to `global_config` for the ones
+    /// that weren't given.
+    fn config(&self, global_config: &GlobalConfig) -> Result<Config> {
+        let root = self.root();
+
+        let current_dir: Utf8PathBuf = config::current_dir()?.try_into()?;
+        let project_dir = if self.no_project {
+            None
+        } else if let Some(project_dir) = &self.project_dir {
             Some(project_dir.clone())
         } else {
-            config::find_project_dir(current_dir.clone(), root.clone())
+            config::find_project_dir(
+                current_dir.clone(),
+                root.clone(),
+                self.stop_at_git,
+                &self.ruby_version_file,
+            )
+        };
+        // Explicit replacements (or the defaults) take precedence over appended directories.
+        let replaced_ruby_dirs = if self.ruby_dir.is_empty() {
+            &global_config.ruby_dirs
+        } else {
+            &self.ruby_dir
         };
-        let ruby_dirs = if self.ruby_dir.is_empty() {
-            config::default_ruby_dirs(&root)
+        let mut ruby_dirs: IndexSet<Utf8PathBuf> = if replaced_ruby_dirs.is_empty() {
+            config::default_ruby_dirs(&root).into_iter().collect()
         } else {
-            self.ruby_dir
+            replaced_ruby_dirs
                 .iter()
-                .map(|path: &Utf8PathBuf| root.join(path))
-                .collect()
+                .map(|path| config::expand_ruby_dir(&root, path))
+                .collect::<std::result::Result<_, _>>()?
+        };
+        for path in &self.add_ruby_dir {
+            ruby_dirs.insert(config::expand_ruby_dir(&root, path)?);
+        }
+        // Settings from `rv.toml` come after anything given on the command line or in the environment.
+        let project_config = match &project_dir {
+            Some(project_dir) => ProjectConfig::load(project_dir)?,
+            None => ProjectConfig::default(),
+        };
+        if let Some(project_dir) = &project_dir
+            && !project_config.ruby_dirs.is_empty()
+        {
+            // Rubies in these directories get run, so a checkout can't add them on its own.
+            let trusted = global_config
+                .trusted_projects
+                .iter()
+                .map(|path| config::expand_ruby_dir(&root, path))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            if trusted.contains(project_dir) {
+                ruby_dirs.extend(project_config.ruby_dirs.iter().cloned());
+            } else {
+                tracing::warn!(
+                    "Ignoring the ruby-dirs in {}, add {project_dir} to trusted-projects in rv's config file to search them",
+                    project_dir.join(config::PROJECT_CONFIG_FILE)
+                );
+            }
+        }
+        let chruby_rubies = match std::env::var("RUBIES") {
+            Ok(rubies) if self.chruby_compat => config::parse_chruby_rubies(&root, &rubies)?,
+            _ => Vec::new(),
         };
         let cache = self.cache_args.to_cache()?;
         let current_exe = if let Some(exe) = self.current_exe.clone() {
@@ -111,16 +264,34 @@ impl Cli {
 
         Ok(Config {
             ruby_dirs,
-            gemfile: self.gemfile.clone(),
+            chruby_rubies,
+            // Relative to where rv was run, but exported for shells that may `cd` elsewhere.
+            gemfile: self
+                .gemfile
+                .as_ref()
+                .map(|gemfile| current_dir.join(gemfile)),
             root,
             current_dir,
             project_dir,
+            ruby_version_file: self.ruby_version_file.clone(),
+            project_config,
             cache,
             current_exe,
+            offline: self.offline,
+            timeout: Duration::from_secs(
+                self.timeout
+                    .or(global_config.timeout)
+                    .unwrap_or(DEFAULT_TIMEOUT_SECS),
+            ),
+            releases_ttl: self.releases_ttl.map(Duration::from_secs),
+            http_client: Default::default(),
+            quiet: self.quiet(),
         })
     }
 }
 
+// Parsed once per run, so the size of the largest command doesn't matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     #[command(about = "Manage Ruby versions and installations")]
@@ -129,9 +300,12 @@ enum Commands {
     Cache(CacheCommandArgs),
     #[command(about = "Configure your shell to use rv")]
     Shell(ShellArgs),
+    #[command(about = "Check your rv setup for problems")]
+    Doctor,
 }
 
-#[derive(Debug, Copy, Clone, clap::ValueEnum)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub(crate) enum ColorMode {
     /// Use color output if the output supports it.
     Auto,
@@ -162,6 +336,14 @@ impl ColorMode {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum LogFormat {
+    /// Human-readable log lines.
+    Text,
+    /// One JSON object per log line, for log aggregation.
+    Json,
+}
+
 impl From<ColorMode> for anstream::ColorChoice {
     /// Maps `ColorMode` to `anstream::ColorChoice`.
     fn from(value: ColorMode) -> Self {
@@ -182,8 +364,12 @@ pub enum Error {
     #[error(transparent)]
     ConfigError(#[from] config::Error),
     #[error(transparent)]
+    CurrentError(#[from] commands::ruby::current::Error),
+    #[error(transparent)]
     FindError(#[from] commands::ruby::find::Error),
     #[error(transparent)]
+    InfoError(#[from] commands::ruby::info::Error),
+    #[error(transparent)]
     PinError(#[from] commands::ruby::pin::Error),
     #[error(transparent)]
     ListError(#[from] commands::ruby::list::Error),
@@ -191,6 +377,10 @@ pub enum Error {
     InstallError(#[from] commands::ruby::install::Error),
     #[error(transparent)]
     UninstallError(#[from] commands::ruby::uninstall::Error),
+    #[error(transparent)]
+    UpgradeError(#[from] commands::ruby::upgrade::Error),
+    #[error(transparent)]
+    ReshimError(#[from] commands::ruby::reshim::Error),
     #[cfg(unix)]
     #[error(transparent)]
     RunError(#[from] commands::ruby::run::Error),
@@ -200,33 +390,153 @@ pub enum Error {
     InitError(#[from] commands::shell::init::Error),
     #[error(transparent)]
     EnvError(#[from] commands::shell::env::Error),
+    #[error(transparent)]
+    CompletionsError(#[from] commands::shell::completions::Error),
+    #[error(transparent)]
+    DoctorError(#[from] commands::doctor::Error),
+}
+
+/// How long to wait for the network without `--timeout` or a `timeout` in the config file.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Exit status for errors without a more specific code below.
+const FAILURE_EXIT_CODE: i32 = 1;
+/// Exit status when no installed or released Ruby matches the request.
+const NOT_FOUND_EXIT_CODE: i32 = commands::ruby::find::NOT_FOUND_EXIT_CODE;
+/// Exit status when the configuration, a project's pin or `rv.toml` is invalid or missing.
+const CONFIG_EXIT_CODE: i32 = 3;
+/// Exit status when a download or request failed, or would have needed the network.
+const NETWORK_EXIT_CODE: i32 = 4;
+
+impl Error {
+    /// The exit status rv ends with when this error reaches `main`, so scripts can tell
+    /// failures apart. These are stable:
+    ///
+    /// | Code | Meaning                                                   |
+    /// |------|-----------------------------------------------------------|
+    /// | 1    | Any other error                                           |
+    /// | 2    | Invalid arguments, reported by clap before anything runs  |
+    /// | 3    | Invalid or missing configuration                          |
+    /// | 4    | Network failure, or the network was needed while offline  |
+    /// | 5    | No Ruby matches the request                               |
+    fn exit_code(&self) -> i32 {
+        use commands::ruby::{current, find, info, pin, uninstall, upgrade};
+        use commands::shell::env;
+
+        match self {
+            Self::ConfigError(_)
+            | Self::FindError(find::Error::ConfigError(_))
+            | Self::InfoError(info::Error::ConfigError(_))
+            | Self::PinError(pin::Error::ConfigError(_))
+            | Self::ListError(list::Error::ConfigError(_))
+            | Self::UninstallError(uninstall::Error::ConfigError(_))
+            | Self::UpgradeError(
+                upgrade::Error::ConfigError(_)
+                | upgrade::Error::PinError(pin::Error::ConfigError(_)),
+            )
+            | Self::EnvError(env::Error::ConfigError(_)) => CONFIG_EXIT_CODE,
+            #[cfg(unix)]
+            Self::RunError(commands::ruby::run::Error::ConfigError(_)) => CONFIG_EXIT_CODE,
+            #[cfg(unix)]
+            Self::RunError(commands::ruby::run::Error::NoMatchingRuby) => NOT_FOUND_EXIT_CODE,
+            Self::FindError(find::Error::NoMatchingRuby)
+            | Self::InfoError(info::Error::NoMatchingRuby)
+            | Self::CurrentError(current::Error::NoActiveRuby)
+            | Self::UninstallError(uninstall::Error::NoMatchingRuby)
+            | Self::EnvError(env::Error::NoRubyFound) => NOT_FOUND_EXIT_CODE,
+            Self::ListError(err) => list_exit_code(err),
+            Self::InstallError(err) | Self::UpgradeError(upgrade::Error::InstallError(err)) => {
+                install_exit_code(err)
+            }
+            _ => FAILURE_EXIT_CODE,
+        }
+    }
+
+    /// A stable name for the kind of error, matching its exit status.
+    fn kind(&self) -> &'static str {
+        match self.exit_code() {
+            NOT_FOUND_EXIT_CODE => "not_found",
+            CONFIG_EXIT_CODE => "config",
+            NETWORK_EXIT_CODE => "network",
+            _ => "failure",
+        }
+    }
+
+    /// The error as a JSON object, printed instead of the report when JSON output was asked for.
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": {
+                "kind": self.kind(),
+                "message": self.to_string(),
+            }
+        })
+    }
+}
+
+fn install_exit_code(err: &install::Error) -> i32 {
+    match err {
+        install::Error::NoMatchingRelease { .. } => NOT_FOUND_EXIT_CODE,
+        install::Error::ReqwestError(_)
+        | install::Error::DownloadFailed { .. }
+        | install::Error::Offline(_) => NETWORK_EXIT_CODE,
+        install::Error::HttpError(err) => http_exit_code(err),
+        install::Error::GetLatestReleaseFailed { error } => list_exit_code(error),
+        _ => FAILURE_EXIT_CODE,
+    }
+}
+
+fn list_exit_code(err: &list::Error) -> i32 {
+    match err {
+        list::Error::ConfigError(_) => CONFIG_EXIT_CODE,
+        list::Error::ReleasesStatus { .. } | list::Error::ConnectError { .. } => NETWORK_EXIT_CODE,
+        list::Error::HttpError(err) => http_exit_code(err),
+        _ => FAILURE_EXIT_CODE,
+    }
+}
+
+fn http_exit_code(err: &http::Error) -> i32 {
+    match err {
+        http::Error::RequestError(_)
+        | http::Error::ConnectError { .. }
+        | http::Error::Timeout { .. }
+        | http::Error::NotCached { .. } => NETWORK_EXIT_CODE,
+        _ => FAILURE_EXIT_CODE,
+    }
 }
 
 type Result<T> = miette::Result<T, Error>;
 
 #[main]
 async fn main() {
-    if let Err(err) = run().await {
+    let cli = Cli::parse();
+    // Programs reading JSON from stdout get the error there too, in a form they can parse.
+    let json_errors = cli
+        .output_format()
+        .is_some_and(|format| matches!(format, OutputFormat::Json | OutputFormat::Ndjson));
+
+    if let Err(err) = run(cli).await {
+        let exit_code = err.exit_code();
         let is_tty = std::io::stderr().is_terminal();
-        if is_tty {
+        if json_errors {
+            println!("{}", err.to_json());
+        } else if is_tty {
             eprintln!("{:?}", Report::new(err));
         } else {
             eprintln!("Error: {:?}", err);
         }
-        std::process::exit(1);
+        std::process::exit(exit_code);
     }
 }
 
-async fn run() -> Result<()> {
-    let cli = Cli::parse();
-
+async fn run(cli: Cli) -> Result<()> {
     let indicatif_layer = IndicatifLayer::new();
+    let global_config = cli.global_config()?;
 
     let color_mode = match cli.color {
         Some(color_mode) => color_mode,
         None => {
             // If `--color` wasn't specified, we first check a handful
-            // of common environment variables, and then fall
+            // of common environment variables, then the config file, and then fall
             // back to `anstream`'s auto detection.
             if std::env::var("NO_COLOR").is_ok() {
                 ColorMode::Never
@@ -235,76 +545,203 @@ async fn run() -> Result<()> {
             {
                 ColorMode::Always
             } else {
-                ColorMode::Auto
+                global_config.color.unwrap_or(ColorMode::Auto)
             }
         }
     };
 
     anstream::ColorChoice::write_global(color_mode.into());
+    // Command output goes through `anstream`, which honors the global choice. Error reports
+    // are rendered by miette, which otherwise decides on colors by itself.
+    if matches!(color_mode, ColorMode::Never) {
+        let _ = miette::set_hook(Box::new(|_| {
+            Box::new(miette::MietteHandlerOpts::new().color(false).build())
+        }));
+    }
 
     let writer = std::sync::Mutex::new(anstream::AutoStream::new(
         Box::new(indicatif_layer.get_stderr_writer()) as Box<dyn std::io::Write + Send>,
         color_mode.color_choice_for_terminal(std::io::stderr()),
     ));
 
+    // Quiet mode is for scripts, which only want to hear about hard errors.
+    let level = if cli.quiet() {
+        cli.verbose.tracing_level_filter().min(LevelFilter::ERROR)
+    } else {
+        cli.verbose.tracing_level_filter()
+    };
     let filter = EnvFilter::builder()
-        .with_default_directive(cli.verbose.tracing_level_filter().into())
+        .with_default_directive(level.into())
         .from_env()?;
 
-    let reg = tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::fmt::layer()
-                .without_time()
-                // NOTE: We don't need `with_ansi` here since our writer is
-                // an `anstream::AutoStream` that handles color output for us.
-                .with_writer(writer),
-        )
-        .with(filter);
-
-    if std::env::var("RV_DISABLE_INDICATIF").is_err() {
-        reg.with(indicatif_layer).init();
-    } else {
-        reg.init();
-    }
+    let (text_layer, json_layer) = match cli.log_format {
+        LogFormat::Text => (
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .without_time()
+                    // NOTE: We don't need `with_ansi` here since our writer is
+                    // an `anstream::AutoStream` that handles color output for us.
+                    .with_writer(writer),
+            ),
+            None,
+        ),
+        LogFormat::Json => (
+            None,
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_writer(std::io::stderr),
+            ),
+        ),
+    };
+
+    // Progress bars would interleave with JSON log lines, so they're only used for text logs.
+    let use_indicatif =
+        std::env::var("RV_DISABLE_INDICATIF").is_err() && cli.log_format == LogFormat::Text;
+
+    tracing_subscriber::registry()
+        .with(text_layer)
+        .with(json_layer)
+        .with(filter)
+        .with(use_indicatif.then_some(indicatif_layer))
+        .init();
 
-    let config = cli.config()?;
+    let config = cli.config(&global_config)?;
 
     match cli.command {
         None => {}
         Some(cmd) => match cmd {
             Commands::Ruby(ruby) => match ruby.command {
-                RubyCommand::Find { request } => ruby_find(&config, &request)?,
-                RubyCommand::List {
+                RubyCommand::Find {
+                    request,
+                    explain,
+                    all,
                     format,
-                    installed_only,
-                } => ruby_list(&config, format, installed_only).await?,
-                RubyCommand::Pin { version_request } => ruby_pin(&config, version_request)?,
+                } => ruby_find(&config, &request, explain, all, format)?,
+                RubyCommand::List(args) => ruby_list(&config, args).await?,
+                RubyCommand::Pin {
+                    version_request,
+                    unset,
+                    list_candidates,
+                } => ruby_pin(&config, version_request, unset, list_candidates)?,
                 RubyCommand::Dir => ruby_dir(&config),
-                RubyCommand::Install {
-                    version,
-                    install_dir,
-                    tarball_path,
-                } => ruby_install(&config, install_dir, version, tarball_path).await?,
+                RubyCommand::Current { format } => ruby_current(&config, format)?,
+                RubyCommand::Info { request, format } => ruby_info(&config, &request, format)?,
+                RubyCommand::Install(args) => ruby_install(&config, args).await?,
+                RubyCommand::Upgrade { all } => ruby_upgrade(&config, all).await?,
                 RubyCommand::Uninstall {
                     version: version_request,
                 } => ruby_uninstall(&config, version_request).await?,
+                RubyCommand::Reshim => ruby_reshim(&config)?,
                 #[cfg(unix)]
-                RubyCommand::Run { version, args } => ruby_run(&config, &version, &args)?,
+                RubyCommand::Run {
+                    version,
+                    version_flag,
+                    args,
+                } => ruby_run(&config, version.or(version_flag), &args)?,
             },
             Commands::Cache(cache) => match cache.command {
-                CacheCommand::Dir => cache_dir(&config)?,
+                CacheCommand::Dir { format } => cache_dir(&config, format)?,
                 CacheCommand::Clean => cache_clean(&config)?,
                 CacheCommand::Prune => cache_prune(&config)?,
+                CacheCommand::Verify { prune } => cache_verify(&config, prune)?,
             },
             Commands::Shell(shell) => match shell.command {
-                ShellCommand::Init { shell } => shell_init(&config, shell)?,
-                ShellCommand::Completions { shell } => {
-                    shell_completions(&mut Cli::command(), shell)
-                }
-                ShellCommand::Env { shell } => shell_env(&config, shell)?,
+                ShellCommand::Init {
+                    shell: Some(shell), ..
+                } => shell_init(&config, shell)?,
+                // Without a shell, clap guarantees `--print-path` was given.
+                ShellCommand::Init { shell: None, .. } => shell_print_path(&config),
+                ShellCommand::Completions {
+                    shell,
+                    install: false,
+                    ..
+                } => shell_completions(&mut Cli::command(), shell),
+                ShellCommand::Completions {
+                    shell,
+                    install: true,
+                    force,
+                } => install_completions(&config, &mut Cli::command(), shell, force)?,
+                ShellCommand::Env {
+                    shell,
+                    unset_all,
+                    source,
+                    hide_env,
+                } => shell_env(&config, shell, unset_all, source, hide_env)?,
             },
+            Commands::Doctor => doctor(&config)?,
         },
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        let no_project = || config::Error::NoProjectDir {
+            current_dir: "/".into(),
+        };
+        let offline = || install::Error::Offline("https://example.com".into());
+
+        let io_error = std::io::Error::other("oops");
+        assert_eq!(Error::IoError(io_error).exit_code(), 1);
+        assert_eq!(
+            Error::FindError(commands::ruby::find::Error::NoMatchingRuby).exit_code(),
+            5
+        );
+        assert_eq!(
+            Error::InstallError(install::Error::NoMatchingRelease {
+                requested: "3.9".parse().unwrap(),
+                nearest: String::new(),
+            })
+            .exit_code(),
+            5
+        );
+        assert_eq!(Error::ConfigError(no_project()).exit_code(), 3);
+        assert_eq!(
+            Error::PinError(commands::ruby::pin::Error::ConfigError(no_project())).exit_code(),
+            3
+        );
+        assert_eq!(Error::InstallError(offline()).exit_code(), 4);
+        assert_eq!(
+            Error::UpgradeError(commands::ruby::upgrade::Error::InstallError(offline()))
+                .exit_code(),
+            4
+        );
+        assert_eq!(
+            Error::ListError(list::Error::HttpError(http::Error::NotCached {
+                url: "https://example.com".into()
+            }))
+            .exit_code(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_error_to_json() {
+        let err = Error::InstallError(install::Error::Offline("https://example.com".into()));
+        assert_eq!(
+            err.to_json().to_string(),
+            r#"{"error":{"kind":"network","message":"https://example.com is not in the cache, and rv is offline"}}"#
+        );
+    }
+
+    #[test]
+    fn test_output_format() {
+        let format = |args: &[&str]| Cli::try_parse_from(args).unwrap().output_format().cloned();
+        assert_eq!(
+            format(&["rv", "ruby", "list", "--format", "json"]),
+            Some(OutputFormat::Json)
+        );
+        assert_eq!(
+            format(&["rv", "ruby", "find", "--format", "ndjson"]),
+            Some(OutputFormat::Ndjson)
+        );
+        assert_eq!(format(&["rv", "cache", "dir"]), Some(OutputFormat::Text));
+        assert_eq!(format(&["rv", "ruby", "dir"]), None);
+    }
+}
@@ -0,0 +1,256 @@
+//! Verification of [minisign](https://jedisct1.github.io/minisign/) signatures.
+//!
+//! Only legacy signatures over the whole file are supported, as made by `minisign -S -l`.
+//! Pre-hashed signatures (the default of newer minisign versions) are rejected with an error
+//! that says so, because they need a BLAKE2b implementation rv doesn't ship.
+
+use std::str::FromStr;
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use camino::Utf8Path;
+use ring::signature::{ED25519, UnparsedPublicKey};
+
+const LEGACY_ALGORITHM: &[u8] = b"Ed";
+const PREHASHED_ALGORITHM: &[u8] = b"ED";
+const TRUSTED_COMMENT_PREFIX: &str = "trusted comment: ";
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum Error {
+    #[error("Invalid minisign public key")]
+    InvalidPublicKey,
+    #[error("No signature found at {0}")]
+    MissingSignature(String),
+    #[error("Invalid minisign signature {0}")]
+    InvalidSignatureFile(String),
+    #[error(
+        "{0} is a pre-hashed minisign signature, rv only verifies legacy ones made with `minisign -S -l`"
+    )]
+    PrehashedSignature(String),
+    #[error("{signature} was made with key {actual}, not with key {expected}")]
+    WrongKey {
+        signature: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("Signature {signature} does not match {path}")]
+    BadSignature { signature: String, path: String },
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+}
+
+/// An Ed25519 public key in minisign's format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    key_id: [u8; 8],
+    key: [u8; 32],
+}
+
+impl PublicKey {
+    /// The key ID the way `minisign` prints it.
+    pub fn id(&self) -> String {
+        key_id_string(&self.key_id)
+    }
+}
+
+/// Parses the base64 key on its own, or the whole contents of a minisign `.pub` file.
+impl FromStr for PublicKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let encoded = s
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+            .ok_or(Error::InvalidPublicKey)?;
+        let bytes = BASE64
+            .decode(encoded)
+            .map_err(|_| Error::InvalidPublicKey)?;
+        if bytes.len() != 42 || &bytes[..2] != LEGACY_ALGORITHM {
+            return Err(Error::InvalidPublicKey);
+        }
+
+        Ok(PublicKey {
+            key_id: bytes[2..10].try_into().unwrap(),
+            key: bytes[10..].try_into().unwrap(),
+        })
+    }
+}
+
+/// Checks `path` against the minisign signature at `signature_path` made with `key`,
+/// including the signature of its trusted comment.
+pub fn verify_file(
+    path: &Utf8Path,
+    signature_path: &Utf8Path,
+    key: &PublicKey,
+) -> Result<(), Error> {
+    let signature = match std::fs::read_to_string(signature_path) {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(Error::MissingSignature(signature_path.to_string()));
+        }
+        result => result?,
+    };
+    let content = std::fs::read(path)?;
+    verify(&content, &signature, key).map_err(|err| match err {
+        SignatureError::Malformed => Error::InvalidSignatureFile(signature_path.to_string()),
+        SignatureError::Prehashed => Error::PrehashedSignature(signature_path.to_string()),
+        SignatureError::WrongKey(actual) => Error::WrongKey {
+            signature: signature_path.to_string(),
+            expected: key.id(),
+            actual,
+        },
+        SignatureError::Mismatch => Error::BadSignature {
+            signature: signature_path.to_string(),
+            path: path.to_string(),
+        },
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum SignatureError {
+    Malformed,
+    Prehashed,
+    WrongKey(String),
+    Mismatch,
+}
+
+/// Verifies `content` against the contents of a `.minisig` file, which are:
+///
+/// ```text
+/// untrusted comment: <anything>
+/// base64(<algorithm> <key id> <signature of content>)
+/// trusted comment: <comment>
+/// base64(<signature of the content signature followed by the comment>)
+/// ```
+fn verify(content: &[u8], signature: &str, key: &PublicKey) -> Result<(), SignatureError> {
+    let mut lines = signature.lines().map(str::trim_end);
+    let (Some(_), Some(encoded), Some(trusted_comment), Some(encoded_global)) =
+        (lines.next(), lines.next(), lines.next(), lines.next())
+    else {
+        return Err(SignatureError::Malformed);
+    };
+    let trusted_comment = trusted_comment
+        .strip_prefix(TRUSTED_COMMENT_PREFIX)
+        .ok_or(SignatureError::Malformed)?;
+    let decoded = BASE64
+        .decode(encoded)
+        .map_err(|_| SignatureError::Malformed)?;
+    let global_signature = BASE64
+        .decode(encoded_global)
+        .map_err(|_| SignatureError::Malformed)?;
+    if decoded.len() != 74 || global_signature.len() != 64 {
+        return Err(SignatureError::Malformed);
+    }
+
+    let (algorithm, rest) = decoded.split_at(2);
+    let (key_id, file_signature) = rest.split_at(8);
+    match algorithm {
+        LEGACY_ALGORITHM => {}
+        PREHASHED_ALGORITHM => return Err(SignatureError::Prehashed),
+        _ => return Err(SignatureError::Malformed),
+    }
+    if key_id != key.key_id {
+        return Err(SignatureError::WrongKey(key_id_string(key_id)));
+    }
+
+    let public_key = UnparsedPublicKey::new(&ED25519, key.key);
+    public_key
+        .verify(content, file_signature)
+        .map_err(|_| SignatureError::Mismatch)?;
+    let signed_comment = [file_signature, trusted_comment.as_bytes()].concat();
+    public_key
+        .verify(&signed_comment, &global_signature)
+        .map_err(|_| SignatureError::Mismatch)
+}
+
+/// Minisign shows key IDs as hex of the little-endian number in them.
+fn key_id_string(key_id: &[u8]) -> String {
+    key_id
+        .iter()
+        .rev()
+        .map(|byte| format!("{byte:02X}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair as _};
+
+    const KEY_ID: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    fn key_pair() -> Ed25519KeyPair {
+        Ed25519KeyPair::from_seed_unchecked(&[7; 32]).unwrap()
+    }
+
+    fn public_key(key_pair: &Ed25519KeyPair) -> String {
+        let bytes = [
+            LEGACY_ALGORITHM,
+            &KEY_ID[..],
+            key_pair.public_key().as_ref(),
+        ]
+        .concat();
+        format!(
+            "untrusted comment: minisign public key\n{}\n",
+            BASE64.encode(bytes)
+        )
+    }
+
+    fn sign(key_pair: &Ed25519KeyPair, algorithm: &[u8], content: &[u8]) -> String {
+        let signature = key_pair.sign(content);
+        let comment = "timestamp:1700000000";
+        let global = key_pair.sign(&[signature.as_ref(), comment.as_bytes()].concat());
+        format!(
+            "untrusted comment: signature\n{}\n{TRUSTED_COMMENT_PREFIX}{comment}\n{}\n",
+            BASE64.encode([algorithm, &KEY_ID[..], signature.as_ref()].concat()),
+            BASE64.encode(global.as_ref())
+        )
+    }
+
+    #[test]
+    fn test_verify_legacy_signature() {
+        let key_pair = key_pair();
+        let key: PublicKey = public_key(&key_pair).parse().unwrap();
+        assert_eq!(key.id(), "0807060504030201");
+
+        let signature = sign(&key_pair, LEGACY_ALGORITHM, b"ruby");
+        assert_eq!(verify(b"ruby", &signature, &key), Ok(()));
+        assert_eq!(
+            verify(b"rubx", &signature, &key),
+            Err(SignatureError::Mismatch)
+        );
+
+        let tampered = signature.replace("timestamp:1700000000", "timestamp:1700000001");
+        assert_eq!(
+            verify(b"ruby", &tampered, &key),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_other_signatures() {
+        let key_pair = key_pair();
+        let key: PublicKey = public_key(&key_pair).parse().unwrap();
+
+        let prehashed = sign(&key_pair, PREHASHED_ALGORITHM, b"ruby");
+        assert_eq!(
+            verify(b"ruby", &prehashed, &key),
+            Err(SignatureError::Prehashed)
+        );
+
+        let other_key = PublicKey {
+            key_id: [9; 8],
+            ..key
+        };
+        let signature = sign(&key_pair, LEGACY_ALGORITHM, b"ruby");
+        assert_eq!(
+            verify(b"ruby", &signature, &other_key),
+            Err(SignatureError::WrongKey("0807060504030201".to_owned()))
+        );
+
+        assert_eq!(
+            verify(b"ruby", "not a signature", &key),
+            Err(SignatureError::Malformed)
+        );
+        assert!("not a key".parse::<PublicKey>().is_err());
+    }
+}
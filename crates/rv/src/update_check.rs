@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use anstream::eprintln;
+use owo_colors::OwoColorize;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use crate::config::Config;
+use crate::http;
+
+/// Set to `1` to be told about new rv releases.
+const CHECK_UPDATE_ENV: &str = "RV_CHECK_UPDATE";
+
+/// Where rv's latest release is cached by [`crate::http::cached_get`].
+const RV_RELEASE_CACHE_KEY: &str = "latest_rv_release.json";
+
+/// rv's releases are checked at most once a day.
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The check gives up sooner than other requests, as nobody is waiting for it.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Deserialize)]
+struct LatestRelease {
+    tag_name: String,
+}
+
+/// Whether `RV_CHECK_UPDATE=1` asked for the update check.
+pub fn is_enabled() -> bool {
+    std::env::var(CHECK_UPDATE_ENV).is_ok_and(|value| value == "1")
+}
+
+/// A check for a newer rv release, made while a command does its own work.
+///
+/// The command never waits for it: the cached release is used, and a refresh of the cache that
+/// hasn't finished by the end of the command is given up.
+pub struct UpdateCheck {
+    /// The latest release in the cache, however old.
+    cached: Option<String>,
+    /// Refreshes the cached release when it is out of date.
+    refresh: Option<JoinHandle<Option<String>>>,
+}
+
+impl UpdateCheck {
+    /// Starts the check, unless it wasn't asked for. When offline, only the cache is read.
+    pub fn start(config: &Config) -> Option<Self> {
+        if !is_enabled() || config.quiet {
+            return None;
+        }
+        let api_base = std::env::var("RV_RELEASES_URL")
+            .unwrap_or_else(|_| "https://api.github.com".to_string());
+        if api_base == "-" {
+            return None;
+        }
+        let url = format!("{}/repos/spinel-coop/rv/releases/latest", api_base);
+
+        let bucket = rv_cache::CacheBucket::Ruby;
+        let cached = http::cached_response(&config.cache, bucket, RV_RELEASE_CACHE_KEY)
+            .and_then(|cached| release_tag(cached.body.as_bytes()));
+        let is_fresh = http::fresh_cached_response(
+            &config.cache,
+            bucket,
+            RV_RELEASE_CACHE_KEY,
+            Some(CHECK_INTERVAL),
+        )
+        .is_some();
+
+        let refresh = if config.offline || is_fresh {
+            None
+        } else {
+            let client = config.http_client().ok()?.clone();
+            let cache = config.cache.clone();
+            let timeout = config.timeout.min(CHECK_TIMEOUT);
+            Some(tokio::spawn(async move {
+                fetch_latest_release(&client, &cache, &url, timeout).await
+            }))
+        };
+        Some(Self { cached, refresh })
+    }
+
+    /// Prints a one-line nudge to stderr if a newer rv has been released.
+    pub async fn finish(self) {
+        let refreshed = match self.refresh {
+            Some(refresh) if refresh.is_finished() => refresh.await.ok().flatten(),
+            Some(_) => {
+                debug!("Not waiting for the check for a new rv release.");
+                None
+            }
+            None => None,
+        };
+        let current = env!("CARGO_PKG_VERSION");
+        if let Some(latest) = refreshed.or(self.cached)
+            && is_newer(&latest, current)
+        {
+            eprintln!(
+                "{} rv {} is available, you have {}",
+                "note:".bold(),
+                latest.trim_start_matches('v').green(),
+                current
+            );
+        }
+    }
+}
+
+/// Fetches the tag of rv's latest release, caching the response.
+async fn fetch_latest_release(
+    client: &reqwest::Client,
+    cache: &rv_cache::Cache,
+    url: &str,
+    timeout: Duration,
+) -> Option<String> {
+    let policy = http::CachePolicy {
+        ttl: Some(CHECK_INTERVAL),
+        ..Default::default()
+    };
+    let body = match http::cached_get(
+        client,
+        cache,
+        rv_cache::CacheBucket::Ruby,
+        RV_RELEASE_CACHE_KEY,
+        url,
+        policy,
+        timeout,
+    )
+    .await
+    {
+        Ok((status, body)) if status.is_success() || status == StatusCode::NOT_MODIFIED => body,
+        Ok((status, _)) => {
+            debug!("Could not check for a new rv release, status: {status}");
+            return None;
+        }
+        Err(err) => {
+            debug!("Could not check for a new rv release: {err}");
+            return None;
+        }
+    };
+    release_tag(&body)
+}
+
+fn release_tag(body: &[u8]) -> Option<String> {
+    let release: LatestRelease = serde_json::from_slice(body).ok()?;
+    Some(release.tag_name)
+}
+
+/// Whether the release tagged `latest`, like `v0.2.0`, is newer than the `current` version.
+///
+/// Tags that aren't plain dotted numbers, like prereleases, never count as newer.
+fn is_newer(latest: &str, current: &str) -> bool {
+    fn parse(version: &str) -> Option<Vec<u64>> {
+        version
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect()
+    }
+
+    match (parse(latest), parse(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("v0.2.0", "0.1.0"));
+        assert!(is_newer("v0.1.10", "0.1.9"));
+        assert!(is_newer("1.0.0", "0.9.9"));
+        assert!(!is_newer("v0.1.0", "0.1.0"));
+        assert!(!is_newer("v0.0.9", "0.1.0"));
+        assert!(!is_newer("v0.2.0-rc1", "0.1.0"));
+        assert!(!is_newer("nightly", "0.1.0"));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::common::RvTest;
+
+#[test]
+fn test_cache_verify_reports_and_prunes_invalid_interpreters() {
+    let mut test = RvTest::new();
+    test.env.remove("RV_NO_CACHE");
+    let cache_dir = test.temp_dir.path().join("cache");
+    test.env
+        .insert("RV_CACHE_DIR".into(), cache_dir.as_str().into());
+
+    let kept = test.create_ruby_dir("ruby-3.3.5");
+    let removed = test.create_ruby_dir("ruby-3.4.1");
+    // Populate the interpreter cache.
+    test.ruby_list(&["--installed-only"]).assert_success();
+    assert!(kept.exists());
+    std::fs::remove_dir_all(&removed).unwrap();
+    let interpreters = cache_dir.join("ruby-v0").join("interpreters");
+    std::fs::write(interpreters.join("garbage"), "not json").unwrap();
+
+    let output = test.rv(&["cache", "verify"]);
+    output.assert_success();
+    let stdout = output.normalized_stdout();
+    assert!(
+        stdout.contains("/opt/rubies/ruby-3.4.1 no longer exists"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("garbage unreadable entry"), "{stdout}");
+    assert!(
+        stdout.ends_with("Checked 3 cached interpreters, found 2 invalid\n"),
+        "{stdout}"
+    );
+
+    let output = test.rv(&["cache", "verify", "--prune"]);
+    output.assert_success();
+    assert!(
+        output
+            .normalized_stdout()
+            .ends_with("Checked 3 cached interpreters, removed 2 invalid\n")
+    );
+    assert!(!interpreters.join("garbage").exists());
+
+    let output = test.rv(&["cache", "verify"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Checked 1 cached interpreters, found 0 invalid\n"
+    );
+}
+
+#[test]
+fn test_no_cache_leaves_cache_dir_untouched() {
+    let mut test = RvTest::new();
+    test.env.remove("RV_NO_CACHE");
+    let cache_dir = test.temp_dir.path().join("cache");
+    test.env
+        .insert("RV_CACHE_DIR".into(), cache_dir.as_str().into());
+    test.create_ruby_dir("ruby-3.3.5");
+
+    let output = test.rv(&["--no-cache", "ruby", "list", "--installed-only"]);
+    output.assert_success();
+    assert!(output.normalized_stdout().contains("ruby-3.3.5"));
+    assert!(!cache_dir.exists());
+
+    let output = test.rv(&["ruby", "list", "--installed-only"]);
+    output.assert_success();
+    assert!(cache_dir.join("ruby-v0").join("interpreters").exists());
+}
+
+#[test]
+fn test_cache_dir_json_lists_buckets() {
+    let mut test = RvTest::new();
+    test.env.remove("RV_NO_CACHE");
+    let cache_dir = test.temp_dir.path().join("cache");
+    test.env
+        .insert("RV_CACHE_DIR".into(), cache_dir.as_str().into());
+
+    let output = test.rv(&["cache", "dir"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "/cache\n");
+
+    let output = test.rv(&["cache", "dir", "--format", "json"]);
+    output.assert_success();
+    let dirs: serde_json::Value = serde_json::from_str(&output.normalized_stdout()).unwrap();
+    assert_eq!(
+        dirs,
+        serde_json::json!({
+            "cache_dir": "/cache",
+            "buckets": { "ruby": "/cache/ruby-v0" }
+        })
+    );
+}
@@ -0,0 +1,32 @@
+use crate::common::RvTest;
+
+#[test]
+fn test_color_never_strips_command_output() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join(".ruby-version"), "3.2.0\n").unwrap();
+    test.cwd = project_dir;
+    // Would force colors if `--color` didn't take precedence
+    test.env.insert("FORCE_COLOR".into(), "1".into());
+
+    for args in [
+        ["--color", "never", "ruby", "list"].as_slice(),
+        &["--color", "never", "ruby", "pin", "3.3.5"],
+        &["--color", "never", "ruby", "find"],
+    ] {
+        let output = test.rv(args);
+        output.assert_success();
+        let stdout = output.normalized_stdout();
+        assert!(!stdout.is_empty());
+        assert!(
+            !stdout.contains('\u{1b}'),
+            "{args:?} printed ANSI escapes: {stdout:?}"
+        );
+    }
+
+    let output = test.rv(&["ruby", "find"]);
+    output.assert_success();
+    assert!(output.normalized_stdout().contains('\u{1b}'));
+}
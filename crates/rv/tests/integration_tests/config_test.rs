@@ -0,0 +1,78 @@
+use crate::common::RvTest;
+
+fn write_config(test: &RvTest, path: &str, content: &str) -> camino::Utf8PathBuf {
+    let path = test.temp_dir.path().join(path);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_config_file_replaces_defaults() {
+    let test = RvTest::new();
+    write_config(
+        &test,
+        "tmp/home/.config/rv/config.toml",
+        "ruby-dirs = [\"~/rubies\"]\ncolor = \"always\"\n",
+    );
+
+    let output = test.rv(&["ruby", "dir"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "\u{1b}[36m/tmp/home/rubies\u{1b}[39m\n"
+    );
+
+    // Flags win over the config file.
+    let output = test.rv(&["--color", "never", "--ruby-dir", "/flag", "ruby", "dir"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "/flag\n");
+}
+
+#[test]
+fn test_env_wins_over_config_file() {
+    let mut test = RvTest::new();
+    write_config(
+        &test,
+        "tmp/home/.config/rv/config.toml",
+        "color = \"always\"\n",
+    );
+    test.env.insert("NO_COLOR".into(), "1".into());
+
+    let output = test.rv(&["ruby", "dir"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "/tmp/home/.local/share/rv/rubies\n"
+    );
+}
+
+#[test]
+fn test_config_flag_loads_file() {
+    let test = RvTest::new();
+    let path = write_config(&test, "rv-config.toml", "ruby-dirs = [\"/from-flag\"]\n");
+
+    let output = test.rv(&["--config", path.as_str(), "ruby", "dir"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "/from-flag\n");
+
+    // Unlike the default file, a file given with `--config` has to exist.
+    let missing = test.temp_dir.path().join("missing.toml");
+    let output = test.rv(&["--config", missing.as_str(), "ruby", "dir"]);
+    assert_eq!(output.output.status.code(), Some(3));
+    assert!(output.stderr().contains("UnreadableGlobalConfig"));
+}
+
+#[test]
+fn test_invalid_config_file() {
+    let test = RvTest::new();
+    write_config(
+        &test,
+        "tmp/home/.config/rv/config.toml",
+        "timeout = \"soon\"\n",
+    );
+
+    let output = test.rv(&["ruby", "dir"]);
+    assert_eq!(output.output.status.code(), Some(3));
+    assert!(output.stderr().contains("InvalidGlobalConfig"));
+}
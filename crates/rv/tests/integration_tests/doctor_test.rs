@@ -0,0 +1,47 @@
+use crate::common::RvTest;
+
+#[test]
+fn test_doctor_passes_with_working_setup() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.env.insert("PATH".into(), "/tmp/bin".into());
+    test.env
+        .insert("RUBY_ROOT".into(), "/opt/rubies/ruby-3.3.5".into());
+
+    let output = test.rv(&["doctor"]);
+    output.assert_success();
+
+    let stdout = output.normalized_stdout();
+    assert!(stdout.contains("[pass] Ruby directory /opt/rubies is readable"));
+    assert!(stdout.contains("[pass] Shell integration appears to be installed"));
+    assert!(stdout.contains("[pass] /tmp/bin/rv is on your PATH"));
+    assert!(!stdout.contains("[fail]"));
+}
+
+#[test]
+fn test_doctor_warns_about_missing_setup() {
+    let test = RvTest::new();
+
+    let output = test.rv(&["doctor"]);
+    output.assert_success();
+
+    let stdout = output.normalized_stdout();
+    assert!(stdout.contains("[warn] None of the Ruby directories exist yet"));
+    assert!(stdout.contains("[warn] Shell integration does not appear to be installed"));
+    assert!(stdout.contains("[warn] /tmp/bin/rv is not on your PATH"));
+}
+
+#[test]
+fn test_doctor_fails_on_unreadable_ruby_dir() {
+    let test = RvTest::new();
+    std::fs::write(test.temp_dir.path().join("not-a-dir"), "").unwrap();
+
+    let output = test.rv(&["--ruby-dir", "not-a-dir", "doctor"]);
+    output.assert_failure();
+
+    assert!(output.normalized_stdout().contains("[fail] Ruby directory"));
+    assert_eq!(
+        output.normalized_stderr(),
+        "Error: DoctorError(ChecksFailed(1))\n"
+    );
+}
@@ -0,0 +1,29 @@
+use crate::common::RvTest;
+
+#[test]
+fn test_json_log_format() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+
+    let output = test.rv(&["--log-format", "json", "-v", "ruby", "find", "3.3"]);
+    output.assert_success();
+
+    let stderr = output.normalized_stderr();
+    let lines: Vec<serde_json::Value> = stderr
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each log line should be JSON"))
+        .collect();
+    assert!(!lines.is_empty(), "verbose output should log something");
+    assert!(lines.iter().all(|line| line["level"] == "DEBUG"));
+}
+
+#[test]
+fn test_json_log_format_respects_verbosity() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.env.insert("RV_LOG_FORMAT".into(), "json".into());
+
+    let output = test.rv(&["ruby", "find", "3.3"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stderr(), "");
+}
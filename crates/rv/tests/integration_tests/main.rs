@@ -1,3 +1,8 @@
+mod cache_test;
+mod color_test;
 mod common;
+mod config_test;
+mod doctor_test;
+mod log_format_test;
 mod ruby;
 mod shell;
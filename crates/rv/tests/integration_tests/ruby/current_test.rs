@@ -0,0 +1,51 @@
+use crate::common::{RvOutput, RvTest};
+
+impl RvTest {
+    pub fn ruby_current(&self, args: &[&str]) -> RvOutput {
+        let mut cmd = self.rv_command();
+        cmd.args(["ruby", "current"]);
+        cmd.args(args);
+
+        let output = cmd.output().expect("Failed to execute rv command");
+        RvOutput::new(self.temp_dir.path().as_str(), output)
+    }
+}
+
+#[test]
+fn test_ruby_current_prints_active_ruby() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.2.0");
+    test.create_ruby_dir("ruby-3.3.0");
+
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join(".ruby-version"), "3.2").unwrap();
+    test.cwd = project_dir;
+
+    let output = test.ruby_current(&[]);
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "ruby-3.2.0\n");
+}
+
+#[test]
+fn test_ruby_current_json_output() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.0");
+
+    let output = test.ruby_current(&["--format", "json"]);
+    output.assert_success();
+
+    let ruby: serde_json::Value =
+        serde_json::from_str(&output.normalized_stdout()).expect("Output should be valid JSON");
+    assert_eq!(ruby["version"], "ruby-3.3.0");
+    assert_eq!(ruby["path"], "/opt/rubies/ruby-3.3.0");
+}
+
+#[test]
+fn test_ruby_current_without_active_ruby_fails() {
+    let test = RvTest::new();
+
+    let output = test.ruby_current(&[]);
+    output.assert_failure();
+    assert_eq!(output.normalized_stdout(), "");
+}
@@ -0,0 +1,66 @@
+use crate::common::RvTest;
+
+#[test]
+fn test_ruby_dir_defaults_to_local_share() {
+    let test = RvTest::new();
+    let output = test.rv(&["ruby", "dir"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "/tmp/home/.local/share/rv/rubies\n"
+    );
+}
+
+#[test]
+fn test_ruby_dir_respects_xdg_data_home() {
+    let mut test = RvTest::new();
+    test.env.insert("XDG_DATA_HOME".into(), "/tmp/xdg".into());
+    let output = test.rv(&["ruby", "dir"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "/tmp/xdg/rv/rubies\n");
+
+    // Relative paths aren't valid XDG base directories.
+    test.env.insert("XDG_DATA_HOME".into(), "xdg".into());
+    let output = test.rv(&["ruby", "dir"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "/tmp/home/.local/share/rv/rubies\n"
+    );
+}
+
+#[test]
+fn test_ruby_dirs_in_home_are_under_root() {
+    let mut test = RvTest::new();
+    test.env.insert("RV_HOME".into(), "/sandbox/home".into());
+    let output = test.rv(&["ruby", "dir"]);
+    output.assert_success();
+    assert_eq!(
+        output.stdout(),
+        format!(
+            "{}/sandbox/home/.local/share/rv/rubies\n",
+            test.temp_dir.path()
+        )
+    );
+
+    // The chruby directory in the sandboxed home is searched, and `~` expands to it too.
+    let rubies_dir = test.temp_dir.path().join("sandbox/home/.rubies");
+    std::fs::create_dir_all(&rubies_dir).unwrap();
+    std::fs::rename(
+        test.create_ruby_dir("ruby-3.3.5"),
+        rubies_dir.join("ruby-3.3.5"),
+    )
+    .unwrap();
+    let output = test.rv(&["ruby", "find", "3.3.5"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "/sandbox/home/.rubies/ruby-3.3.5/bin/ruby\n"
+    );
+    let output = test.rv(&["--ruby-dir", "~/.rubies", "ruby", "find", "3.3.5"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "/sandbox/home/.rubies/ruby-3.3.5/bin/ruby\n"
+    );
+}
@@ -27,13 +27,39 @@ fn test_ruby_find_no_matching_rubies() {
     let test = RvTest::new();
     test.create_ruby_dir("ruby-3.3.5");
     let find = test.ruby_find(&["3.4.5"]);
-    assert!(!find.success());
+    assert_eq!(find.output.status.code(), Some(5));
     assert_eq!(
         find.normalized_stderr(),
         "Error: FindError(NoMatchingRuby)\n"
     );
 }
 
+#[test]
+fn test_ruby_find_not_found_differs_from_usage_error() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+
+    let not_found = test.ruby_find(&["3.4.5"]);
+    let usage_error = test.ruby_find(&["--no-such-flag", "3.3.5"]);
+    assert_eq!(usage_error.output.status.code(), Some(2));
+    assert_ne!(
+        not_found.output.status.code(),
+        usage_error.output.status.code()
+    );
+}
+
+#[test]
+fn test_ruby_find_config_errors_exit_with_3() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    // A version file without a request in it.
+    std::fs::write(test.cwd.join(".ruby-version"), "# no version\n").unwrap();
+
+    let find = test.ruby_find(&[]);
+    assert_eq!(find.output.status.code(), Some(3));
+    assert!(find.normalized_stderr().contains("ConfigError"));
+}
+
 #[test]
 fn test_ruby_find_matching_request() {
     let test = RvTest::new();
@@ -67,8 +93,9 @@ fn test_ruby_find_dot_ruby_version_empty() {
     let find = test.ruby_find(&[]);
     find.assert_failure();
     assert_eq!(
-        find.normalized_stderr(),
-        "Error: FindError(ConfigError(RequestError(EmptyInput)))\n"
+        find.normalized_stderr()
+            .replace(test.temp_dir.path().as_str(), ""),
+        "Error: FindError(ConfigError(NoRequest { path: \"/.ruby-version\" }))\n"
     );
 }
 
@@ -124,3 +151,231 @@ fn test_ruby_find_matching_jruby() {
         "/opt/rubies/jruby-9.4.8.0/bin/ruby\n"
     );
 }
+
+#[test]
+fn test_ruby_find_picks_highest_matching_patch() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.10");
+    test.create_ruby_dir("ruby-3.3.9");
+    test.create_ruby_dir("ruby-3.3.2");
+    test.create_ruby_dir("ruby-3.4.1");
+    let find = test.ruby_find(&["3.3"]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.10/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_ruby_find_with_added_ruby_dir() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let extra_dir = test.temp_dir.path().join("extra");
+    std::fs::create_dir_all(&extra_dir).unwrap();
+    std::fs::rename(
+        test.create_ruby_dir("ruby-3.4.1"),
+        extra_dir.join("ruby-3.4.1"),
+    )
+    .unwrap();
+
+    let find = test.ruby_find(&["3.4"]);
+    assert!(!find.success());
+
+    // Added directories are searched on top of the defaults...
+    let find = test.rv(&["--add-ruby-dir", "extra", "ruby", "find", "3.3"]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+    let find = test.rv(&["--add-ruby-dir", "extra", "ruby", "find", "3.4"]);
+    find.assert_success();
+    assert_eq!(find.normalized_stdout(), "/extra/ruby-3.4.1/bin/ruby\n");
+
+    // ...while `--ruby-dir` still replaces them.
+    let find = test.rv(&["--ruby-dir", "extra", "ruby", "find", "3.3"]);
+    assert!(!find.success());
+}
+
+#[test]
+fn test_ruby_find_expands_ruby_dirs() {
+    let mut test = RvTest::new();
+    let home_rubies = test.temp_dir.path().join("tmp/home/my-rubies");
+    std::fs::create_dir_all(&home_rubies).unwrap();
+    std::fs::rename(
+        test.create_ruby_dir("ruby-3.3.5"),
+        home_rubies.join("ruby-3.3.5"),
+    )
+    .unwrap();
+
+    let find = test.rv(&["--ruby-dir", "~/my-rubies", "ruby", "find", "3.3"]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/tmp/home/my-rubies/ruby-3.3.5/bin/ruby\n"
+    );
+
+    test.env
+        .insert("MY_RUBIES".into(), "/tmp/home/my-rubies".into());
+    let find = test.rv(&["--add-ruby-dir", "$MY_RUBIES", "ruby", "find", "3.3"]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/tmp/home/my-rubies/ruby-3.3.5/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_ruby_find_stops_at_git_when_requested() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.1");
+    std::fs::write(test.temp_dir.path().join(".ruby-version"), "3.3.5\n").unwrap();
+    let repo = test.temp_dir.path().join("repo");
+    std::fs::create_dir_all(repo.join(".git")).unwrap();
+    std::fs::create_dir_all(repo.join("lib")).unwrap();
+    test.cwd = repo.join("lib");
+
+    let find = test.ruby_find(&[]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+
+    // The pin above the repository is ignored, so the default request picks the latest Ruby.
+    test.env.insert("RV_STOP_AT_GIT".into(), "1".into());
+    let find = test.ruby_find(&[]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.4.1/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_ruby_find_explain() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.1");
+    test.create_ruby_dir("jruby-9.4.12.0");
+
+    let find = test.ruby_find(&["--explain", "3.4"]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.4.1/bin/ruby\n"
+    );
+    insta::assert_snapshot!(find.normalized_stderr(), @r"
+    Matching ruby-3.4 against installed rubies:
+      ruby-3.3.5 no (minor 3 != 4)
+      ruby-3.4.1 yes
+      jruby-9.4.12.0 no (engine mismatch, jruby != ruby)
+    ");
+}
+
+#[test]
+fn test_ruby_find_explain_without_match() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+
+    let find = test.ruby_find(&["--explain", "3.3.6"]);
+    assert_eq!(find.output.status.code(), Some(5));
+    assert!(
+        find.normalized_stderr()
+            .contains("ruby-3.3.5 no (patch 5 != 6)")
+    );
+}
+
+#[test]
+fn test_ruby_find_no_project_ignores_dot_ruby_version() {
+    let test = RvTest::new();
+    std::fs::write(test.temp_dir.path().join(".ruby-version"), "3.3.5\n").unwrap();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.5");
+
+    let find = test.rv(&["--no-project", "ruby", "find"]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.4.5/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_ruby_find_all() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.3.10");
+    test.create_ruby_dir("ruby-3.4.1");
+
+    let find = test.ruby_find(&["3.3", "--all"]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.10/bin/ruby\n/opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+
+    let find = test.ruby_find(&["3.3", "--all", "--format", "json"]);
+    find.assert_success();
+    let rubies: serde_json::Value = serde_json::from_str(&find.normalized_stdout()).unwrap();
+    let paths: Vec<_> = rubies
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|ruby| ruby["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, ["/opt/rubies/ruby-3.3.10", "/opt/rubies/ruby-3.3.5"]);
+}
+
+#[test]
+fn test_ruby_find_all_without_match() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.4.1");
+
+    let find = test.ruby_find(&["3.3", "--all", "--format", "json"]);
+    assert_eq!(find.output.status.code(), Some(5));
+    // In JSON mode the error goes to stdout as JSON, instead of a report on stderr.
+    assert_eq!(
+        find.normalized_stdout(),
+        "{\"error\":{\"kind\":\"not_found\",\"message\":\"no matching ruby version found\"}}\n"
+    );
+    assert_eq!(find.normalized_stderr(), "");
+}
+
+#[test]
+fn test_ruby_find_chruby_compat_rubies() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let elsewhere = test.temp_dir.path().join("elsewhere");
+    std::fs::create_dir_all(&elsewhere).unwrap();
+    std::fs::rename(
+        test.create_ruby_dir("ruby-3.4.1"),
+        elsewhere.join("ruby-3.4.1"),
+    )
+    .unwrap();
+    test.env.insert(
+        "RUBIES".into(),
+        "/elsewhere/ruby-3.4.1:/opt/rubies/ruby-3.3.5".into(),
+    );
+
+    // `RUBIES` is only read in chruby compatibility mode.
+    let output = test.rv(&["ruby", "find", "3.4"]);
+    assert_eq!(output.output.status.code(), Some(5));
+
+    let output = test.rv(&["--chruby-compat", "ruby", "find", "3.4"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "/elsewhere/ruby-3.4.1/bin/ruby\n"
+    );
+
+    // A Ruby that is also in a Ruby directory is only found once.
+    let output = test.rv(&["--chruby-compat", "ruby", "find", "--all"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "/elsewhere/ruby-3.4.1/bin/ruby\n/opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+}
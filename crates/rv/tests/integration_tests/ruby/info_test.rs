@@ -0,0 +1,46 @@
+use crate::common::RvTest;
+
+#[test]
+fn test_ruby_info_reports_project_ruby() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.2.0");
+    test.create_ruby_dir("ruby-3.3.0");
+
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join(".ruby-version"), "3.2").unwrap();
+    test.cwd = project_dir;
+
+    let output = test.rv(&["ruby", "info"]);
+    output.assert_success();
+    insta::assert_snapshot!(output.normalized_stdout());
+
+    let output = test.rv(&["ruby", "info", "3.3"]);
+    output.assert_success();
+    assert!(output.normalized_stdout().contains("Version:    3.3.0\n"));
+    assert!(output.normalized_stdout().ends_with("Active:     no\n"));
+}
+
+#[test]
+fn test_ruby_info_json_output() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.0");
+
+    let output = test.rv(&["ruby", "info", "3.3", "--format", "json"]);
+    output.assert_success();
+
+    let ruby: serde_json::Value =
+        serde_json::from_str(&output.normalized_stdout()).expect("Output should be valid JSON");
+    assert_eq!(ruby["version"], "ruby-3.3.0");
+    assert_eq!(ruby["path"], "/opt/rubies/ruby-3.3.0");
+}
+
+#[test]
+fn test_ruby_info_without_match_fails() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.0");
+
+    let output = test.rv(&["ruby", "info", "3.4"]);
+    assert_eq!(output.output.status.code(), Some(5));
+    assert_eq!(output.stdout(), "");
+}
@@ -56,6 +56,190 @@ fn test_ruby_install_successful_download() {
     );
 }
 
+/// A minisign public key, and a legacy minisign signature of `content` made with it.
+fn minisign(content: &[u8]) -> (String, String) {
+    use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+    use ring::signature::{Ed25519KeyPair, KeyPair as _};
+
+    let key_pair = Ed25519KeyPair::from_seed_unchecked(&[7; 32]).unwrap();
+    let key_id = [1, 2, 3, 4, 5, 6, 7, 8];
+    let public_key = [&b"Ed"[..], &key_id, key_pair.public_key().as_ref()].concat();
+
+    let signature = key_pair.sign(content);
+    let comment = "timestamp:1700000000";
+    let global = key_pair.sign(&[signature.as_ref(), comment.as_bytes()].concat());
+    let minisig = format!(
+        "untrusted comment: signature\n{}\ntrusted comment: {comment}\n{}\n",
+        BASE64.encode([&b"Ed"[..], &key_id, signature.as_ref()].concat()),
+        BASE64.encode(global.as_ref())
+    );
+    (BASE64.encode(public_key), minisig)
+}
+
+#[test]
+fn test_ruby_install_verifies_signature() {
+    let mut test = RvTest::new();
+
+    let tarball_content = create_mock_tarball();
+    let (public_key, signature) = minisign(&tarball_content);
+    let download_suffix = make_dl_suffix("3.4.5");
+    let _mock = test
+        .mock_tarball_download(&download_suffix, &tarball_content)
+        .create();
+    let signature_mock = test
+        .mock_tarball_download(&format!("{download_suffix}.minisig"), signature.as_bytes())
+        .create();
+    test.env.insert("RV_SIGNATURE_KEY".into(), public_key);
+
+    let output = test.rv(&["ruby", "install", "--verify-signature", "3.4.5"]);
+
+    output.assert_success();
+    signature_mock.assert();
+    assert!(
+        test.temp_dir
+            .path()
+            .join("tmp/home/.local/share/rv/rubies/portable-ruby/bin/ruby")
+            .exists()
+    );
+}
+
+#[test]
+fn test_ruby_install_bad_signature_aborts_before_extracting() {
+    let mut test = RvTest::new();
+
+    let tarball_content = create_mock_tarball();
+    let (public_key, signature) = minisign(b"something else");
+    let download_suffix = make_dl_suffix("3.4.5");
+    let _mock = test
+        .mock_tarball_download(&download_suffix, &tarball_content)
+        .create();
+    let _signature_mock = test
+        .mock_tarball_download(&format!("{download_suffix}.minisig"), signature.as_bytes())
+        .create();
+    test.env.insert("RV_SIGNATURE_KEY".into(), public_key);
+
+    let output = test.rv(&["ruby", "install", "--verify-signature", "3.4.5"]);
+
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("BadSignature"));
+    assert!(
+        !test
+            .temp_dir
+            .path()
+            .join("tmp/home/.local/share/rv/rubies/portable-ruby")
+            .exists()
+    );
+}
+
+#[test]
+fn test_ruby_install_verify_signature_needs_key() {
+    let test = RvTest::new();
+    let output = test.rv(&["ruby", "install", "--verify-signature", "3.4.5"]);
+
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("--signature-key <KEY>"));
+}
+
+#[test]
+fn test_ruby_install_logs_progress_without_progress_bar() {
+    let mut test = RvTest::new();
+
+    let tarball_content = create_mock_tarball();
+    let download_suffix = make_dl_suffix("3.4.5");
+    let _mock = test
+        .mock_tarball_download(&download_suffix, &tarball_content)
+        .create();
+
+    let output = test.rv(&["-v", "ruby", "install", "3.4.5"]);
+    output.assert_success();
+
+    let stderr = output.normalized_stderr();
+    let total = bytesize::ByteSize::b(tarball_content.len() as u64)
+        .display()
+        .iec_short();
+    assert!(
+        stderr.contains(&format!("Downloaded {total} of {total}")),
+        "stderr should log download progress, got: {stderr}"
+    );
+    assert!(stderr.contains("Extracted "), "stderr: {stderr}");
+}
+
+#[test]
+fn test_ruby_install_from_download_mirror() {
+    let mut test = RvTest::new();
+
+    let tarball_content = create_mock_tarball();
+    let download_suffix = make_dl_suffix("3.4.5");
+    let mock = test
+        .mock_tarball_download(&download_suffix, &tarball_content)
+        .create();
+
+    // Without RV_RELEASES_URL, downloads would go to GitHub, so the mirror must be used.
+    test.env.remove("RV_RELEASES_URL");
+    let mirror = test.server_url();
+    test.env.insert("RV_DOWNLOAD_MIRROR".into(), mirror.clone());
+
+    let output = test.rv(&["ruby", "install", "3.4.5"]);
+    output.assert_success();
+    mock.assert();
+    assert!(
+        output
+            .stdout()
+            .contains(&format!("Downloaded {mirror}/{download_suffix}"))
+    );
+}
+
+#[test]
+fn test_ruby_install_for_another_platform() {
+    let mut test = RvTest::new();
+    let platform = if arch() == "x86_64_linux" {
+        "arm64_linux"
+    } else {
+        "x86_64_linux"
+    };
+
+    let tarball_content = create_mock_tarball_of("rv-ruby@3.4.5/3.4.5", "#!/bin/bash\n");
+    let mock = test
+        .mock_tarball_download(
+            &format!("latest/download/ruby-3.4.5.{platform}.tar.gz"),
+            &tarball_content,
+        )
+        .create();
+
+    let output = test.rv(&["ruby", "install", "3.4.5", "--platform", platform]);
+    output.assert_success();
+    mock.assert();
+    assert!(output.stderr().contains("won't run on this host"));
+
+    let root = test.temp_dir.path();
+    let rubies_dir = root.join(format!("tmp/home/.local/share/rv/rubies-{platform}"));
+    assert!(rubies_dir.join("ruby-3.4.5/bin/ruby").exists());
+    assert!(
+        !root
+            .join("tmp/home/.local/share/rv/rubies/ruby-3.4.5")
+            .exists()
+    );
+
+    let output = test.rv(&["ruby", "find", "3.4.5"]);
+    output.assert_failure();
+}
+
+#[test]
+fn test_ruby_install_platform_conflicts_with_tarballs() {
+    let test = RvTest::new();
+    let output = test.rv(&[
+        "ruby",
+        "install",
+        "3.4.5",
+        "--platform",
+        "arm64_linux",
+        "--tarball-path",
+        "ruby.tar.gz",
+    ]);
+    output.assert_failure();
+    assert!(output.stderr().contains("cannot be used with"));
+}
+
 #[test]
 fn test_ruby_install_from_tarball() {
     let mut test = RvTest::new();
@@ -73,11 +257,170 @@ fn test_ruby_install_from_tarball() {
     let mocked_ruby_path = test
         .temp_dir
         .path()
-        .join("tmp/home/.data/rv/rubies/portable-ruby/bin/ruby");
+        .join("tmp/home/.local/share/rv/rubies/portable-ruby/bin/ruby");
     let mut command = Command::new(mocked_ruby_path);
     command.output().expect("mock ruby");
 }
 
+#[test]
+fn test_ruby_install_from_file_infers_version() {
+    let mut test = RvTest::new();
+    let tarball_file =
+        test.mock_tarball_on_disk("ruby-3.4.5.arm64_linux.tar.gz", &create_mock_tarball());
+
+    let output = test.rv(&["ruby", "install", "--from-file", tarball_file.as_str()]);
+
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Installed Ruby version ruby-3.4.5 to /tmp/home/.local/share/rv/rubies\n"
+    );
+    let ruby_dir = test
+        .temp_dir
+        .path()
+        .join("tmp/home/.local/share/rv/rubies/portable-ruby");
+    assert!(ruby_dir.join("bin/ruby").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_ruby_install_alias() {
+    let mut test = RvTest::new();
+    let rubies_dir = test.temp_dir.path().join("opt/rubies");
+    let mock_ruby =
+        "#!/bin/bash\nprintf 'ruby\\n{version}\\naarch64-darwin23\\naarch64\\ndarwin23\\n\\n'\n";
+    for version in ["3.4.4", "3.4.5"] {
+        let tarball = create_mock_tarball_of(
+            &format!("rv-ruby@{version}/{version}"),
+            &mock_ruby.replace("{version}", version),
+        );
+        let tarball_file =
+            test.mock_tarball_on_disk(&format!("ruby-{version}.arm64_linux.tar.gz"), &tarball);
+        let output = test.rv(&[
+            "ruby",
+            "install",
+            "--install-dir",
+            rubies_dir.as_str(),
+            "--from-file",
+            tarball_file.as_str(),
+            "--alias",
+            "current",
+        ]);
+        output.assert_success();
+        assert!(
+            output
+                .normalized_stdout()
+                .ends_with(&format!("Aliased current to ruby-{version}\n"))
+        );
+    }
+
+    // Installing again moves the alias along.
+    assert_eq!(
+        fs::read_link(rubies_dir.join("current")).unwrap(),
+        std::path::Path::new("ruby-3.4.5")
+    );
+    assert!(rubies_dir.join("current/bin/ruby").exists());
+
+    // The alias doesn't show up as another install.
+    let output = test.rv(&["ruby", "list", "--installed-only"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "  ruby-3.4.4 [installed] /opt/rubies/ruby-3.4.4/bin/ruby\n\
+         * ruby-3.4.5 [installed] /opt/rubies/ruby-3.4.5/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_ruby_install_alias_must_be_a_name() {
+    let mut test = RvTest::new();
+    let tarball_file =
+        test.mock_tarball_on_disk("ruby-3.4.5.arm64_linux.tar.gz", &create_mock_tarball());
+
+    let output = test.rv(&[
+        "ruby",
+        "install",
+        "--from-file",
+        tarball_file.as_str(),
+        "--alias",
+        "../current",
+    ]);
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("InvalidAlias"));
+}
+
+#[test]
+fn test_ruby_install_alias_keeps_real_directories() {
+    let mut test = RvTest::new();
+    let rubies_dir = test.temp_dir.path().join("opt/rubies");
+    fs::create_dir_all(rubies_dir.join("current")).unwrap();
+    let tarball_file =
+        test.mock_tarball_on_disk("ruby-3.4.5.arm64_linux.tar.gz", &create_mock_tarball());
+
+    let output = test.rv(&[
+        "ruby",
+        "install",
+        "--install-dir",
+        rubies_dir.as_str(),
+        "--from-file",
+        tarball_file.as_str(),
+        "--alias",
+        "current",
+    ]);
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("AliasExists"));
+    assert!(rubies_dir.join("current").is_dir());
+}
+
+#[test]
+fn test_ruby_install_from_file_checks_sidecar_checksum() {
+    use sha2::{Digest, Sha256};
+
+    let mut test = RvTest::new();
+    let tarball_content = create_mock_tarball();
+    let tarball_file = test.mock_tarball_on_disk("ruby-3.4.5.arm64_linux.tar.gz", &tarball_content);
+    let sidecar = format!("{tarball_file}.sha256");
+    let ruby_dir = test
+        .temp_dir
+        .path()
+        .join("tmp/home/.local/share/rv/rubies/portable-ruby");
+
+    fs::write(&sidecar, format!("{}  ruby.tar.gz\n", "0".repeat(64))).unwrap();
+    let output = test.rv(&["ruby", "install", "--from-file", tarball_file.as_str()]);
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("ChecksumMismatch"));
+    assert!(!ruby_dir.exists());
+
+    let digest = format!("{:x}", Sha256::digest(&tarball_content));
+    fs::write(
+        &sidecar,
+        format!("{digest}  ruby-3.4.5.arm64_linux.tar.gz\n"),
+    )
+    .unwrap();
+    let output = test.rv(&["ruby", "install", "--from-file", tarball_file.as_str()]);
+    output.assert_success();
+    assert!(ruby_dir.join("bin/ruby").exists());
+}
+
+#[test]
+fn test_ruby_install_from_file_needs_version_in_name() {
+    let mut test = RvTest::new();
+    let tarball_file = test.mock_tarball_on_disk("ruby.tar.gz", &create_mock_tarball());
+
+    let output = test.rv(&["ruby", "install", "--from-file", tarball_file.as_str()]);
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("UnknownTarballVersion"));
+
+    let output = test.rv(&[
+        "ruby",
+        "install",
+        "--from-file",
+        tarball_file.as_str(),
+        "3.4.5",
+    ]);
+    output.assert_success();
+}
+
 #[test]
 fn test_ruby_install_http_failure_no_empty_file() {
     let mut test = RvTest::new();
@@ -225,7 +568,7 @@ fn test_ruby_install_cached_file_reused() {
     let output1 = test.rv(&["ruby", "install", "3.4.5"]);
     output1.assert_success();
 
-    let output2 = test.rv(&["ruby", "install", "3.4.5"]);
+    let output2 = test.rv(&["ruby", "install", "--force", "3.4.5"]);
     output2.assert_success();
 
     assert!(
@@ -237,6 +580,37 @@ fn test_ruby_install_cached_file_reused() {
     mock.assert();
 }
 
+#[test]
+fn test_ruby_install_offline_uses_cache_only() {
+    let mut test = RvTest::new();
+
+    let tarball_content = create_mock_tarball();
+    let download_suffix = make_dl_suffix("3.4.5");
+    let mock = test
+        .mock_tarball_download(&download_suffix, &tarball_content)
+        .expect(1)
+        .create();
+
+    test.env.remove("RV_NO_CACHE");
+    let cache_dir = test.temp_dir.path().join("cache");
+    test.env
+        .insert("RV_CACHE_DIR".into(), cache_dir.as_str().into());
+
+    // Nothing is cached yet, so installing offline fails without a request.
+    let output = test.rv(&["--offline", "ruby", "install", "3.4.5"]);
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("Offline(\""));
+
+    let output = test.rv(&["ruby", "install", "3.4.5"]);
+    output.assert_success();
+
+    test.env.insert("RV_OFFLINE".into(), "1".into());
+    let output = test.rv(&["ruby", "install", "--force", "3.4.5"]);
+    output.assert_success();
+
+    mock.assert();
+}
+
 #[test]
 fn test_ruby_install_invalid_url() {
     let mut test = RvTest::new();
@@ -265,7 +639,169 @@ fn test_ruby_install_invalid_url() {
     }
 }
 
-fn make_dl_suffix(version: &str) -> String {
+#[test]
+fn test_ruby_install_corrupt_archive_leaves_nothing_behind() {
+    let mut test = RvTest::new();
+
+    // Cut the archive off halfway, after the first entries but before the end.
+    let mut tarball_content = create_mock_tarball();
+    tarball_content.truncate(tarball_content.len() / 2);
+    let filename = make_tarball_file_name("3.4.5");
+    let tarball_file = test.mock_tarball_on_disk(&filename, &tarball_content);
+
+    let output = test.rv(&[
+        "ruby",
+        "install",
+        "--tarball-path",
+        tarball_file.as_str(),
+        "3.4.5",
+    ]);
+    output.assert_failure();
+
+    let rubies_dir = test.temp_dir.path().join("tmp/home/.local/share/rv/rubies");
+    let leftovers: Vec<_> = fs::read_dir(&rubies_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert!(
+        leftovers.is_empty(),
+        "No partial install should remain, found {leftovers:?}"
+    );
+}
+
+#[test]
+fn test_ruby_install_requires_force_to_overwrite() {
+    let mut test = RvTest::new();
+
+    let tarball_content = create_mock_tarball();
+    let filename = make_tarball_file_name("3.4.5");
+    let tarball_file = test.mock_tarball_on_disk(&filename, &tarball_content);
+    let install = |args: &[&str]| {
+        let mut all_args = vec!["ruby", "install", "--tarball-path", tarball_file.as_str()];
+        all_args.extend(args);
+        all_args.push("3.4.5");
+        test.rv(&all_args)
+    };
+
+    install(&[]).assert_success();
+
+    let ruby_dir = test
+        .temp_dir
+        .path()
+        .join("tmp/home/.local/share/rv/rubies/portable-ruby");
+    fs::write(ruby_dir.join("marker"), "").unwrap();
+
+    let output = install(&[]);
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("AlreadyInstalled"));
+    assert!(ruby_dir.join("marker").exists());
+
+    install(&["--force"]).assert_success();
+    assert!(ruby_dir.join("bin/ruby").exists());
+    assert!(!ruby_dir.join("marker").exists());
+}
+
+#[test]
+fn test_ruby_install_partial_version_installs_latest_patch() {
+    let mut test = RvTest::new();
+
+    // RvTest pretends to be on arm64 macOS when listing releases.
+    let assets: Vec<String> = ["3.3.4", "3.3.5", "3.4.1"]
+        .iter()
+        .map(|version| {
+            format!(
+                r#"{{"name": "ruby-{version}.arm64_sonoma.tar.gz", "browser_download_url": "http://.../{version}"}}"#
+            )
+        })
+        .collect();
+    let releases = format!(r#"{{"name": "latest", "assets": [{}]}}"#, assets.join(", "));
+    let _releases_mock = test.mock_releases(&releases);
+
+    let tarball_content = create_mock_tarball();
+    let download_suffix = make_dl_suffix("3.3.5");
+    let download_mock = test
+        .mock_tarball_download(&download_suffix, &tarball_content)
+        .create();
+
+    let output = test.rv(&["ruby", "install", "3.3"]);
+    output.assert_success();
+    download_mock.assert();
+    assert!(
+        output
+            .stdout()
+            .contains("Installed Ruby version ruby-3.3.5")
+    );
+}
+
+#[test]
+fn test_ruby_install_partial_version_skips_prereleases() {
+    let mut test = RvTest::new();
+
+    let assets: Vec<String> = ["3.3.5", "3.4.0-preview1"]
+        .iter()
+        .map(|version| {
+            format!(
+                r#"{{"name": "ruby-{version}.arm64_sonoma.tar.gz", "browser_download_url": "http://.../{version}"}}"#
+            )
+        })
+        .collect();
+    let releases = format!(r#"{{"name": "latest", "assets": [{}]}}"#, assets.join(", "));
+    let _releases_mock = test.mock_releases(&releases);
+
+    let tarball_content = create_mock_tarball();
+    let download_mock = test
+        .mock_tarball_download(&make_dl_suffix("3.3.5"), &tarball_content)
+        .create();
+
+    let output = test.rv(&["ruby", "install", "3"]);
+    output.assert_success();
+    download_mock.assert();
+    assert!(
+        output
+            .stdout()
+            .contains("Installed Ruby version ruby-3.3.5")
+    );
+
+    let output = test.rv(&["ruby", "install", "3.4"]);
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("NoMatchingRelease"));
+}
+
+#[test]
+fn test_ruby_install_partial_version_without_match() {
+    let mut test = RvTest::new();
+
+    let _releases_mock = test.mock_releases(
+        r#"{"name": "latest", "assets": [
+            {"name": "ruby-3.3.5.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.3.5"},
+            {"name": "ruby-3.4.1.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.4.1"}
+        ]}"#,
+    );
+
+    let output = test.rv(&["ruby", "install", "3.9"]);
+    output.assert_failure();
+    let stderr = output.normalized_stderr();
+    assert!(stderr.contains("NoMatchingRelease"), "stderr: {stderr}");
+    assert!(
+        stderr.contains(r#"nearest: "ruby-3.4.1, ruby-3.3.5""#),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_ruby_install_other_engines_is_an_error() {
+    let test = RvTest::new();
+
+    for version in ["jruby-9.4.12.0", "jruby-9.4"] {
+        let output = test.rv(&["ruby", "install", version]);
+        output.assert_failure();
+        let stderr = output.normalized_stderr();
+        assert!(stderr.contains("UnsupportedEngine"), "stderr: {stderr}");
+        assert!(!stderr.contains("panicked"), "stderr: {stderr}");
+    }
+}
+
+pub(super) fn make_dl_suffix(version: &str) -> String {
     let filename = make_tarball_file_name(version);
     format!("latest/download/{filename}")
 }
@@ -395,7 +931,12 @@ fn test_ruby_install_temp_file_cleanup_on_extraction_failure() {
     assert!(!temp_path.exists(), "Temp file should be cleaned up");
 }
 
-fn create_mock_tarball() -> Vec<u8> {
+pub(super) fn create_mock_tarball() -> Vec<u8> {
+    create_mock_tarball_of("portable-ruby", "#!/bin/bash\necho 'mock ruby'\n")
+}
+
+/// A tarball with a single Ruby in `dir`, whose `bin/ruby` is `ruby_content`.
+fn create_mock_tarball_of(dir: &str, ruby_content: &str) -> Vec<u8> {
     use flate2::Compression;
     use flate2::write::GzEncoder;
     use std::io::Write;
@@ -406,7 +947,7 @@ fn create_mock_tarball() -> Vec<u8> {
         let mut builder = Builder::new(&mut archive_data);
 
         let mut dir_header = tar::Header::new_gnu();
-        dir_header.set_path("portable-ruby/").unwrap();
+        dir_header.set_path(format!("{dir}/")).unwrap();
         dir_header.set_size(0);
         dir_header.set_mode(0o755);
         dir_header.set_entry_type(tar::EntryType::Directory);
@@ -414,16 +955,15 @@ fn create_mock_tarball() -> Vec<u8> {
         builder.append(&dir_header, std::io::empty()).unwrap();
 
         let mut bin_dir_header = tar::Header::new_gnu();
-        bin_dir_header.set_path("portable-ruby/bin/").unwrap();
+        bin_dir_header.set_path(format!("{dir}/bin/")).unwrap();
         bin_dir_header.set_size(0);
         bin_dir_header.set_mode(0o755);
         bin_dir_header.set_entry_type(tar::EntryType::Directory);
         bin_dir_header.set_cksum();
         builder.append(&bin_dir_header, std::io::empty()).unwrap();
 
-        let ruby_content = "#!/bin/bash\necho 'mock ruby'\n";
         let mut ruby_header = tar::Header::new_gnu();
-        ruby_header.set_path("portable-ruby/bin/ruby").unwrap();
+        ruby_header.set_path(format!("{dir}/bin/ruby")).unwrap();
         ruby_header.set_size(ruby_content.len() as u64);
         ruby_header.set_mode(0o755);
         ruby_header.set_cksum();
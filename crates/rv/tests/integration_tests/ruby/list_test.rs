@@ -10,6 +10,52 @@ impl RvTest {
         let output = cmd.output().expect("Failed to execute rv command");
         RvOutput::new(self.temp_dir.path().as_str(), output)
     }
+
+    /// Use a persistent cache and seed it with a cached release containing one Ruby version.
+    fn write_cached_releases(&mut self, expires_at: u64, etag: Option<&str>, version: &str) {
+        self.env.remove("RV_NO_CACHE");
+        let cache_dir = self.temp_dir.path().join("cache");
+        self.env
+            .insert("RV_CACHE_DIR".into(), cache_dir.as_str().into());
+
+        let http_dir = cache_dir.join("ruby-v0").join("http");
+        std::fs::create_dir_all(&http_dir).unwrap();
+        let release = serde_json::json!({
+            "name": "latest",
+            "assets": [{
+                "name": format!("ruby-{version}.arm64_sonoma.tar.gz"),
+                "browser_download_url": format!("http://.../{version}")
+            }]
+        });
+        let cached = serde_json::json!({
+            "expires_at": { "secs_since_epoch": expires_at, "nanos_since_epoch": 0 },
+            "etag": etag,
+            "body": release.to_string(),
+        });
+        std::fs::write(http_dir.join("available_rubies.json"), cached.to_string()).unwrap();
+    }
+}
+
+impl RvTest {
+    /// Records when the cached releases were fetched, which `RV_RELEASES_TTL` counts from.
+    fn set_cached_releases_fetched_at(&self, fetched_at: u64) {
+        let path = self
+            .temp_dir
+            .path()
+            .join("cache/ruby-v0/http/available_rubies.json");
+        let mut cached: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        cached["fetched_at"] =
+            serde_json::json!({ "secs_since_epoch": fetched_at, "nanos_since_epoch": 0 });
+        std::fs::write(path, cached.to_string()).unwrap();
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
 }
 
 #[test]
@@ -69,6 +115,72 @@ fn test_ruby_list_json_output_with_rubies() {
     assert_snapshot!(output.normalized_stdout());
 }
 
+#[test]
+fn test_ruby_list_json_output_ends_with_one_newline() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.2.0");
+
+    for format in ["json", "yaml", "ndjson"] {
+        let output = test.ruby_list(&["--format", format]);
+        output.assert_success();
+        let stdout = output.stdout();
+        assert!(
+            stdout.ends_with('\n') && !stdout.ends_with("\n\n"),
+            "{format} output should end with exactly one newline: {stdout:?}"
+        );
+    }
+}
+
+#[test]
+fn test_ruby_list_yaml_output_with_rubies() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.2.0");
+
+    let output = test.ruby_list(&["--format", "yaml"]);
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout(), @r"
+    release: null
+    rubies:
+    - key: ruby-3.2.0-macos-aarch64
+      version: ruby-3.2.0
+      path: /opt/rubies/ruby-3.2.0
+      arch: aarch64
+      os: macos
+      gem_root: null
+      installed: true
+      active: true
+    ");
+}
+
+#[test]
+fn test_ruby_list_ndjson_output_with_rubies() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.4.1");
+    test.create_ruby_dir("ruby-3.2.0");
+
+    let output = test.ruby_list(&["--format", "ndjson"]);
+    output.assert_success();
+    let stdout = output.normalized_stdout();
+    let versions: Vec<String> = stdout
+        .lines()
+        .map(|line| {
+            let entry: serde_json::Value = serde_json::from_str(line).unwrap();
+            entry["version"].as_str().unwrap().to_owned()
+        })
+        .collect();
+    assert_eq!(versions, ["ruby-3.2.0", "ruby-3.4.1"]);
+
+    let text = test.ruby_list(&[]).normalized_stdout();
+    let text_order: Vec<&str> = text
+        .lines()
+        .filter_map(|line| {
+            line.split_whitespace()
+                .find(|word| word.starts_with("ruby-"))
+        })
+        .collect();
+    assert_eq!(text_order, versions);
+}
+
 #[test]
 fn test_ruby_list_multiple_matching_rubies() {
     let mut test = RvTest::new();
@@ -138,6 +250,318 @@ fn test_ruby_list_with_available_and_installed() {
     insta::assert_snapshot!(output.normalized_stdout());
 }
 
+#[test]
+fn test_ruby_list_table_output() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.1.4");
+    test.create_ruby_dir("ruby-3.3.10");
+    std::fs::write(test.cwd.join(".ruby-version"), "3.1.4").unwrap();
+
+    let releases_body = r#"{
+    "name": "3.4.5",
+    "assets": [{
+        "name": "ruby-3.4.5.arm64_sonoma.tar.gz",
+        "browser_download_url": "http://..."}
+    ]}"#;
+    let mock = test.mock_releases(releases_body);
+    let output = test.ruby_list(&["--format", "table"]);
+
+    mock.assert();
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout(), @r"
+    Available from release 3.4.5
+    ACTIVE NAME        STATUS    PATH
+    *      ruby-3.1.4  installed /opt/rubies/ruby-3.1.4/bin/ruby
+           ruby-3.3.10 installed /opt/rubies/ruby-3.3.10/bin/ruby
+           ruby-3.4.5  available
+    ");
+}
+
+#[test]
+fn test_ruby_list_table_renders_text_options() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.4");
+    let gems_ruby = test.create_ruby_dir("ruby-3.4.1");
+
+    // Make the second ruby report a gem root
+    let ruby_exe = gems_ruby.join("bin").join("ruby");
+    let script = std::fs::read_to_string(&ruby_exe).unwrap().replace(
+        "echo \"\"",
+        "echo \"/opt/rubies/ruby-3.4.1/lib/ruby/gems/3.4.0\"",
+    );
+    std::fs::write(&ruby_exe, script).unwrap();
+
+    let releases_body = r#"{
+    "name": "latest",
+    "assets": [
+        {"name": "ruby-3.3.7.arm64_sonoma.tar.gz", "browser_download_url": "https://example.com/ruby-3.3.7.tar.gz"}
+    ]}"#;
+    let mock = test.mock_releases(releases_body);
+    let output = test.ruby_list(&[
+        "--format",
+        "table",
+        "--group-by",
+        "minor",
+        "--show-url",
+        "--updatable",
+    ]);
+
+    mock.assert();
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout(), @r"
+    Available from release latest
+    ACTIVE NAME       STATUS    PATH
+    3.3
+           ruby-3.3.4 installed /opt/rubies/ruby-3.3.4/bin/ruby (3.3.7 available)
+           ruby-3.3.7 available https://example.com/ruby-3.3.7.tar.gz
+    3.4
+    *      ruby-3.4.1 installed /opt/rubies/ruby-3.4.1/bin/ruby (gems)
+    ");
+}
+
+#[test]
+fn test_ruby_list_show_url() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.10");
+
+    let releases_body = r#"{
+    "name": "3.4.5",
+    "assets": [{
+        "name": "ruby-3.4.5.arm64_sonoma.tar.gz",
+        "browser_download_url": "https://example.com/ruby-3.4.5.arm64_sonoma.tar.gz"}
+    ]}"#;
+    let mock = test.mock_releases(releases_body);
+    let output = test.ruby_list(&["--show-url"]);
+
+    mock.assert();
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout(), @r"
+    Available from release 3.4.5
+    * ruby-3.3.10 [installed] /opt/rubies/ruby-3.3.10/bin/ruby
+      ruby-3.4.5  [available] https://example.com/ruby-3.4.5.arm64_sonoma.tar.gz
+    ");
+}
+
+#[test]
+fn test_ruby_list_updatable() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.4");
+    test.create_ruby_dir("ruby-3.4.5");
+
+    let releases_body = r#"{
+    "name": "latest",
+    "assets": [
+        {"name": "ruby-3.3.7.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.3.7"},
+        {"name": "ruby-3.4.5.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.4.5"}
+    ]}"#;
+    let mock = test.mock_releases(releases_body).expect(2);
+
+    let output = test.ruby_list(&["--updatable", "--installed-only"]);
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout(), @r"
+      ruby-3.3.4 [installed] /opt/rubies/ruby-3.3.4/bin/ruby (3.3.7 available)
+    * ruby-3.4.5 [installed] /opt/rubies/ruby-3.4.5/bin/ruby
+    ");
+
+    let output = test.ruby_list(&["--updatable", "--format", "json"]);
+    output.assert_success();
+    let list: serde_json::Value = serde_json::from_str(&output.normalized_stdout()).unwrap();
+    let updatable: Vec<_> = list["rubies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|ruby| (ruby["key"].as_str().unwrap(), ruby.get("updatable_to")))
+        .collect();
+    assert_eq!(
+        updatable,
+        [
+            ("ruby-3.3.4-macos-aarch64", Some(&"ruby-3.3.7".into())),
+            ("ruby-3.3.7-macos-aarch64", None),
+            ("ruby-3.4.5-macos-aarch64", None),
+        ]
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_group_by_minor() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.4");
+    test.create_ruby_dir("ruby-3.10.1");
+
+    let releases_body = r#"{
+    "name": "latest",
+    "assets": [
+        {"name": "ruby-3.3.7.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.3.7"},
+        {"name": "ruby-3.9.2.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.9.2"}
+    ]}"#;
+    let mock = test.mock_releases(releases_body).expect(2);
+
+    let output = test.ruby_list(&["--group-by", "minor"]);
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout(), @r"
+    Available from release latest
+    3.3
+        ruby-3.3.4  [installed] /opt/rubies/ruby-3.3.4/bin/ruby
+        ruby-3.3.7  [available]
+    3.9
+        ruby-3.9.2  [available]
+    3.10
+      * ruby-3.10.1 [installed] /opt/rubies/ruby-3.10.1/bin/ruby
+    ");
+
+    // JSON output stays flat.
+    let output = test.ruby_list(&["--group-by", "minor", "--format", "json"]);
+    output.assert_success();
+    let list: serde_json::Value = serde_json::from_str(&output.normalized_stdout()).unwrap();
+    assert_eq!(list["rubies"].as_array().unwrap().len(), 4);
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_count() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.4");
+    test.create_ruby_dir("ruby-3.4.5");
+
+    let releases_body = r#"{
+    "name": "latest",
+    "assets": [
+        {"name": "ruby-3.3.7.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.3.7"},
+        {"name": "ruby-3.4.5.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.4.5"}
+    ]}"#;
+    let mock = test.mock_releases(releases_body).expect(2);
+
+    let output = test.ruby_list(&["--count"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "installed=2 available=1 active=1\n"
+    );
+
+    let output = test.ruby_list(&["--count", "--format", "json"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "{\"installed\":2,\"available\":1,\"active\":1}\n"
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_sort() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.9.1");
+    test.create_ruby_dir("ruby-3.10.0");
+
+    let releases_body = r#"{
+    "name": "latest",
+    "assets": [
+        {"name": "ruby-3.8.0.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.8.0"}
+    ]}"#;
+    let mock = test.mock_releases(releases_body).expect(3);
+
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        concat!(
+            "Available from release latest\n",
+            "  ruby-3.8.0  [available]\n",
+            "  ruby-3.9.1  [installed] /opt/rubies/ruby-3.9.1/bin/ruby\n",
+            "* ruby-3.10.0 [installed] /opt/rubies/ruby-3.10.0/bin/ruby\n",
+        )
+    );
+
+    let output = test.ruby_list(&["--sort", "installed"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        concat!(
+            "Available from release latest\n",
+            "  ruby-3.9.1  [installed] /opt/rubies/ruby-3.9.1/bin/ruby\n",
+            "* ruby-3.10.0 [installed] /opt/rubies/ruby-3.10.0/bin/ruby\n",
+            "  ruby-3.8.0  [available]\n",
+        )
+    );
+
+    let output = test.ruby_list(&["--sort", "name", "--format", "json"]);
+    output.assert_success();
+    let list: serde_json::Value = serde_json::from_str(&output.normalized_stdout()).unwrap();
+    let keys: Vec<&str> = list["rubies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|ruby| ruby["key"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            "ruby-3.10.0-macos-aarch64",
+            "ruby-3.8.0-macos-aarch64",
+            "ruby-3.9.1-macos-aarch64"
+        ]
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_prereleases() {
+    let mut test = RvTest::new();
+    let releases_body = r#"{
+    "name": "latest",
+    "assets": [
+        {"name": "ruby-3.4.1.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.4.1"},
+        {"name": "ruby-3.5.0-preview1.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.5.0-preview1"}
+    ]}"#;
+    let mock = test.mock_releases(releases_body).expect(3);
+    let names = |output: &RvOutput| -> Vec<String> {
+        output
+            .normalized_stdout()
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .filter(|name| name.starts_with("ruby-"))
+            .map(ToOwned::to_owned)
+            .collect()
+    };
+
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    assert_eq!(names(&output), ["ruby-3.4.1"]);
+
+    let output = test.ruby_list(&["--prereleases"]);
+    output.assert_success();
+    assert_eq!(names(&output), ["ruby-3.4.1", "ruby-3.5.0-preview1"]);
+
+    // Pinning a prerelease shows them without the flag.
+    std::fs::write(test.cwd.join(".ruby-version"), "3.5.0-preview1\n").unwrap();
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    assert_eq!(names(&output), ["ruby-3.4.1", "ruby-3.5.0-preview1"]);
+    mock.assert();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_ruby_list_skips_dangling_ruby_symlinks() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let ruby_dir = test.create_ruby_dir("ruby-3.4.5");
+    let ruby_bin = ruby_dir.join("bin/ruby");
+    std::fs::remove_file(&ruby_bin).unwrap();
+    std::os::unix::fs::symlink(test.temp_dir.path().join("deleted/ruby"), &ruby_bin).unwrap();
+
+    let output = test.ruby_list(&["--installed-only"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "* ruby-3.3.5 [installed] /opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+    let stderr = output.stderr();
+    assert!(stderr.contains("Skipping ruby at"));
+    assert!(stderr.contains("ruby-3.4.5/bin/ruby links to missing"));
+}
+
 #[test]
 fn test_ruby_list_with_no_installed_rubies_is_empty() {
     let test = RvTest::new();
@@ -148,3 +572,630 @@ fn test_ruby_list_with_no_installed_rubies_is_empty() {
     // and the API is disabled.
     assert_eq!(output.normalized_stdout(), "");
 }
+
+#[test]
+fn test_ruby_list_installed_only_json_output_empty() {
+    let test = RvTest::new();
+    let output = test.ruby_list(&["--installed-only", "--format", "json"]);
+    output.assert_success();
+
+    let entries: serde_json::Value =
+        serde_json::from_str(&output.normalized_stdout()).expect("Output should be valid JSON");
+    assert_eq!(entries, serde_json::json!({"release": null, "rubies": []}));
+    assert_eq!(output.normalized_stderr(), "");
+}
+
+#[test]
+fn test_ruby_list_installed_only_text_output_empty_warns() {
+    let test = RvTest::new();
+    let output = test.ruby_list(&["--installed-only"]);
+    output.assert_success();
+
+    assert_eq!(output.normalized_stdout(), "");
+    assert!(
+        output
+            .normalized_stderr()
+            .contains("No Ruby installations found.")
+    );
+}
+
+#[test]
+fn test_ruby_list_limit_keeps_installed_rubies() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.1.4");
+
+    let releases_body = r#"{
+    "name": "latest",
+    "assets": [
+        {"name": "ruby-3.2.9.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.2.9"},
+        {"name": "ruby-3.3.9.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.3.9"},
+        {"name": "ruby-3.4.5.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.4.5"}
+    ]}"#;
+    let _mock = test.mock_releases(releases_body);
+
+    let output = test.ruby_list(&["--limit", "1"]);
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout(), @r"
+    Available from release latest
+    * ruby-3.1.4 [installed] /opt/rubies/ruby-3.1.4/bin/ruby
+      ruby-3.4.5 [available]
+    ");
+}
+
+#[test]
+fn test_ruby_list_reports_stale_cache_age() {
+    let mut test = RvTest::new();
+    let _mock = test
+        .server
+        .mock("GET", "/repos/spinel-coop/rv-ruby/releases/latest")
+        .with_status(500)
+        .create();
+
+    let expired_at = unix_now() - 2 * 3600 - 60;
+    test.write_cached_releases(expired_at, None, "3.4.5");
+
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Available from release latest\n  ruby-3.4.5 [available]\n"
+    );
+    assert!(
+        output
+            .normalized_stderr()
+            .contains("Displaying stale list of available rubies from cache (stale by 2h).")
+    );
+}
+
+#[test]
+fn test_ruby_list_uses_fresh_cache_without_refresh() {
+    let mut test = RvTest::new();
+    test.write_cached_releases(unix_now() + 3600, Some("\"abc\""), "3.4.5");
+    let mock = test
+        .server
+        .mock("GET", "/repos/spinel-coop/rv-ruby/releases/latest")
+        .expect(0)
+        .create();
+
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Available from release latest\n  ruby-3.4.5 [available]\n"
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_releases_ttl_keeps_stale_cache_fresh() {
+    let mut test = RvTest::new();
+    test.write_cached_releases(unix_now() - 3600, Some("\"abc\""), "3.4.5");
+    test.set_cached_releases_fetched_at(unix_now() - 7200);
+    test.env.insert("RV_RELEASES_TTL".into(), "86400".into());
+    let mock = test
+        .server
+        .mock("GET", "/repos/spinel-coop/rv-ruby/releases/latest")
+        .expect(0)
+        .create();
+
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Available from release latest\n  ruby-3.4.5 [available]\n"
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_releases_ttl_revalidates_fresh_cache() {
+    let mut test = RvTest::new();
+    test.write_cached_releases(unix_now() + 3600, Some("\"abc\""), "3.4.5");
+    test.set_cached_releases_fetched_at(unix_now() - 60);
+    test.env.insert("RV_RELEASES_TTL".into(), "0".into());
+    let mock = test
+        .server
+        .mock("GET", "/repos/spinel-coop/rv-ruby/releases/latest")
+        .match_header("If-None-Match", "\"abc\"")
+        .with_status(304)
+        .expect(1)
+        .create();
+
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Available from release latest\n  ruby-3.4.5 [available]\n"
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_refresh_revalidates_with_etag() {
+    let mut test = RvTest::new();
+    test.write_cached_releases(unix_now() + 3600, Some("\"abc\""), "3.4.5");
+    let mock = test
+        .server
+        .mock("GET", "/repos/spinel-coop/rv-ruby/releases/latest")
+        .match_header("If-None-Match", "\"abc\"")
+        .with_status(304)
+        .expect(1)
+        .create();
+
+    let output = test.ruby_list(&["--refresh"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Available from release latest\n  ruby-3.4.5 [available]\n"
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_hard_refresh_skips_etag() {
+    let mut test = RvTest::new();
+    test.write_cached_releases(unix_now() + 3600, Some("\"abc\""), "3.4.5");
+    let mock = test
+        .server
+        .mock("GET", "/repos/spinel-coop/rv-ruby/releases/latest")
+        .match_header("If-None-Match", mockito::Matcher::Missing)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"name": "latest", "assets": [
+                {"name": "ruby-3.4.6.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.4.6"}
+            ]}"#,
+        )
+        .expect(1)
+        .create();
+
+    let output = test.ruby_list(&["--refresh=hard"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Available from release latest\n  ruby-3.4.6 [available]\n"
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_sends_versioned_user_agent() {
+    let mut test = RvTest::new();
+    let user_agent = format!("rv/{} (", env!("CARGO_PKG_VERSION"));
+    let mock = test
+        .server
+        .mock("GET", "/repos/spinel-coop/rv-ruby/releases/latest")
+        .match_header(
+            "user-agent",
+            mockito::Matcher::Regex(format!(r"^{}\w+/\w+\)$", regex::escape(&user_agent))),
+        )
+        .with_status(200)
+        .with_body(r#"{"name": "3.4.5", "assets": []}"#)
+        .create();
+
+    test.ruby_list(&[]).assert_success();
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_uses_proxy_unless_disabled() {
+    let mut test = RvTest::new();
+    let mock = test
+        .mock_releases(
+            r#"{"name": "latest", "assets": [
+                {"name": "ruby-3.4.5.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.4.5"}
+            ]}"#,
+        )
+        .expect(1);
+
+    // Nothing listens on this proxy, so going through it fails...
+    test.env
+        .insert("HTTP_PROXY".into(), "http://127.0.0.1:1".into());
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "");
+    assert!(
+        output
+            .normalized_stderr()
+            .contains("Could not fetch or re-validate available Ruby versions")
+    );
+
+    // ...unless the proxy is bypassed.
+    test.env.insert("RV_NO_PROXY".into(), "1".into());
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Available from release latest\n  ruby-3.4.5 [available]\n"
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_engine_filter() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("jruby-9.4.0.0");
+
+    let output = test.ruby_list(&["--installed-only", "--engine", "jruby"]);
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout(), @"  jruby-9.4.0.0 [installed] /opt/rubies/jruby-9.4.0.0/bin/ruby");
+
+    let output = test.ruby_list(&["--installed-only", "--engine", "ruby"]);
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout(), @"* ruby-3.3.5 [installed] /opt/rubies/ruby-3.3.5/bin/ruby");
+}
+
+#[test]
+fn test_ruby_list_engine_filter_rejects_unknown_engine() {
+    let test = RvTest::new();
+    let output = test.ruby_list(&["--engine", "rubinius"]);
+    output.assert_failure();
+    assert!(
+        output
+            .normalized_stderr()
+            .contains("invalid value 'rubinius' for '--engine <ENGINE>'")
+    );
+}
+
+#[test]
+fn test_ruby_list_reports_unreachable_releases_host() {
+    let mut test = RvTest::new();
+    // Nothing listens on port 1.
+    test.env
+        .insert("RV_RELEASES_URL".into(), "http://127.0.0.1:1".into());
+
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    assert!(
+        output
+            .normalized_stderr()
+            .contains("Could not connect to releases host 127.0.0.1")
+    );
+}
+
+#[test]
+fn test_ruby_list_times_out_on_stalled_releases_host() {
+    let mut test = RvTest::new();
+    // Connections are accepted by the OS, but nothing ever responds.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    test.env.insert("RV_RELEASES_URL".into(), url.clone());
+    test.env.insert("RV_TIMEOUT".into(), "1".into());
+
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    assert!(output.normalized_stderr().contains(&format!(
+        "Timed out waiting for {url}/repos/spinel-coop/rv-ruby/releases/latest"
+    )));
+}
+
+#[test]
+fn test_ruby_list_reports_malformed_releases() {
+    let mut test = RvTest::new();
+    let _mock = test.mock_releases("<html>Not the releases you're looking for</html>");
+
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    let releases_url = format!(
+        "{}/repos/spinel-coop/rv-ruby/releases/latest",
+        test.server_url()
+    );
+    assert!(output.normalized_stderr().contains(&format!(
+        "Releases endpoint {releases_url} returned an unexpected response body"
+    )));
+}
+
+#[test]
+fn test_ruby_list_quiet_keeps_the_list() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+
+    let output = test.rv(&["--quiet", "ruby", "list", "--installed-only"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "* ruby-3.3.5 [installed] /opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+
+    // The release the available rubies come from isn't cited.
+    let mock = test
+        .mock_releases(
+            r#"{"name": "latest", "assets": [
+                {"name": "ruby-3.4.5.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.4.5"}
+            ]}"#,
+        )
+        .expect(1);
+    let output = test.rv(&["--quiet", "ruby", "list"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "* ruby-3.3.5 [installed] /opt/rubies/ruby-3.3.5/bin/ruby\n  ruby-3.4.5 [available]\n"
+    );
+    mock.assert();
+
+    let output = test.rv(&["--quiet", "ruby", "list", "--installed-only", "--count"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "installed=1 available=0 active=1\n"
+    );
+
+    let output = test.rv(&[
+        "--quiet",
+        "ruby",
+        "list",
+        "--installed-only",
+        "--format",
+        "json",
+    ]);
+    output.assert_success();
+    let entries: serde_json::Value =
+        serde_json::from_str(&output.normalized_stdout()).expect("Output should be valid JSON");
+    assert_eq!(entries["rubies"][0]["version"], "ruby-3.3.5");
+}
+
+#[test]
+fn test_ruby_list_quiet_hides_warnings() {
+    let test = RvTest::new();
+    let output = test.rv(&["--quiet", "ruby", "list", "--installed-only"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stderr(), "");
+}
+
+#[test]
+fn test_ruby_list_with_gems() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.2.0");
+    let used_ruby = test.create_ruby_dir("ruby-3.3.5");
+
+    // Make the second ruby report a gem root
+    let ruby_exe = used_ruby.join("bin").join("ruby");
+    let script = std::fs::read_to_string(&ruby_exe).unwrap().replace(
+        "echo \"\"",
+        "echo \"/opt/rubies/ruby-3.3.5/lib/ruby/gems/3.3.0\"",
+    );
+    std::fs::write(&ruby_exe, script).unwrap();
+
+    let output = test.ruby_list(&["--installed-only"]);
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout(), @r"
+      ruby-3.2.0 [installed] /opt/rubies/ruby-3.2.0/bin/ruby
+    * ruby-3.3.5 [installed] /opt/rubies/ruby-3.3.5/bin/ruby (gems)
+    ");
+
+    let output = test.ruby_list(&["--installed-only", "--with-gems"]);
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout(), @"* ruby-3.3.5 [installed] /opt/rubies/ruby-3.3.5/bin/ruby (gems)");
+}
+
+#[test]
+fn test_ruby_list_offline_uses_stale_cache_without_requests() {
+    let mut test = RvTest::new();
+    let mock = test
+        .server
+        .mock("GET", "/repos/spinel-coop/rv-ruby/releases/latest")
+        .expect(0)
+        .create();
+
+    let output = test.rv(&["--offline", "ruby", "list"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "");
+
+    test.write_cached_releases(unix_now() - 3600, None, "3.4.5");
+    let output = test.rv(&["--offline", "ruby", "list", "--refresh"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Available from release latest\n  ruby-3.4.5 [available]\n"
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_available_only() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.9");
+
+    let _mock = test.mock_releases(
+        r#"{"name": "latest", "assets": [
+            {"name": "ruby-3.3.9.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.3.9"},
+            {"name": "ruby-3.4.5.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.4.5"}
+        ]}"#,
+    );
+
+    let output = test.ruby_list(&["--available-only"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Available from release latest\n  ruby-3.4.5 [available]\n"
+    );
+
+    let output = test.ruby_list(&["--available-only", "--format", "json"]);
+    output.assert_success();
+    let entries: serde_json::Value =
+        serde_json::from_str(&output.normalized_stdout()).expect("Output should be valid JSON");
+    let entries = entries["rubies"].as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["version"], "ruby-3.4.5");
+    assert_eq!(entries[0]["installed"], false);
+}
+
+#[test]
+fn test_ruby_list_shows_release_tag() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let _mock = test.mock_releases(
+        r#"{"name": "Rubies 2025-08-18", "tag_name": "20250818", "assets": [
+            {"name": "ruby-3.4.5.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.4.5"}
+        ]}"#,
+    );
+
+    let output = test.ruby_list(&[]);
+    output.assert_success();
+    assert!(
+        output
+            .normalized_stdout()
+            .starts_with("Available from release Rubies 2025-08-18 (20250818)\n")
+    );
+
+    let output = test.ruby_list(&["--format", "json"]);
+    output.assert_success();
+    let list: serde_json::Value =
+        serde_json::from_str(&output.normalized_stdout()).expect("Output should be valid JSON");
+    assert_eq!(
+        list["release"],
+        serde_json::json!({"name": "Rubies 2025-08-18", "tag": "20250818"})
+    );
+
+    // Nothing listed comes from the release, so there's nothing to cite.
+    let output = test.ruby_list(&["--installed-only", "--format", "json"]);
+    let list: serde_json::Value =
+        serde_json::from_str(&output.normalized_stdout()).expect("Output should be valid JSON");
+    assert_eq!(list["release"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_ruby_list_available_only_conflicts_with_installed_only() {
+    let test = RvTest::new();
+    let output = test.ruby_list(&["--available-only", "--installed-only"]);
+    output.assert_failure();
+    assert_eq!(output.output.status.code(), Some(2));
+}
+
+#[test]
+fn test_ruby_list_path_only() {
+    let test = RvTest::new();
+
+    let output = test.ruby_list(&["--path-only"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "");
+    assert_eq!(output.normalized_stderr(), "");
+
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("jruby-9.4.0.0");
+    let output = test.ruby_list(&["--path-only"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.5/bin/ruby\n/opt/rubies/jruby-9.4.0.0/bin/ruby\n"
+    );
+
+    let output = test.ruby_list(&["--path-only", "--engine", "jruby"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "/opt/rubies/jruby-9.4.0.0/bin/ruby\n"
+    );
+
+    let output = test.ruby_list(&["--path-only", "--format", "json"]);
+    assert_eq!(output.output.status.code(), Some(2));
+}
+
+#[test]
+fn test_ruby_list_update_check() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.env.remove("RV_NO_CACHE");
+    let cache_dir = test.temp_dir.path().join("cache");
+    test.env
+        .insert("RV_CACHE_DIR".into(), cache_dir.as_str().into());
+    let mock = test
+        .server
+        .mock("GET", "/repos/spinel-coop/rv/releases/latest")
+        .expect(0)
+        .create();
+
+    // The check is opt-in.
+    let output = test.ruby_list(&["--installed-only"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stderr(), "");
+
+    // Offline, a cached release is still looked at, however old.
+    let http_dir = cache_dir.join("ruby-v0").join("http");
+    std::fs::create_dir_all(&http_dir).unwrap();
+    let cached = serde_json::json!({
+        "expires_at": { "secs_since_epoch": 0, "nanos_since_epoch": 0 },
+        "fetched_at": { "secs_since_epoch": 0, "nanos_since_epoch": 0 },
+        "etag": null,
+        "body": r#"{"tag_name": "v99.0.0"}"#,
+    });
+    std::fs::write(http_dir.join("latest_rv_release.json"), cached.to_string()).unwrap();
+    test.env.insert("RV_CHECK_UPDATE".into(), "1".into());
+    let output = test.rv(&["--offline", "ruby", "list", "--installed-only"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stderr(),
+        format!(
+            "note: rv 99.0.0 is available, you have {}\n",
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_ruby_list_does_not_wait_for_update_check() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    // Accepts connections, but never answers.
+    let unresponsive = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", unresponsive.local_addr().unwrap());
+    test.env.insert("RV_RELEASES_URL".into(), url);
+    test.env.insert("RV_CHECK_UPDATE".into(), "1".into());
+
+    let start = std::time::Instant::now();
+    let output = test.ruby_list(&["--installed-only"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stderr(), "");
+    // The check itself would only give up after two seconds.
+    assert!(
+        start.elapsed() < std::time::Duration::from_secs(2),
+        "ruby list waited {:?} for the update check",
+        start.elapsed()
+    );
+}
+
+#[test]
+fn test_ruby_list_no_remote_uses_only_fresh_cache() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let mock = test
+        .server
+        .mock("GET", "/repos/spinel-coop/rv-ruby/releases/latest")
+        .expect(0)
+        .create();
+
+    // Without a cached list, only the installs are shown.
+    let output = test.ruby_list(&["--no-remote"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "* ruby-3.3.5 [installed] /opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+
+    test.write_cached_releases(unix_now() + 3600, None, "3.4.5");
+    let output = test.ruby_list(&["--no-remote"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        concat!(
+            "Available from release latest\n",
+            "* ruby-3.3.5 [installed] /opt/rubies/ruby-3.3.5/bin/ruby\n",
+            "  ruby-3.4.5 [available]\n",
+        )
+    );
+
+    // Unlike `--offline`, a stale list isn't used.
+    test.write_cached_releases(unix_now() - 3600, None, "3.4.5");
+    let output = test.ruby_list(&["--no-remote"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "* ruby-3.3.5 [installed] /opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+    mock.assert();
+
+    let output = test.ruby_list(&["--no-remote", "--installed-only"]);
+    assert_eq!(output.output.status.code(), Some(2));
+}
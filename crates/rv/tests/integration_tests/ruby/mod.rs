@@ -1,4 +1,13 @@
+mod current_test;
+mod dir_test;
 mod find_test;
+mod info_test;
 mod install_test;
 mod list_test;
+mod pin_test;
+mod project_config_test;
+mod reshim_test;
+#[cfg(unix)]
+mod run_test;
 mod uninstall_test;
+mod upgrade_test;
@@ -0,0 +1,217 @@
+use crate::common::{RvOutput, RvTest};
+
+impl RvTest {
+    pub fn ruby_pin(&self, args: &[&str]) -> RvOutput {
+        let mut cmd = self.rv_command();
+        cmd.args(["ruby", "pin"]);
+        cmd.args(args);
+
+        let output = cmd.output().expect("Failed to execute rv command");
+        RvOutput::new(self.temp_dir.path().as_str(), output)
+    }
+}
+
+#[test]
+fn test_ruby_pin_unset_removes_ruby_version() {
+    let mut test = RvTest::new();
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join(".ruby-version"), "3.3.5\n").unwrap();
+    test.cwd = project_dir.clone();
+
+    let pin = test.ruby_pin(&["--unset"]);
+    pin.assert_success();
+    assert_eq!(
+        pin.normalized_stdout(),
+        "Removed /project/.ruby-version (was pinned to Ruby 3.3.5)\n"
+    );
+    assert!(!project_dir.join(".ruby-version").exists());
+
+    let pin = test.ruby_pin(&["--unset"]);
+    pin.assert_success();
+    assert_eq!(
+        pin.normalized_stdout(),
+        "/project has no pinned Ruby version\n"
+    );
+}
+
+#[test]
+fn test_ruby_pin_unset_conflicts_with_version() {
+    let test = RvTest::new();
+
+    let pin = test.ruby_pin(&["--unset", "3.3.5"]);
+    assert_eq!(pin.output.status.code(), Some(2));
+    assert!(
+        pin.normalized_stderr()
+            .contains("the argument '--unset' cannot be used with '[VERSION_REQUEST]'")
+    );
+}
+
+#[test]
+fn test_ruby_pin_engine_request_only_matches_that_engine() {
+    let mut test = RvTest::new();
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    test.cwd = project_dir.clone();
+
+    test.create_ruby_dir("ruby-9.4.12");
+    test.create_ruby_dir("ruby-3.4.1");
+
+    let pin = test.ruby_pin(&["jruby-9.4"]);
+    pin.assert_success();
+    assert_eq!(
+        std::fs::read_to_string(project_dir.join(".ruby-version")).unwrap(),
+        "jruby-9.4\n"
+    );
+
+    // Only MRI is installed, so the JRuby pin must not resolve.
+    let find = test.ruby_find(&[]);
+    assert!(!find.success());
+
+    test.create_ruby_dir("jruby-9.4.12.0");
+    let find = test.ruby_find(&[]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/jruby-9.4.12.0/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_ruby_pin_quiet_prints_nothing() {
+    let mut test = RvTest::new();
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    test.cwd = project_dir.clone();
+
+    let output = test.rv(&["--quiet", "ruby", "pin", "3.3.5"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "");
+    assert_eq!(output.normalized_stderr(), "");
+    assert_eq!(
+        std::fs::read_to_string(project_dir.join(".ruby-version")).unwrap(),
+        "3.3.5\n"
+    );
+}
+
+#[test]
+fn test_ruby_pin_shows_version_change() {
+    let mut test = RvTest::new();
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    test.cwd = project_dir;
+
+    let pin = test.ruby_pin(&["3.2.0"]);
+    pin.assert_success();
+    assert_eq!(
+        pin.normalized_stdout(),
+        "/project pinned to Ruby 3.2.0 (created .ruby-version)\n"
+    );
+
+    let pin = test.ruby_pin(&["3.3.0"]);
+    pin.assert_success();
+    assert_eq!(
+        pin.normalized_stdout(),
+        "/project pinned to Ruby 3.2.0 -> 3.3.0\n"
+    );
+
+    let pin = test.ruby_pin(&["3.3.0"]);
+    pin.assert_success();
+    assert_eq!(pin.normalized_stdout(), "/project pinned to Ruby 3.3.0\n");
+}
+
+#[test]
+fn test_ruby_pin_custom_ruby_version_file() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.1");
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(project_dir.join("lib")).unwrap();
+    test.cwd = project_dir.clone();
+    test.env
+        .insert("RV_RUBY_VERSION_FILE".into(), ".rv-ruby".into());
+
+    let pin = test.ruby_pin(&["3.3.5"]);
+    pin.assert_success();
+    assert_eq!(
+        pin.normalized_stdout(),
+        "/project pinned to Ruby 3.3.5 (created .rv-ruby)\n"
+    );
+    assert!(project_dir.join(".rv-ruby").exists());
+    assert!(!project_dir.join(".ruby-version").exists());
+
+    test.cwd = project_dir.join("lib");
+    let find = test.ruby_find(&[]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_ruby_pin_change_is_colored() {
+    let mut test = RvTest::new();
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join(".ruby-version"), "3.2.0\n").unwrap();
+    test.cwd = project_dir;
+
+    let pin = test.rv(&["--color", "always", "ruby", "pin", "3.3.0"]);
+    pin.assert_success();
+    assert!(
+        pin.normalized_stdout()
+            .contains("\u{1b}[31m3.2.0\u{1b}[39m -> \u{1b}[32m3.3.0\u{1b}[39m")
+    );
+}
+
+#[test]
+fn test_ruby_pin_list_candidates_does_not_pin() {
+    let mut test = RvTest::new();
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    test.cwd = project_dir.clone();
+
+    test.create_ruby_dir("ruby-3.3.1");
+    test.create_ruby_dir("ruby-3.3.10");
+    test.create_ruby_dir("ruby-3.4.1");
+
+    let pin = test.ruby_pin(&["3.3", "--list-candidates"]);
+    pin.assert_success();
+    assert_eq!(
+        pin.normalized_stdout(),
+        "Pinning 3.3 would use:\n\
+         * ruby-3.3.10 /opt/rubies/ruby-3.3.10/bin/ruby\n  \
+         ruby-3.3.1 /opt/rubies/ruby-3.3.1/bin/ruby\n"
+    );
+    assert!(!project_dir.join(".ruby-version").exists());
+}
+
+#[test]
+fn test_ruby_pin_list_candidates_without_match() {
+    let mut test = RvTest::new();
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    test.cwd = project_dir.clone();
+
+    test.create_ruby_dir("ruby-3.4.1");
+
+    let pin = test.ruby_pin(&["3.3", "--list-candidates"]);
+    pin.assert_success();
+    assert_eq!(
+        pin.normalized_stdout(),
+        "No installed Ruby matches 3.3, it can still be pinned and installed later\n"
+    );
+    assert!(!project_dir.join(".ruby-version").exists());
+
+    test.ruby_pin(&["3.3"]).assert_success();
+    assert!(project_dir.join(".ruby-version").exists());
+}
+
+#[test]
+fn test_ruby_pin_list_candidates_requires_version() {
+    let test = RvTest::new();
+
+    let pin = test.ruby_pin(&["--list-candidates"]);
+    assert_eq!(pin.output.status.code(), Some(2));
+}
@@ -0,0 +1,108 @@
+use crate::common::RvTest;
+
+#[test]
+fn test_rv_toml_sets_ruby_request() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.1");
+
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join("rv.toml"), "ruby = \"3.3\"\n").unwrap();
+    test.cwd = project_dir.clone();
+
+    let output = test.ruby_find(&[]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+
+    // `.ruby-version` takes precedence
+    std::fs::write(project_dir.join(".ruby-version"), "3.4\n").unwrap();
+    let output = test.ruby_find(&[]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "/opt/rubies/ruby-3.4.1/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_rv_toml_adds_ruby_dirs() {
+    let mut test = RvTest::new();
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(
+        project_dir.join("rv.toml"),
+        "ruby-dirs = [\"vendor/rubies\"]\n",
+    )
+    .unwrap();
+    test.cwd = project_dir.clone();
+
+    // Move a ruby into the project's own ruby dir
+    let ruby_dir = test.create_ruby_dir("ruby-3.2.9");
+    let vendored_dir = project_dir.join("vendor/rubies");
+    std::fs::create_dir_all(&vendored_dir).unwrap();
+    std::fs::rename(&ruby_dir, vendored_dir.join("ruby-3.2.9")).unwrap();
+
+    // Only trusted projects may add Ruby directories.
+    let config_dir = test.temp_dir.path().join("tmp/home/.config/rv");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("config.toml"),
+        "trusted-projects = [\"/project\"]\n",
+    )
+    .unwrap();
+
+    let output = test.ruby_find(&["3.2"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "/project/vendor/rubies/ruby-3.2.9/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_untrusted_rv_toml_adds_no_ruby_dirs() {
+    let mut test = RvTest::new();
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(
+        project_dir.join("rv.toml"),
+        "ruby-dirs = [\"vendor/rubies\"]\n",
+    )
+    .unwrap();
+    test.cwd = project_dir.clone();
+
+    let ruby_dir = test.create_ruby_dir("ruby-3.2.9");
+    let vendored_dir = project_dir.join("vendor/rubies");
+    std::fs::create_dir_all(&vendored_dir).unwrap();
+    std::fs::rename(&ruby_dir, vendored_dir.join("ruby-3.2.9")).unwrap();
+
+    let output = test.ruby_find(&["3.2"]);
+    assert_eq!(output.output.status.code(), Some(5));
+    assert!(
+        output
+            .normalized_stderr()
+            .contains(&format!("Ignoring the ruby-dirs in {project_dir}/rv.toml"))
+    );
+
+    let output = test.ruby_list(&["--installed-only", "--format", "json"]);
+    output.assert_success();
+    let list: serde_json::Value = serde_json::from_str(&output.stdout()).unwrap();
+    assert_eq!(list["rubies"], serde_json::json!([]));
+}
+
+#[test]
+fn test_invalid_rv_toml_is_an_error() {
+    let mut test = RvTest::new();
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join("rv.toml"), "ruby = 3\n").unwrap();
+    test.cwd = project_dir;
+
+    let output = test.ruby_find(&[]);
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("InvalidProjectConfig"));
+}
@@ -0,0 +1,34 @@
+use crate::common::RvTest;
+
+#[test]
+fn test_ruby_reshim_refreshes_cached_rubies() {
+    let mut test = RvTest::new();
+    test.env.remove("RV_NO_CACHE");
+    let cache_dir = test.temp_dir.path().join("cache");
+    test.env
+        .insert("RV_CACHE_DIR".into(), cache_dir.as_str().into());
+
+    let ruby_dir = test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.1");
+    test.ruby_list(&["--installed-only"]).assert_success();
+
+    // Plant a cached entry that would otherwise be trusted, and some release metadata.
+    let interpreters = cache_dir.join("ruby-v0").join("interpreters");
+    std::fs::write(interpreters.join("leftover"), "{}").unwrap();
+    let http_dir = cache_dir.join("ruby-v0").join("http");
+    std::fs::create_dir_all(&http_dir).unwrap();
+    std::fs::write(http_dir.join("available_rubies.json"), "{}").unwrap();
+
+    let output = test.rv(&["ruby", "reshim"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "Refreshed 2 rubies\n");
+    assert!(!interpreters.join("leftover").exists());
+    assert!(http_dir.join("available_rubies.json").exists());
+    assert!(ruby_dir.exists());
+
+    let output = test.rv(&["cache", "verify"]);
+    assert_eq!(
+        output.normalized_stdout(),
+        "Checked 2 cached interpreters, found 0 invalid\n"
+    );
+}
@@ -0,0 +1,78 @@
+use crate::common::{RvOutput, RvTest};
+
+impl RvTest {
+    pub fn ruby_run(&self, args: &[&str]) -> RvOutput {
+        let mut cmd = self.rv_command();
+        cmd.args(["ruby", "run"]);
+        cmd.args(args);
+
+        let output = cmd.output().expect("Failed to execute rv command");
+        RvOutput::new(self.temp_dir.path().as_str(), output)
+    }
+
+    /// Creates a mock ruby that also prints the arguments it was run with to stderr.
+    fn create_echoing_ruby_dir(&self, name: &str) {
+        let ruby_dir = self.create_ruby_dir(name);
+        let ruby_exe = ruby_dir.join("bin").join("ruby");
+        let script = std::fs::read_to_string(&ruby_exe).unwrap();
+        std::fs::write(
+            &ruby_exe,
+            format!("{script}printf 'arg: %s\\n' \"$@\" >&2\n"),
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_ruby_run_version_flag_passes_ruby_flags_through() {
+    let test = RvTest::new();
+    test.create_echoing_ruby_dir("ruby-3.2.0");
+    test.create_echoing_ruby_dir("ruby-3.3.0");
+
+    let output = test.ruby_run(&["--version", "3.2", "--", "-e", "puts RUBY_VERSION"]);
+    output.assert_success();
+    assert!(output.normalized_stdout().starts_with("ruby\n3.2.0\n"));
+    assert_eq!(
+        output.normalized_stderr(),
+        "arg: -e\narg: puts RUBY_VERSION\n"
+    );
+}
+
+#[test]
+fn test_ruby_run_positional_version() {
+    let test = RvTest::new();
+    test.create_echoing_ruby_dir("ruby-3.2.0");
+    test.create_echoing_ruby_dir("ruby-3.3.0");
+
+    let output = test.ruby_run(&["3.2", "--", "--version"]);
+    output.assert_success();
+    assert!(output.normalized_stdout().starts_with("ruby\n3.2.0\n"));
+    assert_eq!(output.normalized_stderr(), "arg: --version\n");
+}
+
+#[test]
+fn test_ruby_run_without_version_uses_project_pin() {
+    let mut test = RvTest::new();
+    test.create_echoing_ruby_dir("ruby-3.2.0");
+    test.create_echoing_ruby_dir("ruby-3.3.0");
+
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join(".ruby-version"), "3.2").unwrap();
+    test.cwd = project_dir;
+
+    let output = test.ruby_run(&["--", "-v"]);
+    output.assert_success();
+    assert!(output.normalized_stdout().starts_with("ruby\n3.2.0\n"));
+    assert_eq!(output.normalized_stderr(), "arg: -v\n");
+}
+
+#[test]
+fn test_ruby_run_version_flag_conflicts_with_positional_version() {
+    let test = RvTest::new();
+    test.create_echoing_ruby_dir("ruby-3.3.0");
+
+    let output = test.ruby_run(&["3.3", "--version", "3.3"]);
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("cannot be used with"));
+}
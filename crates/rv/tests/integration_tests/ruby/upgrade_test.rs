@@ -0,0 +1,85 @@
+use super::install_test::{create_mock_tarball, make_dl_suffix};
+use crate::common::RvTest;
+
+const RELEASES: &str = r#"{"name": "latest", "assets": [
+    {"name": "ruby-3.3.7.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.3.7"},
+    {"name": "ruby-3.4.5.arm64_sonoma.tar.gz", "browser_download_url": "http://.../3.4.5"}
+]}"#;
+
+#[test]
+fn test_ruby_upgrade_installs_and_pins_latest_patch() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.4");
+    std::fs::write(test.cwd.join(".ruby-version"), "3.3\n").unwrap();
+    let _releases = test.mock_releases(RELEASES);
+    let download = test
+        .mock_tarball_download(&make_dl_suffix("3.3.7"), &create_mock_tarball())
+        .create();
+
+    let output = test.rv(&["ruby", "upgrade"]);
+
+    output.assert_success();
+    download.assert();
+    assert!(
+        output
+            .normalized_stdout()
+            .contains("Installed Ruby version ruby-3.3.7")
+    );
+    assert_eq!(
+        std::fs::read_to_string(test.cwd.join(".ruby-version")).unwrap(),
+        "3.3.7\n"
+    );
+}
+
+#[test]
+fn test_ruby_upgrade_already_latest_only_pins() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.4.5");
+    std::fs::write(test.cwd.join(".ruby-version"), "3.4.1\n").unwrap();
+    let _releases = test.mock_releases(RELEASES);
+
+    let output = test.rv(&["ruby", "upgrade"]);
+
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Ruby ruby-3.4.5 is already the latest patch of ruby-3.4\n pinned to Ruby 3.4.1 -> 3.4.5\n"
+    );
+}
+
+#[test]
+fn test_ruby_upgrade_all_leaves_pin_alone() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.4");
+    test.create_ruby_dir("ruby-3.4.5");
+    test.create_ruby_dir("jruby-9.4.12.0");
+    std::fs::write(test.cwd.join(".ruby-version"), "3.3.4\n").unwrap();
+    let _releases = test.mock_releases(RELEASES);
+    let download = test
+        .mock_tarball_download(&make_dl_suffix("3.3.7"), &create_mock_tarball())
+        .create();
+
+    let output = test.rv(&["ruby", "upgrade", "--all"]);
+
+    output.assert_success();
+    download.assert();
+    let stdout = output.normalized_stdout();
+    assert!(
+        stdout.contains("Installed Ruby version ruby-3.3.7"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Ruby ruby-3.4.5 is already the latest patch of ruby-3.4"));
+    assert_eq!(
+        std::fs::read_to_string(test.cwd.join(".ruby-version")).unwrap(),
+        "3.3.4\n"
+    );
+}
+
+#[test]
+fn test_ruby_upgrade_needs_project() {
+    let test = RvTest::new();
+    let output = test.rv(&["ruby", "upgrade"]);
+
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("NoProjectDir"));
+}
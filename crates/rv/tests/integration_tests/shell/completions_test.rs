@@ -0,0 +1,75 @@
+use crate::common::RvTest;
+
+#[test]
+fn test_shell_completions_prints_script() {
+    let test = RvTest::new();
+    let output = test.rv(&["shell", "completions", "fish"]);
+
+    output.assert_success();
+    assert!(output.stdout().contains("complete -c rv"));
+}
+
+#[test]
+fn test_shell_completions_install_writes_script() {
+    let test = RvTest::new();
+    let output = test.rv(&["shell", "completions", "fish", "--install"]);
+
+    output.assert_success();
+    let path = test
+        .temp_dir
+        .path()
+        .join("tmp/home/.config/fish/completions/rv.fish");
+    assert_eq!(
+        output.normalized_stdout(),
+        "Installed fish completions to /tmp/home/.config/fish/completions/rv.fish\n"
+    );
+    let script = std::fs::read_to_string(path).unwrap();
+    assert!(script.contains("complete -c rv"));
+}
+
+#[test]
+fn test_shell_completions_install_requires_force_to_overwrite() {
+    let test = RvTest::new();
+    let path = test.temp_dir.path().join("tmp/home/.zsh/completions/_rv");
+
+    test.rv(&["shell", "completions", "zsh", "--install"])
+        .assert_success();
+    std::fs::write(&path, "outdated").unwrap();
+
+    let output = test.rv(&["shell", "completions", "zsh", "--install"]);
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("AlreadyExists"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "outdated");
+
+    test.rv(&["shell", "completions", "zsh", "--install", "--force"])
+        .assert_success();
+    assert!(
+        std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("#compdef rv")
+    );
+}
+
+#[test]
+fn test_shell_completions_install_follows_xdg_data_home() {
+    let mut test = RvTest::new();
+    test.env.insert("XDG_DATA_HOME".into(), "/tmp/data".into());
+
+    test.rv(&["shell", "completions", "bash", "--install"])
+        .assert_success();
+
+    let path = test
+        .temp_dir
+        .path()
+        .join("tmp/data/bash-completion/completions/rv");
+    assert!(path.exists());
+}
+
+#[test]
+fn test_shell_completions_install_rejects_unknown_location() {
+    let test = RvTest::new();
+    let output = test.rv(&["shell", "completions", "powershell", "--install"]);
+
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("NoCompletionsDir"));
+}
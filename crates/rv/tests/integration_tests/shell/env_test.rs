@@ -1,4 +1,4 @@
-use crate::common::RvTest;
+use crate::common::{RvOutput, RvTest};
 use insta::assert_snapshot;
 
 #[test]
@@ -19,6 +19,50 @@ fn test_nushell_env_succeeds() {
     assert!(output.success());
 }
 
+#[test]
+fn test_nushell_env_with_ruby() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.env.insert("PATH".into(), "/tmp/bin".into());
+    test.env.insert("RUBYOPT".into(), "--verbose".into());
+    let output = test.rv(&["shell", "env", "nu"]);
+    output.assert_success();
+
+    // A single record for `load-env`, which hooks from earlier versions still pipe it to.
+    let env: serde_json::Value =
+        serde_json::from_str(&output.normalized_stdout()).expect("Output should be valid JSON");
+    assert!(env["PATH"].is_array(), "Nushell PATH should be a list");
+    assert_eq!(env["RUBYOPT"], serde_json::json!({}));
+    assert_snapshot!(output.normalized_stdout());
+
+    let output = test.rv(&["shell", "env", "nu", "--hide-env"]);
+    output.assert_success();
+    let env: serde_json::Value =
+        serde_json::from_str(&output.normalized_stdout()).expect("Output should be valid JSON");
+    assert!(
+        env["set"]["PATH"].is_array(),
+        "Nushell PATH should be a list"
+    );
+    assert!(
+        env["unset"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("RUBYOPT"))
+    );
+}
+
+#[test]
+fn test_powershell_env_succeeds() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.env.insert("PATH".into(), "/tmp/bin".into());
+    test.env.insert("RUBYOPT".into(), "--verbose".into());
+    let output = test.rv(&["shell", "env", "powershell"]);
+
+    assert_snapshot!(output.normalized_stdout());
+    assert!(output.success());
+}
+
 #[test]
 fn test_shell_env_with_path() {
     let mut test = RvTest::new();
@@ -81,3 +125,244 @@ fn test_shell_env_with_ruby() {
 
     assert_snapshot!(output.normalized_stdout());
 }
+
+#[test]
+fn test_shell_env_does_not_duplicate_path_entries() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.env
+        .insert("PATH".into(), "/tmp/bin:/usr/bin:/tmp/bin".into());
+
+    let exported_path = |output: String| -> String {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix("export PATH="))
+            .expect("PATH should be exported")
+            .trim_matches('\'')
+            .to_owned()
+    };
+
+    let output = test.rv(&["shell", "env", "zsh"]);
+    output.assert_success();
+    let first_path = exported_path(output.stdout());
+
+    // Evaluating the hook again with the resulting PATH shouldn't grow it.
+    test.env.insert("PATH".into(), first_path.clone());
+    let output = test.rv(&["shell", "env", "zsh"]);
+    output.assert_success();
+    let second_path = exported_path(output.stdout());
+
+    assert_eq!(first_path, second_path);
+    assert_eq!(
+        second_path.matches("/tmp/bin").count(),
+        1,
+        "PATH should not contain duplicates: {second_path}"
+    );
+}
+
+#[test]
+fn test_shell_env_uses_detected_gem_root() {
+    let mut test = RvTest::new();
+    let ruby_dir = test.create_ruby_dir("ruby-3.3.5");
+
+    // A ruby shim that reports its `Gem.default_dir`
+    let ruby_exe = ruby_dir.join("bin").join("ruby");
+    let script = std::fs::read_to_string(&ruby_exe)
+        .unwrap()
+        .replace("echo \"\"", "echo \"/gems/ruby/3.3.0\"");
+    std::fs::write(&ruby_exe, script).unwrap();
+
+    test.env.remove("RV_NO_CACHE");
+    let cache_dir = test.temp_dir.path().join("cache");
+    test.env
+        .insert("RV_CACHE_DIR".into(), cache_dir.as_str().into());
+    test.env.insert("PATH".into(), "/tmp/bin".into());
+
+    // The second run reads the gem root back from the interpreter cache.
+    for _ in 0..2 {
+        let output = test.rv(&["shell", "env", "zsh"]);
+        output.assert_success();
+        let stdout = output.normalized_stdout();
+        assert!(stdout.contains("export GEM_ROOT=/gems/ruby/3.3.0\n"));
+        assert!(stdout.contains("export PATH='/gems/ruby/3.3.0/bin:"));
+    }
+
+    let interpreters_dir = cache_dir.join("ruby-v0").join("interpreters");
+    let cached: Vec<serde_json::Value> = std::fs::read_dir(&interpreters_dir)
+        .unwrap()
+        .map(|entry| {
+            let content = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            serde_json::from_str(&content).unwrap()
+        })
+        .collect();
+    assert_eq!(cached.len(), 1);
+    assert_eq!(cached[0]["gem_root"], "/gems/ruby/3.3.0");
+}
+
+#[test]
+fn test_shell_env_unset_all_deactivates_ruby() {
+    let mut test = RvTest::new();
+    let ruby_dir = test.create_ruby_dir("ruby-3.3.5");
+    let gem_root = ruby_dir.join("lib/ruby/gems/3.3.0");
+    test.env.insert(
+        "PATH".into(),
+        format!("{ruby_dir}/bin:{gem_root}/bin:/tmp/bin"),
+    );
+    test.env.insert("RUBY_ROOT".into(), ruby_dir.to_string());
+    test.env.insert("GEM_ROOT".into(), gem_root.to_string());
+    test.env.insert("RUBY_VERSION".into(), "3.3.5".into());
+    std::fs::write(test.cwd.join(".ruby-version"), "3.3.5").unwrap();
+
+    let output = test.rv(&["shell", "env", "bash", "--unset-all"]);
+
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout());
+}
+
+#[test]
+fn test_shell_env_unset_all_without_active_ruby() {
+    let mut test = RvTest::new();
+    test.env.insert("PATH".into(), "/usr/bin:/tmp/bin".into());
+
+    let output = test.rv(&["shell", "env", "bash", "--unset-all"]);
+
+    output.assert_success();
+    assert_snapshot!(output.normalized_stdout());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_env_in_deleted_current_dir() {
+    let test = RvTest::new();
+    let gone = test.cwd.join("gone");
+    std::fs::create_dir(&gone).unwrap();
+
+    // Remove the directory after the shell that execs rv has moved into it.
+    let mut cmd = std::process::Command::new("sh");
+    cmd.current_dir(&test.cwd)
+        .env_clear()
+        .envs(&test.env)
+        .arg("-c")
+        .arg(r#"cd "$1" && rmdir "$1" && exec "$0" shell env zsh"#)
+        .arg(env!("CARGO_BIN_EXE_rv"))
+        .arg(gone.as_str());
+    let output = RvOutput::new(test.temp_dir.path().as_str(), cmd.output().unwrap());
+
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("NoCurrentDir"));
+}
+
+#[test]
+fn test_shell_env_source_from_version_file() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join(".ruby-version"), "3.3").unwrap();
+    test.cwd = project_dir;
+
+    let output = test.rv(&["shell", "env", "zsh", "--source"]);
+    output.assert_success();
+    assert!(
+        output
+            .normalized_stdout()
+            .contains("export RV_RUBY_SOURCE=/project/.ruby-version\n")
+    );
+}
+
+#[test]
+fn test_shell_env_source_from_project_config() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join("rv.toml"), "ruby = \"3.3\"\n").unwrap();
+    test.cwd = project_dir;
+
+    let output = test.rv(&["shell", "env", "fish", "--source"]);
+    output.assert_success();
+    assert!(
+        output
+            .normalized_stdout()
+            .contains("set -gx RV_RUBY_SOURCE \"/project/rv.toml\"\n")
+    );
+}
+
+#[test]
+fn test_shell_env_source_without_active_ruby() {
+    let test = RvTest::new();
+
+    let output = test.rv(&["shell", "env", "bash", "--source"]);
+    output.assert_success();
+    assert!(output.normalized_stdout().contains(" RV_RUBY_SOURCE"));
+    assert!(!output.normalized_stdout().contains("RV_RUBY_SOURCE="));
+
+    let output = test.rv(&["shell", "env", "bash"]);
+    output.assert_success();
+    assert!(!output.normalized_stdout().contains("RV_RUBY_SOURCE"));
+}
+
+#[test]
+fn test_shell_env_no_project_uses_default_ruby() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.5");
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join(".ruby-version"), "3.3").unwrap();
+    test.cwd = project_dir;
+
+    let output = test.rv(&["--no-project", "shell", "env", "zsh", "--source"]);
+    output.assert_success();
+    let stdout = output.normalized_stdout();
+    assert!(stdout.contains("export RUBY_VERSION=3.4.5\n"));
+    assert!(stdout.contains("export RV_RUBY_SOURCE=default\n"));
+}
+
+#[test]
+fn test_shell_env_exports_gemfile() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    test.cwd = project_dir;
+
+    let output = test.rv(&["--gemfile", "Gemfile", "shell", "env", "zsh"]);
+    output.assert_success();
+    assert!(
+        output
+            .normalized_stdout()
+            .contains("export BUNDLE_GEMFILE=/project/Gemfile\n")
+    );
+}
+
+#[test]
+fn test_shell_env_drops_gemfile_of_previous_project() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let project_dir = test.temp_dir.path().join("other-project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    test.cwd = project_dir;
+    // Left behind by activating the first project with `--gemfile`.
+    test.env
+        .insert("BUNDLE_GEMFILE".into(), "/project/Gemfile".into());
+    test.env
+        .insert("RV_BUNDLE_GEMFILE".into(), "/project/Gemfile".into());
+
+    let output = test.rv(&["shell", "env", "zsh"]);
+    output.assert_success();
+    let stdout = output.normalized_stdout();
+    assert!(!stdout.contains("export BUNDLE_GEMFILE"));
+    let unset = stdout
+        .lines()
+        .find(|line| line.starts_with("unset "))
+        .unwrap();
+    assert!(unset.split(' ').any(|var| var == "BUNDLE_GEMFILE"));
+    assert!(unset.split(' ').any(|var| var == "RV_BUNDLE_GEMFILE"));
+
+    // A BUNDLE_GEMFILE the user set is neither used nor unset.
+    test.env.remove("RV_BUNDLE_GEMFILE");
+    let output = test.rv(&["shell", "env", "zsh"]);
+    output.assert_success();
+    assert!(!output.normalized_stdout().contains("BUNDLE_GEMFILE"));
+}
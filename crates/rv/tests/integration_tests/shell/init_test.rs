@@ -10,6 +10,65 @@ fn test_shell_init_succeeds() {
     assert_snapshot!(output.normalized_stdout());
 }
 
+#[test]
+fn test_bash_shell_init_succeeds() {
+    let test = RvTest::new();
+    let output = test.rv(&["shell", "init", "bash"]);
+    output.assert_success();
+
+    assert_snapshot!(output.normalized_stdout(), @r#"
+    if [ -z "${__RV_INITIALIZED:-}" ]; then
+    __RV_INITIALIZED=1
+    _rv_autoload_hook() {
+        eval "$(/tmp/bin/rv shell env bash)"
+    }
+    _rv_autoload_hook
+    _chpwd_hook() {
+        if [[ "$PWD" != "$_OLDPWD" ]]; then
+            _rv_autoload_hook
+            _OLDPWD="$PWD"
+        fi
+    }
+    _OLDPWD="$PWD"
+    PROMPT_COMMAND="_chpwd_hook${PROMPT_COMMAND:+; $PROMPT_COMMAND}"
+    fi
+    "#);
+}
+
+#[test]
+fn test_fish_shell_init_succeeds() {
+    let test = RvTest::new();
+    let output = test.rv(&["shell", "init", "fish"]);
+    output.assert_success();
+
+    assert_snapshot!(output.normalized_stdout(), @r"
+    if not set -q __RV_INITIALIZED
+    set -g __RV_INITIALIZED 1
+    function _rv_autoload_hook --on-variable PWD --description 'Change Ruby version on directory change using rv'
+        status --is-command-substitution; and return
+        /tmp/bin/rv shell env fish | source
+    end
+    _rv_autoload_hook
+    end
+    ");
+}
+
+#[test]
+fn test_shell_init_is_guarded_against_resourcing() {
+    let test = RvTest::new();
+    for shell in ["zsh", "bash", "fish"] {
+        let output = test.rv(&["shell", "init", shell]);
+        output.assert_success();
+
+        let stdout = output.normalized_stdout();
+        assert!(
+            stdout.lines().next().unwrap().contains("__RV_INITIALIZED"),
+            "{shell} init should open with the guard:\n{stdout}"
+        );
+        assert_eq!(stdout.matches("__RV_INITIALIZED").count(), 2, "{shell}");
+    }
+}
+
 #[test]
 fn test_nu_shell_init_succeeds() {
     let test = RvTest::new();
@@ -19,6 +78,15 @@ fn test_nu_shell_init_succeeds() {
     assert_snapshot!(output.normalized_stdout());
 }
 
+#[test]
+fn test_powershell_init_succeeds() {
+    let test = RvTest::new();
+    let output = test.rv(&["shell", "init", "powershell"]);
+    output.assert_success();
+
+    assert_snapshot!(output.normalized_stdout());
+}
+
 #[test]
 fn test_shell_init_fails_without_shell() {
     let test = RvTest::new();
@@ -27,3 +95,26 @@ fn test_shell_init_fails_without_shell() {
 
     assert_eq!(output.normalized_stdout(), "");
 }
+
+#[test]
+fn test_shell_init_print_path() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.env.insert("PATH".into(), "/tmp/bin:/usr/bin".into());
+    let output = test.rv(&["shell", "init", "--print-path"]);
+    output.assert_success();
+
+    assert_snapshot!(output.normalized_stdout(), @r"
+    /tmp/home/.gem/ruby/3.3.5/bin (gem_home bin)
+    /opt/rubies/ruby-3.3.5/bin (ruby bin)
+    /tmp/bin (existing)
+    /usr/bin (existing)
+    ");
+}
+
+#[test]
+fn test_shell_init_requires_shell_or_print_path() {
+    let test = RvTest::new();
+    let output = test.rv(&["shell", "init"]);
+    output.assert_failure();
+}
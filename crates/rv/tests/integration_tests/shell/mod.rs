@@ -1,2 +1,3 @@
+mod completions_test;
 mod env_test;
 mod init_test;